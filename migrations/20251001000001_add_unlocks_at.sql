@@ -0,0 +1,5 @@
+-- Track when an unstaking position's cooldown ends (from InitiateWithdraw.unlocksAt)
+ALTER TABLE positions ADD COLUMN IF NOT EXISTS unlocks_at BIGINT;
+
+-- Keep unlocksAt on the audit trail as well
+ALTER TABLE events ADD COLUMN IF NOT EXISTS unlocks_at BIGINT;
@@ -0,0 +1,11 @@
+-- Periodic snapshots of total staked amount for the analytics dashboard
+CREATE TABLE IF NOT EXISTS tvl_history (
+    id SERIAL PRIMARY KEY,
+    timestamp BIGINT NOT NULL,
+    block_number BIGINT NOT NULL,
+    active_amount NUMERIC(78, 0) NOT NULL,   -- wei
+    unstaking_amount NUMERIC(78, 0) NOT NULL, -- wei
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_tvl_history_timestamp ON tvl_history(timestamp);
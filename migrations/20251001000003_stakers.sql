@@ -0,0 +1,17 @@
+-- First-seen timestamp per staker for growth metrics
+CREATE TABLE IF NOT EXISTS stakers (
+    user_address VARCHAR(42) PRIMARY KEY,
+    first_seen_timestamp BIGINT NOT NULL,
+    first_seen_block BIGINT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_stakers_first_seen ON stakers(first_seen_timestamp);
+
+-- Backfill from existing deposits
+INSERT INTO stakers (user_address, first_seen_timestamp, first_seen_block)
+SELECT user_address, MIN(timestamp), MIN(block_number)
+FROM events
+WHERE event_type = 'Deposit'
+GROUP BY user_address
+ON CONFLICT (user_address) DO NOTHING;
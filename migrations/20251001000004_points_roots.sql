@@ -0,0 +1,11 @@
+-- Points Merkle roots submitted on-chain
+CREATE TABLE IF NOT EXISTS points_roots (
+    id SERIAL PRIMARY KEY,
+    root VARCHAR(66) NOT NULL,
+    block_number BIGINT NOT NULL,
+    leaf_count INTEGER NOT NULL,
+    transaction_hash VARCHAR(66) NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_points_roots_block ON points_roots(block_number);
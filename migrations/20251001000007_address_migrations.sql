@@ -0,0 +1,20 @@
+-- Admin migrations moving a user's points to a new address (lost wallets)
+CREATE TABLE IF NOT EXISTS address_migrations (
+    id SERIAL PRIMARY KEY,
+    from_address VARCHAR(42) NOT NULL UNIQUE,
+    to_address VARCHAR(42) NOT NULL,
+    include_positions BOOLEAN NOT NULL DEFAULT FALSE,
+    reason TEXT NOT NULL DEFAULT '',
+    migrated_at BIGINT NOT NULL,
+    CHECK (from_address <> to_address)
+);
+
+-- Audit log for admin operations
+CREATE TABLE IF NOT EXISTS admin_audit_log (
+    id SERIAL PRIMARY KEY,
+    action VARCHAR(50) NOT NULL,
+    details TEXT NOT NULL,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX idx_admin_audit_log_action ON admin_audit_log(action);
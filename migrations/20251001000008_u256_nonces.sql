@@ -0,0 +1,3 @@
+-- Nonces are uint256 on chain; store them losslessly instead of as BIGINT
+ALTER TABLE positions ALTER COLUMN nonce TYPE NUMERIC(78, 0);
+ALTER TABLE events ALTER COLUMN nonce TYPE NUMERIC(78, 0);
@@ -0,0 +1,2 @@
+-- Look up events by transaction for /tx/{hash}
+CREATE INDEX IF NOT EXISTS idx_events_tx ON events(transaction_hash);
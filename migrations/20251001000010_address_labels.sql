@@ -0,0 +1,7 @@
+-- Known owners of addresses (exchanges, funds, partners), shown in alerts
+CREATE TABLE IF NOT EXISTS address_labels (
+    address VARCHAR(42) PRIMARY KEY,
+    label TEXT NOT NULL,
+    category TEXT,
+    updated_at BIGINT NOT NULL
+);
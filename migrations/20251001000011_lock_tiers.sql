@@ -0,0 +1,3 @@
+-- Lock duration chosen at deposit (seconds, 0 for V1 deposits), drives lock-tier multipliers
+ALTER TABLE positions ADD COLUMN IF NOT EXISTS lock_duration BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE events ADD COLUMN IF NOT EXISTS lock_duration BIGINT;
@@ -0,0 +1,12 @@
+-- On-chain Delegate events; who earns delegated points is a settings switch
+CREATE TABLE IF NOT EXISTS delegations (
+    id SERIAL PRIMARY KEY,
+    delegator VARCHAR(42) NOT NULL,
+    delegatee VARCHAR(42) NOT NULL, -- Zero address revokes
+    block_number BIGINT NOT NULL,
+    transaction_hash VARCHAR(66) NOT NULL,
+    timestamp BIGINT NOT NULL,
+    UNIQUE (transaction_hash, delegator)
+);
+
+CREATE INDEX IF NOT EXISTS idx_delegations_delegatee ON delegations(delegatee);
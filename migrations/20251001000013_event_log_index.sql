@@ -0,0 +1,4 @@
+-- Position of each event within its block, so same-block events replay in chain order
+ALTER TABLE events ADD COLUMN IF NOT EXISTS log_index BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_events_block_log ON events(block_number, log_index);
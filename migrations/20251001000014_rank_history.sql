@@ -0,0 +1,12 @@
+-- Every points owner's rank at each rank snapshot, for "climbed N places" messaging
+CREATE TABLE IF NOT EXISTS rank_history (
+    id SERIAL PRIMARY KEY,
+    timestamp BIGINT NOT NULL,
+    block_number BIGINT NOT NULL,
+    address VARCHAR(42) NOT NULL,
+    rank INTEGER NOT NULL,
+    total_points DOUBLE PRECISION NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_rank_history_address ON rank_history(address, timestamp);
+CREATE INDEX IF NOT EXISTS idx_rank_history_block ON rank_history(block_number);
@@ -0,0 +1,9 @@
+-- Per-user notification channels and the events they subscribed to, set through SIWE-protected endpoints
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    address VARCHAR(42) PRIMARY KEY,
+    webhook_url TEXT,
+    discord_user_id VARCHAR(32),
+    email TEXT,
+    events TEXT NOT NULL DEFAULT '', -- Comma-separated event types
+    updated_at BIGINT NOT NULL
+);
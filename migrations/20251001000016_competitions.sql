@@ -0,0 +1,21 @@
+-- Weekly and monthly competition periods, archived once they end
+CREATE TABLE IF NOT EXISTS competition_periods (
+    board VARCHAR(16) NOT NULL, -- weekly or monthly
+    period BIGINT NOT NULL,
+    period_start BIGINT NOT NULL,
+    period_end BIGINT NOT NULL,
+    archived_at BIGINT NOT NULL,
+    PRIMARY KEY (board, period)
+);
+
+-- Final top places of each archived period
+CREATE TABLE IF NOT EXISTS competition_winners (
+    board VARCHAR(16) NOT NULL,
+    period BIGINT NOT NULL,
+    rank INTEGER NOT NULL,
+    address VARCHAR(42) NOT NULL,
+    sage_points DOUBLE PRECISION NOT NULL,
+    formation_points DOUBLE PRECISION NOT NULL,
+    total_points DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (board, period, rank)
+);
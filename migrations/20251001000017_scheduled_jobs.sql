@@ -0,0 +1,12 @@
+-- Leases and last runs of scheduled jobs, shared by every instance on the database
+CREATE TABLE IF NOT EXISTS scheduled_jobs (
+    name VARCHAR(64) PRIMARY KEY,
+    locked_by VARCHAR(128), -- Instance running the job
+    locked_until BIGINT,
+    last_started_at BIGINT,
+    last_finished_at BIGINT,
+    last_status VARCHAR(16), -- ok or failed
+    last_message TEXT,
+    last_duration_ms BIGINT,
+    run_count BIGINT NOT NULL DEFAULT 0
+);
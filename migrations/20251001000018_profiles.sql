@@ -0,0 +1,7 @@
+-- Public display names and avatars users set through SIWE-protected endpoints, shown on the leaderboard
+CREATE TABLE IF NOT EXISTS profiles (
+    address VARCHAR(42) PRIMARY KEY,
+    display_name VARCHAR(64),
+    avatar_url TEXT,
+    updated_at BIGINT NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Whole rank snapshots are read by timestamp when diffing two of them
+CREATE INDEX IF NOT EXISTS idx_rank_history_timestamp ON rank_history(timestamp);
@@ -0,0 +1,46 @@
+-- Create positions table (stores all positions)
+CREATE TABLE IF NOT EXISTS positions (
+    user_address TEXT NOT NULL,
+    nonce INTEGER NOT NULL,
+    amount TEXT NOT NULL, -- Store as wei decimal string
+    deposit_timestamp INTEGER NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('active', 'unstaking', 'withdrawn')),
+    withdrawal_initiated_timestamp INTEGER,
+    block_number INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_address, nonce)
+);
+
+-- Create indexes for efficient querying
+CREATE INDEX idx_positions_status ON positions(status);
+CREATE INDEX idx_positions_user ON positions(user_address);
+CREATE INDEX idx_positions_block ON positions(block_number);
+
+-- Create metadata table for tracking sync state
+CREATE TABLE IF NOT EXISTS sync_metadata (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Insert default last block
+INSERT INTO sync_metadata (key, value) VALUES ('last_processed_block', '0') 
+ON CONFLICT (key) DO NOTHING;
+
+-- Create events table for audit trail
+CREATE TABLE IF NOT EXISTS events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_type TEXT NOT NULL,
+    user_address TEXT NOT NULL,
+    nonce INTEGER,
+    amount TEXT,
+    block_number INTEGER NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_events_user ON events(user_address);
+CREATE INDEX idx_events_block ON events(block_number);
+CREATE INDEX idx_events_type ON events(event_type);
@@ -0,0 +1,11 @@
+-- Periodic snapshots of total staked amount for the analytics dashboard
+CREATE TABLE IF NOT EXISTS tvl_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp INTEGER NOT NULL,
+    block_number INTEGER NOT NULL,
+    active_amount TEXT NOT NULL,   -- wei
+    unstaking_amount TEXT NOT NULL, -- wei
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_tvl_history_timestamp ON tvl_history(timestamp);
@@ -0,0 +1,11 @@
+-- Points Merkle roots submitted on-chain
+CREATE TABLE IF NOT EXISTS points_roots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    root TEXT NOT NULL,
+    block_number INTEGER NOT NULL,
+    leaf_count INTEGER NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_points_roots_block ON points_roots(block_number);
@@ -0,0 +1,7 @@
+-- Emission rate history; each row applies from effective_from until the next one
+CREATE TABLE IF NOT EXISTS rate_periods (
+    effective_from INTEGER PRIMARY KEY,
+    sage_rate REAL NOT NULL,
+    formation_rate REAL NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,9 @@
+-- Time windows during which accrual is globally paused (maintenance, exploit response)
+CREATE TABLE IF NOT EXISTS pause_windows (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    start_timestamp INTEGER NOT NULL,
+    end_timestamp INTEGER NOT NULL,
+    reason TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CHECK (end_timestamp > start_timestamp)
+);
@@ -0,0 +1,20 @@
+-- Admin migrations moving a user's points to a new address (lost wallets)
+CREATE TABLE IF NOT EXISTS address_migrations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    from_address TEXT NOT NULL UNIQUE,
+    to_address TEXT NOT NULL,
+    include_positions INTEGER NOT NULL DEFAULT 0,
+    reason TEXT NOT NULL DEFAULT '',
+    migrated_at INTEGER NOT NULL,
+    CHECK (from_address <> to_address)
+);
+
+-- Audit log for admin operations
+CREATE TABLE IF NOT EXISTS admin_audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    action TEXT NOT NULL,
+    details TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX idx_admin_audit_log_action ON admin_audit_log(action);
@@ -0,0 +1,54 @@
+-- Nonces are uint256 on chain; store them losslessly as decimal strings.
+-- SQLite cannot change a column type in place, so rebuild both tables.
+CREATE TABLE positions_new (
+    user_address TEXT NOT NULL,
+    nonce TEXT NOT NULL,
+    amount TEXT NOT NULL, -- Store as wei decimal string
+    deposit_timestamp INTEGER NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('active', 'unstaking', 'withdrawn')),
+    withdrawal_initiated_timestamp INTEGER,
+    block_number INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    unlocks_at INTEGER,
+    PRIMARY KEY (user_address, nonce)
+);
+
+INSERT INTO positions_new
+    (user_address, nonce, amount, deposit_timestamp, status, withdrawal_initiated_timestamp,
+     block_number, created_at, updated_at, unlocks_at)
+SELECT user_address, CAST(nonce AS TEXT), amount, deposit_timestamp, status, withdrawal_initiated_timestamp,
+       block_number, created_at, updated_at, unlocks_at
+FROM positions;
+
+DROP TABLE positions;
+ALTER TABLE positions_new RENAME TO positions;
+
+CREATE INDEX idx_positions_status ON positions(status);
+CREATE INDEX idx_positions_user ON positions(user_address);
+CREATE INDEX idx_positions_block ON positions(block_number);
+
+CREATE TABLE events_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_type TEXT NOT NULL,
+    user_address TEXT NOT NULL,
+    nonce TEXT,
+    amount TEXT,
+    block_number INTEGER NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    unlocks_at INTEGER
+);
+
+INSERT INTO events_new
+    (id, event_type, user_address, nonce, amount, block_number, transaction_hash, timestamp, created_at, unlocks_at)
+SELECT id, event_type, user_address, CAST(nonce AS TEXT), amount, block_number, transaction_hash, timestamp, created_at, unlocks_at
+FROM events;
+
+DROP TABLE events;
+ALTER TABLE events_new RENAME TO events;
+
+CREATE INDEX idx_events_user ON events(user_address);
+CREATE INDEX idx_events_block ON events(block_number);
+CREATE INDEX idx_events_type ON events(event_type);
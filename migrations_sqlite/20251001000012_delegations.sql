@@ -0,0 +1,12 @@
+-- On-chain Delegate events; who earns delegated points is a settings switch
+CREATE TABLE IF NOT EXISTS delegations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    delegator TEXT NOT NULL,
+    delegatee TEXT NOT NULL, -- Zero address revokes
+    block_number INTEGER NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    UNIQUE (transaction_hash, delegator)
+);
+
+CREATE INDEX IF NOT EXISTS idx_delegations_delegatee ON delegations(delegatee);
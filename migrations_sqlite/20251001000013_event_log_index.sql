@@ -0,0 +1,4 @@
+-- Position of each event within its block, so same-block events replay in chain order
+ALTER TABLE events ADD COLUMN log_index INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_events_block_log ON events(block_number, log_index);
@@ -0,0 +1,12 @@
+-- Every points owner's rank at each rank snapshot, for "climbed N places" messaging
+CREATE TABLE IF NOT EXISTS rank_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp INTEGER NOT NULL,
+    block_number INTEGER NOT NULL,
+    address TEXT NOT NULL,
+    rank INTEGER NOT NULL,
+    total_points REAL NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_rank_history_address ON rank_history(address, timestamp);
+CREATE INDEX IF NOT EXISTS idx_rank_history_block ON rank_history(block_number);
@@ -0,0 +1,9 @@
+-- Per-user notification channels and the events they subscribed to, set through SIWE-protected endpoints
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    address TEXT PRIMARY KEY,
+    webhook_url TEXT,
+    discord_user_id TEXT,
+    email TEXT,
+    events TEXT NOT NULL DEFAULT '', -- Comma-separated event types
+    updated_at INTEGER NOT NULL
+);
@@ -0,0 +1,21 @@
+-- Weekly and monthly competition periods, archived once they end
+CREATE TABLE IF NOT EXISTS competition_periods (
+    board TEXT NOT NULL, -- weekly or monthly
+    period INTEGER NOT NULL,
+    period_start INTEGER NOT NULL,
+    period_end INTEGER NOT NULL,
+    archived_at INTEGER NOT NULL,
+    PRIMARY KEY (board, period)
+);
+
+-- Final top places of each archived period
+CREATE TABLE IF NOT EXISTS competition_winners (
+    board TEXT NOT NULL,
+    period INTEGER NOT NULL,
+    rank INTEGER NOT NULL,
+    address TEXT NOT NULL,
+    sage_points REAL NOT NULL,
+    formation_points REAL NOT NULL,
+    total_points REAL NOT NULL,
+    PRIMARY KEY (board, period, rank)
+);
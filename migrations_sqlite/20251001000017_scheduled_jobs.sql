@@ -0,0 +1,12 @@
+-- Leases and last runs of scheduled jobs, shared by every instance on the database
+CREATE TABLE IF NOT EXISTS scheduled_jobs (
+    name TEXT PRIMARY KEY,
+    locked_by TEXT, -- Instance running the job
+    locked_until INTEGER,
+    last_started_at INTEGER,
+    last_finished_at INTEGER,
+    last_status TEXT, -- ok or failed
+    last_message TEXT,
+    last_duration_ms INTEGER,
+    run_count INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,7 @@
+-- Public display names and avatars users set through SIWE-protected endpoints, shown on the leaderboard
+CREATE TABLE IF NOT EXISTS profiles (
+    address TEXT PRIMARY KEY,
+    display_name TEXT,
+    avatar_url TEXT,
+    updated_at INTEGER NOT NULL
+);
@@ -0,0 +1,167 @@
+use alloy::primitives::{Address, U256};
+use eyre::{eyre, Result};
+use serde_json::json;
+
+use crate::db::Database;
+use crate::{format_token_amount, format_token_amount_as_float};
+
+/// Outgoing alert channels shared by the watchdog and whale alerts
+#[derive(Clone)]
+pub struct Notifier {
+    client: reqwest::Client,
+    webhook_url: Option<String>,        // Discord webhooks get a Discord payload, anything else generic JSON
+    telegram: Option<(String, String)>, // (bot token, chat id)
+}
+
+impl Notifier {
+    /// Read alert channels from the environment
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+        Self {
+            client: reqwest::Client::new(),
+            webhook_url: var("ALERT_WEBHOOK_URL"),
+            telegram: var("TELEGRAM_BOT_TOKEN").zip(var("TELEGRAM_CHAT_ID")),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.webhook_url.is_some() || self.telegram.is_some()
+    }
+
+    /// Send `message` to every configured channel. `details` (a JSON object) is
+    /// merged into generic webhook payloads. Fails if any channel failed.
+    pub async fn send(&self, message: &str, details: serde_json::Value) -> Result<()> {
+        let mut errors = Vec::new();
+
+        if let Some(url) = &self.webhook_url {
+            let body = if url.contains("discord.com/api/webhooks") {
+                json!({ "content": message })
+            } else {
+                let mut body = json!({ "text": message, "service": "points-calculator" });
+                if let (Some(body), Some(details)) = (body.as_object_mut(), details.as_object()) {
+                    body.extend(details.clone());
+                }
+                body
+            };
+            if let Err(e) = self.post(url, &body).await {
+                errors.push(format!("webhook: {}", e));
+            }
+        }
+
+        if let Some((token, chat_id)) = &self.telegram {
+            let url = format!("https://api.telegram.org/bot{}/sendMessage", token);
+            if let Err(e) = self.post(&url, &json!({ "chat_id": chat_id, "text": message })).await {
+                errors.push(format!("telegram: {}", e.without_url()));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!(errors.join(", ")))
+        }
+    }
+
+    async fn post(&self, url: &str, body: &serde_json::Value) -> reqwest::Result<()> {
+        self.client.post(url).json(body).send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Kind of flow a whale alert is about
+#[derive(Debug, Clone, Copy)]
+pub enum Flow {
+    Deposit,
+    Unstake, // Withdrawal initiated, the earliest sign of an outflow
+}
+
+/// Whale alert thresholds in tokens, from `WHALE_DEPOSIT_THRESHOLD` and
+/// `WHALE_UNSTAKE_THRESHOLD` (read by `config::load`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WhaleAlertConfig {
+    pub deposit_threshold: Option<f64>,
+    pub unstake_threshold: Option<f64>,
+}
+
+/// Notifications for deposits and unstakes at or above configured token amounts
+#[derive(Clone)]
+pub struct WhaleAlerts {
+    deposit_threshold: Option<f64>, // Tokens
+    unstake_threshold: Option<f64>, // Tokens
+    notifier: Notifier,
+}
+
+impl WhaleAlerts {
+    /// Alerts sent through `notifier`, None when no threshold is set
+    pub fn new(config: WhaleAlertConfig, notifier: Notifier) -> Option<Self> {
+        if config.deposit_threshold.is_none() && config.unstake_threshold.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            deposit_threshold: config.deposit_threshold,
+            unstake_threshold: config.unstake_threshold,
+            notifier,
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        let show = |t: Option<f64>| t.map_or("off".to_string(), |t| format!("≥ {} tokens", t));
+        format!("deposits {}, unstakes {}", show(self.deposit_threshold), show(self.unstake_threshold))
+    }
+
+    /// Alert in the background if `amount` (wei) crosses the threshold for `flow`.
+    /// The address label is looked up in `db` when available.
+    pub fn check(&self, db: Option<Database>, flow: Flow, user: Address, amount: U256, tx_hash: String) {
+        let threshold = match flow {
+            Flow::Deposit => self.deposit_threshold,
+            Flow::Unstake => self.unstake_threshold,
+        };
+        let tokens = format_token_amount_as_float(amount);
+        if !threshold.is_some_and(|t| tokens >= t) {
+            return;
+        }
+
+        let notifier = self.notifier.clone();
+        tokio::spawn(async move {
+            let label = match &db {
+                Some(db) => db.get_address_label(&user).await.unwrap_or_else(|e| {
+                    eprintln!("⚠️  Failed to look up address label: {}", e);
+                    None
+                }),
+                None => None,
+            };
+
+            let who = match &label {
+                Some(label) => match &label.category {
+                    Some(category) => format!("{} ({}, {})", user, label.label, category),
+                    None => format!("{} ({})", user, label.label),
+                },
+                None => user.to_string(),
+            };
+            let message = match flow {
+                Flow::Deposit => format!("🐋 Whale deposit: {} tokens from {}\nhttps://basescan.org/tx/{}",
+                    format_token_amount(amount), who, tx_hash),
+                Flow::Unstake => format!("🐋 Whale unstake: {} tokens by {}\nhttps://basescan.org/tx/{}",
+                    format_token_amount(amount), who, tx_hash),
+            };
+            println!("{}", message);
+
+            let details = json!({
+                "whale": {
+                    "flow": format!("{:?}", flow).to_lowercase(),
+                    "user_address": user.to_string(),
+                    "label": label.as_ref().map(|l| &l.label),
+                    "category": label.as_ref().and_then(|l| l.category.as_ref()),
+                    "amount": tokens,
+                    "amount_wei": amount.to_string(),
+                    "transaction_hash": tx_hash,
+                }
+            });
+            if let Err(e) = notifier.send(&message, details).await {
+                eprintln!("⚠️  Failed to send whale alert: {}", e);
+            }
+        });
+    }
+}
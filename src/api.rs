@@ -1,20 +1,383 @@
 use actix_cors::Cors;
-use actix_web::{get, web, App, HttpResponse, HttpServer, Result};
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::error::InternalError;
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use actix_web::http::StatusCode;
+use actix_web::middleware::{from_fn, DefaultHeaders, Logger, Next};
+use actix_web::{delete, get, post, put, web, App, FromRequest, HttpMessage, HttpRequest, HttpResponse, HttpServer, Result};
+use alloy::primitives::{Address, B256, U256};
 use serde::{Deserialize, Serialize};
+use std::future::{ready, Ready};
+use std::str::FromStr;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use crate::db::{Database, LeaderboardEntry, UserEvent, UserPoints};
+use crate::cache::{Cached, SwrCache};
+use crate::points::{CompetitionBoard, DisplayPoints, SharedRules};
+use crate::profiles::{Profile, ProfileModeration, Profiles};
+use crate::request_id::{self, request_eprintln};
+use crate::ens::{self, EnsResolver};
+use crate::db::{allocation_from, competition_from, emissions_from, leaderboard_from, statement_from, user_points_from, Database, LeaderboardSort, LeaderboardWindow, SnapshotDiffSort};
+use crate::notifications::NotificationPreferences;
+use crate::scheduler::Scheduler;
+use crate::siwe::SiweAuth;
+use crate::watchdog::{Freshness, IndexerStatus};
+use crate::{format_token_amount_as_float, Position, PositionStatus, SharedTracker};
+
+// Header used by clients to request a version and by the server to report it
+const API_VERSION_HEADER: &str = "api-version";
+
+// Header carrying the key for quest-platform verification endpoints
+const API_KEY_HEADER: &str = "x-api-key";
+
+// Headers naming the ENS name in the request and the address it resolved to
+const ENS_NAME_HEADER: &str = "x-ens-name";
+const RESOLVED_ADDRESS_HEADER: &str = "x-resolved-address";
+
+// Header strict clients send to get a 503 instead of stale data
+const REQUIRE_FRESH_HEADER: &str = "x-require-fresh";
+
+tokio::task_local! {
+    // Indexer freshness when the current request started, echoed in success envelopes
+    static FRESHNESS: Freshness;
+    // Display scaling of the program being served, applied when success envelopes are serialized
+    static DISPLAY_POINTS: DisplayPoints;
+}
+
+/// Indexer lag in seconds after which responses are flagged stale
+#[derive(Debug, Clone, Copy)]
+struct StaleAfter(u64);
+
+/// Longest an API request may run before it is cancelled with a 504
+#[derive(Debug, Clone, Copy)]
+struct QueryTimeout(Duration);
+
+/// API server settings, checked by `config::load`
+#[derive(Debug, Clone, Copy)]
+pub struct ApiConfig {
+    pub port: u16,
+    pub cache_ttl_secs: u64,   // From `API_CACHE_TTL_SECS`
+    pub session_ttl_secs: u64, // From `SIWE_SESSION_TTL_SECS`
+    pub rate_limit: Option<u32>, // Requests per minute per client, from `API_RATE_LIMIT`
+    pub stale_after_secs: u64, // From `STALE_AFTER_SECS`
+    pub query_timeout: Duration, // From `API_QUERY_TIMEOUT_SECS`, also the statement timeout of API queries
+}
+
+// Response payloads of the slower aggregate endpoints, served stale while refreshing
+type ResponseCache = Arc<SwrCache<serde_json::Value>>;
+
+/// Storage, rules and indexer of an additional points program, served under `/v1/{name}`
+#[derive(Clone)]
+pub struct ProgramState {
+    pub name: String,
+    pub db: Database,
+    pub rules: SharedRules,
+    pub tracker: SharedTracker,
+    pub status: Arc<IndexerStatus>,
+}
+
+// Comma separated keys from `name`, empty when unset
+fn api_keys_from_env(name: &str) -> Vec<String> {
+    std::env::var(name)
+        .ok()
+        .map(|keys| keys.split(',').map(str::trim).filter(|k| !k.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Keys accepted by the verification endpoints, from `VERIFY_API_KEYS` (comma separated)
+#[derive(Debug, Clone, Default)]
+struct VerifyApiKeys(Vec<String>);
+
+impl VerifyApiKeys {
+    fn from_env() -> Self {
+        Self(api_keys_from_env("VERIFY_API_KEYS"))
+    }
+}
+
+/// Keys accepted by the operator endpoints, from `ADMIN_API_KEYS` (comma separated)
+#[derive(Debug, Clone, Default)]
+struct AdminApiKeys(Vec<String>);
+
+impl AdminApiKeys {
+    fn from_env() -> Self {
+        Self(api_keys_from_env("ADMIN_API_KEYS"))
+    }
+}
+
+/// Response shape version negotiated for a request.
+/// Handlers that change shape between versions take this as an extractor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ApiVersion {
+    V1,
+}
+
+impl ApiVersion {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().trim_start_matches(['v', 'V']) {
+            "1" => Some(Self::V1),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::V1 => "1",
+        }
+    }
+}
+
+impl FromRequest for ApiVersion {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Ok(req.extensions().get::<ApiVersion>().copied().unwrap_or(ApiVersion::V1)))
+    }
+}
+
+/// Address signed in with SIWE, from an `Authorization: Bearer <token>` session.
+/// Handlers for per-user settings take this as an extractor.
+pub struct SiweSession(pub Address);
+
+impl FromRequest for SiweSession {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let session = req.headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .zip(req.app_data::<web::Data<SiweAuth>>())
+            .and_then(|(token, auth)| auth.session(token.trim()));
+
+        ready(match session {
+            Some(session) => Ok(Self(session.address)),
+            None => {
+                let detail = "Missing or expired session, sign in through /auth/siwe";
+                Err(InternalError::from_response(detail, problem(ErrorCode::Unauthorized, detail)).into())
+            }
+        })
+    }
+}
 
 // Request/response structures
 #[derive(Debug, Serialize)]
+#[serde(bound(serialize = "T: Serialize"))]
 struct ApiResponse<T> {
     success: bool,
+    #[serde(serialize_with = "serialize_with_display_points")]
     data: Option<T>,
     error: Option<String>,
+    #[serde(flatten)]
+    freshness: Option<Freshness>,
 }
 
 #[derive(Debug, Deserialize)]
 struct LeaderboardQuery {
     limit: Option<i64>,
+    #[serde(default)]
+    sort: LeaderboardSort,
+    #[serde(default)]
+    window: LeaderboardWindow,
+}
+
+#[derive(Debug, Deserialize)]
+struct WithdrawableQuery {
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TvlQuery {
+    interval: Option<String>,
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StakerMetricsQuery {
+    days: Option<i64>,
+}
+
+/// Representation of a points statement
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum StatementFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+struct StatementQuery {
+    #[serde(default)]
+    format: StatementFormat,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmissionsQuery {
+    days: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RankHistoryQuery {
+    days: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SnapshotDiffQuery {
+    from: u64, // Snapshot timestamps, as listed by /snapshots
+    to: u64,
+    #[serde(default)]
+    sort: SnapshotDiffSort,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiKeyQuery {
+    api_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RequireFreshQuery {
+    #[serde(default)]
+    require_fresh: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct VerifyStakedQuery {
+    address: String,
+    min_amount: Option<f64>, // Tokens, default any non-zero stake
+    min_days: Option<f64>,   // Default 0
+}
+
+#[derive(Debug, Deserialize)]
+struct VerifyPointsQuery {
+    address: String,
+    min_points: Option<f64>, // Default any non-zero points
+}
+
+#[derive(Debug, Serialize)]
+struct SiweNonce {
+    nonce: String,
+    domain: String, // Domain the message must be issued for
+}
+
+#[derive(Debug, Deserialize)]
+struct SiweSignIn {
+    message: String,   // EIP-4361 message as signed
+    signature: String, // 0x-prefixed 65-byte personal_sign signature
+}
+
+#[derive(Debug, Serialize)]
+struct SiweToken {
+    token: String, // Send as `Authorization: Bearer <token>`
+    address: String,
+    expires_at: u64,
+}
+
+/// Eligibility answer in the flat shape quest platforms (Galxe, Zealy) read
+#[derive(Debug, Serialize)]
+struct VerifyResult {
+    result: bool,
+    address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    staked_amount: Option<f64>, // Tokens staked for at least `min_days`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_points: Option<f64>,
+}
+
+/// Machine-readable error codes returned in problem responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ErrorCode {
+    InvalidAddress,
+    InvalidParameter,
+    Unauthorized,
+    NotFound,
+    StaleData,
+    UnsupportedVersion,
+    RateLimited,
+    Timeout,
+    Internal,
+}
+
+impl ErrorCode {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::InvalidAddress => "INVALID_ADDRESS",
+            Self::InvalidParameter => "INVALID_PARAMETER",
+            Self::Unauthorized => "UNAUTHORIZED",
+            Self::NotFound => "NOT_FOUND",
+            Self::StaleData => "STALE_DATA",
+            Self::UnsupportedVersion => "UNSUPPORTED_VERSION",
+            Self::RateLimited => "RATE_LIMITED",
+            Self::Timeout => "TIMEOUT",
+            Self::Internal => "INTERNAL_ERROR",
+        }
+    }
+
+    fn status(self) -> StatusCode {
+        match self {
+            Self::InvalidAddress | Self::InvalidParameter | Self::UnsupportedVersion => StatusCode::BAD_REQUEST,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::StaleData => StatusCode::SERVICE_UNAVAILABLE,
+            Self::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            Self::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::InvalidAddress => "Invalid address",
+            Self::InvalidParameter => "Invalid parameter",
+            Self::Unauthorized => "Unauthorized",
+            Self::NotFound => "Not found",
+            Self::StaleData => "Data is stale",
+            Self::UnsupportedVersion => "Unsupported API version",
+            Self::RateLimited => "Too many requests",
+            Self::Timeout => "Request timed out",
+            Self::Internal => "Internal server error",
+        }
+    }
+}
+
+/// RFC 7807 problem details body
+#[derive(Debug, Serialize)]
+struct Problem {
+    #[serde(rename = "type")]
+    problem_type: String,
+    title: &'static str,
+    status: u16,
+    detail: String,
+    code: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    // Legacy envelope fields so existing `success`/`error` checks keep working
+    success: bool,
+    error: String,
+}
+
+// Build an `application/problem+json` error response
+fn problem(code: ErrorCode, detail: impl Into<String>) -> HttpResponse {
+    let detail = detail.into();
+    let status = code.status();
+
+    HttpResponse::build(status)
+        .content_type("application/problem+json")
+        .json(Problem {
+            problem_type: format!("urn:sage-points:error:{}", code.as_str().to_lowercase().replace('_', "-")),
+            title: code.title(),
+            status: status.as_u16(),
+            detail: detail.clone(),
+            code: code.as_str(),
+            request_id: request_id::current(),
+            success: false,
+            error: detail,
+        })
 }
 
 impl<T> ApiResponse<T> {
@@ -23,101 +386,1204 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            freshness: FRESHNESS.try_with(|f| f.clone()).ok(),
         }
     }
+}
 
-    fn error(error: String) -> Self {
-        Self {
-            success: false,
-            data: None,
-            error: Some(error),
+// Payload as is, or with `<field>_display` values added when the program configures display scaling
+fn serialize_with_display_points<T: Serialize, S: serde::Serializer>(data: &Option<T>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    match DISPLAY_POINTS.try_with(|d| d.clone()) {
+        Ok(display) => {
+            let mut value = serde_json::to_value(data).map_err(serde::ser::Error::custom)?;
+            display.annotate(&mut value);
+            value.serialize(serializer)
+        }
+        Err(_) => data.serialize(serializer),
+    }
+}
+
+// Successful response from a cached payload, with headers telling clients how old it is
+fn cached_response(cached: Cached<serde_json::Value>) -> HttpResponse {
+    let as_of: chrono::DateTime<chrono::Utc> = cached.computed_at.into();
+    HttpResponse::Ok()
+        .insert_header(("X-Data-As-Of", as_of.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)))
+        .insert_header((header::AGE, cached.age().as_secs().to_string()))
+        .json(ApiResponse::success(cached.value.as_ref()))
+}
+
+// Parse a 0x-prefixed address in any letter case; stores and responses use the checksummed form
+fn parse_address(address: &str) -> Option<Address> {
+    if !address.starts_with("0x") || address.len() != 42 {
+        return None;
+    }
+    Address::from_str(address).ok()
+}
+
+// ENS name resolved while serving a request, echoed back in response headers
+#[derive(Debug, Clone)]
+struct ResolvedName {
+    name: String,
+    address: Address,
+}
+
+// Address named by a path or query value: a 0x address in any letter case, or an ENS
+// name resolved through the mainnet provider. Failures come back as ready responses.
+async fn lookup_address(req: &HttpRequest, input: &str) -> std::result::Result<Address, HttpResponse> {
+    if let Some(address) = parse_address(input) {
+        return Ok(address);
+    }
+
+    let name = input.to_lowercase();
+    if !ens::is_ens_name(&name) {
+        return Err(problem(ErrorCode::InvalidAddress, "Invalid address format"));
+    }
+    let Some(resolver) = req.app_data::<web::Data<EnsResolver>>() else {
+        return Err(problem(ErrorCode::InvalidAddress, "ENS names are not supported, use a 0x address"));
+    };
+
+    match resolver.resolve(&name).await {
+        Ok(Some(address)) => {
+            req.extensions_mut().insert(ResolvedName { name, address });
+            Ok(address)
+        }
+        Ok(None) => Err(problem(ErrorCode::NotFound, format!("{} does not resolve to an address", name))),
+        Err(e) => {
+            request_eprintln!("Error resolving {}: {}", name, e);
+            Err(problem(ErrorCode::Internal, "Failed to resolve ENS name"))
         }
     }
 }
 
 // Get user points endpoint
-#[get("/api/points/{address}")]
+#[get("/points/{address}")]
 async fn get_user_points(
+    req: HttpRequest,
     address: web::Path<String>,
     db: web::Data<Database>,
+    rules: web::Data<SharedRules>,
+    tracker: web::Data<SharedTracker>,
 ) -> Result<HttpResponse> {
-    let address = address.into_inner();
-    
-    // Basic validation - check if it looks like an Ethereum address
-    if !address.starts_with("0x") || address.len() != 42 {
-        return Ok(HttpResponse::BadRequest().json(ApiResponse::<UserPoints>::error(
-            "Invalid address format".to_string()
-        )));
+    let address = match lookup_address(&req, &address).await {
+        Ok(address) => address,
+        Err(response) => return Ok(response),
+    };
+
+    let rules = rules.read().unwrap().clone();
+
+    // Answer from the indexer's memory, or the database while it is busy writing
+    if let Ok(tracker) = tracker.try_read() {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let points = user_points_from(address, tracker.positions(), &rules, now);
+        return Ok(HttpResponse::Ok().json(ApiResponse::success(points)));
     }
 
-    match db.get_user_points(&address).await {
+    match db.get_user_points(address, &rules).await {
         Ok(points) => Ok(HttpResponse::Ok().json(ApiResponse::success(points))),
         Err(e) => {
-            eprintln!("Error getting user points: {}", e);
-            Ok(HttpResponse::InternalServerError().json(ApiResponse::<UserPoints>::error(
-                "Failed to fetch user points".to_string()
-            )))
+            request_eprintln!("Error getting user points: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch user points"))
         }
     }
 }
 
+// Line-item statement of how an address earned its points, as JSON or a CSV download
+#[get("/points/{address}/statement")]
+async fn get_points_statement(
+    req: HttpRequest,
+    address: web::Path<String>,
+    query: web::Query<StatementQuery>,
+    db: web::Data<Database>,
+    rules: web::Data<SharedRules>,
+    tracker: web::Data<SharedTracker>,
+) -> Result<HttpResponse> {
+    let address = match lookup_address(&req, &address).await {
+        Ok(address) => address,
+        Err(response) => return Ok(response),
+    };
+
+    let rules = rules.read().unwrap().clone();
+
+    let in_memory = tracker.try_read().ok().map(|tracker| {
+        let now = chrono::Utc::now().timestamp() as u64;
+        statement_from(address, tracker.positions(), &rules, now)
+    });
+    let statement = match in_memory {
+        Some(statement) => statement,
+        None => match db.get_points_statement(address, &rules).await {
+            Ok(statement) => statement,
+            Err(e) => {
+                request_eprintln!("Error building points statement: {}", e);
+                return Ok(problem(ErrorCode::Internal, "Failed to build points statement"));
+            }
+        },
+    };
+
+    match query.format {
+        StatementFormat::Json => Ok(HttpResponse::Ok().json(ApiResponse::success(statement))),
+        StatementFormat::Csv => Ok(HttpResponse::Ok()
+            .content_type("text/csv")
+            .insert_header((
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"points-statement-{}.csv\"", statement.address),
+            ))
+            .body(statement.to_csv())),
+    }
+}
+
 // Get user events endpoint
-#[get("/api/events/{address}")]
+#[get("/events/{address}")]
 async fn get_user_events(
+    req: HttpRequest,
     address: web::Path<String>,
     db: web::Data<Database>,
+    rules: web::Data<SharedRules>,
 ) -> Result<HttpResponse> {
-    let address = address.into_inner();
-    
-    // Basic validation
-    if !address.starts_with("0x") || address.len() != 42 {
-        return Ok(HttpResponse::BadRequest().json(ApiResponse::<Vec<UserEvent>>::error(
-            "Invalid address format".to_string()
-        )));
+    let address = match lookup_address(&req, &address).await {
+        Ok(address) => address,
+        Err(response) => return Ok(response),
+    };
+
+    let rules = rules.read().unwrap().clone();
+    match db.get_attributed_events(address, &rules).await {
+        Ok(events) => Ok(HttpResponse::Ok().json(ApiResponse::success(events))),
+        Err(e) => {
+            request_eprintln!("Error getting user events: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch user events"))
+        }
+    }
+}
+
+// Get user positions endpoint
+#[get("/positions/{address}")]
+async fn get_user_positions(
+    req: HttpRequest,
+    address: web::Path<String>,
+    db: web::Data<Database>,
+    rules: web::Data<SharedRules>,
+) -> Result<HttpResponse> {
+    let address = match lookup_address(&req, &address).await {
+        Ok(address) => address,
+        Err(response) => return Ok(response),
+    };
+
+    let rules = rules.read().unwrap().clone();
+    match db.get_attributed_positions(address, &rules).await {
+        Ok(positions) => Ok(HttpResponse::Ok().json(ApiResponse::success(positions))),
+        Err(e) => {
+            request_eprintln!("Error getting user positions: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch user positions"))
+        }
+    }
+}
+
+// Get profile page summary endpoint
+#[get("/summary/{address}")]
+async fn get_user_summary(
+    req: HttpRequest,
+    address: web::Path<String>,
+    db: web::Data<Database>,
+    rules: web::Data<SharedRules>,
+) -> Result<HttpResponse> {
+    let address = match lookup_address(&req, &address).await {
+        Ok(address) => address,
+        Err(response) => return Ok(response),
+    };
+
+    let rules = rules.read().unwrap().clone();
+    match db.get_user_summary(address, 10, &rules).await {
+        Ok(summary) => Ok(HttpResponse::Ok().json(ApiResponse::success(summary))),
+        Err(e) => {
+            request_eprintln!("Error getting user summary: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch user summary"))
+        }
+    }
+}
+
+// Get single position lifecycle endpoint
+#[get("/positions/{address}/{nonce}")]
+async fn get_position_detail(
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+    db: web::Data<Database>,
+    rules: web::Data<SharedRules>,
+) -> Result<HttpResponse> {
+    let (address, nonce) = path.into_inner();
+    let address = match lookup_address(&req, &address).await {
+        Ok(address) => address,
+        Err(response) => return Ok(response),
+    };
+    let Ok(nonce) = U256::from_str_radix(&nonce, 10) else {
+        return Ok(problem(ErrorCode::InvalidParameter, "Invalid nonce, expected a decimal integer"));
+    };
+
+    let rules = rules.read().unwrap().clone();
+    match db.get_position_detail(address, nonce, &rules).await {
+        Ok(Some(detail)) => Ok(HttpResponse::Ok().json(ApiResponse::success(detail))),
+        Ok(None) => Ok(problem(ErrorCode::NotFound, format!("No position {} for {}", nonce, address))),
+        Err(e) => {
+            request_eprintln!("Error getting position detail: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch position"))
+        }
+    }
+}
+
+// Get withdrawable positions for a user endpoint
+#[get("/withdrawable/{address}")]
+async fn get_user_withdrawable(
+    req: HttpRequest,
+    address: web::Path<String>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let address = match lookup_address(&req, &address).await {
+        Ok(address) => address,
+        Err(response) => return Ok(response),
+    };
+
+    match db.get_withdrawable_positions(Some(&address.to_string()), 1000).await {
+        Ok(positions) => Ok(HttpResponse::Ok().json(ApiResponse::success(positions))),
+        Err(e) => {
+            request_eprintln!("Error getting withdrawable positions: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch withdrawable positions"))
+        }
+    }
+}
+
+// Get all withdrawable positions endpoint (ops view)
+#[get("/withdrawable")]
+async fn get_all_withdrawable(
+    query: web::Query<WithdrawableQuery>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let limit = query.limit.unwrap_or(100).min(1000); // Default 100, max 1000
+
+    match db.get_withdrawable_positions(None, limit).await {
+        Ok(positions) => Ok(HttpResponse::Ok().json(ApiResponse::success(positions))),
+        Err(e) => {
+            request_eprintln!("Error getting withdrawable positions: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch withdrawable positions"))
+        }
     }
+}
+
+// Get staking events of a transaction endpoint
+#[get("/tx/{hash}")]
+async fn get_tx_events(
+    hash: web::Path<String>,
+    db: web::Data<Database>,
+    rules: web::Data<SharedRules>,
+) -> Result<HttpResponse> {
+    let Ok(hash) = B256::from_str(&hash) else {
+        return Ok(problem(ErrorCode::InvalidParameter, "Invalid transaction hash"));
+    };
 
-    match db.get_user_events(&address).await {
+    let rules = rules.read().unwrap().clone();
+    match db.get_tx_events(&hash.to_string(), &rules).await {
+        Ok(events) if events.is_empty() => Ok(problem(
+            ErrorCode::NotFound,
+            format!("No staking events indexed for transaction {}", hash),
+        )),
         Ok(events) => Ok(HttpResponse::Ok().json(ApiResponse::success(events))),
         Err(e) => {
-            eprintln!("Error getting user events: {}", e);
-            Ok(HttpResponse::InternalServerError().json(ApiResponse::<Vec<UserEvent>>::error(
-                "Failed to fetch user events".to_string()
-            )))
+            request_eprintln!("Error getting transaction events: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch transaction events"))
+        }
+    }
+}
+
+// Get TVL history endpoint
+#[get("/tvl")]
+async fn get_tvl(
+    query: web::Query<TvlQuery>,
+    db: web::Data<Database>,
+    cache: web::Data<ResponseCache>,
+) -> Result<HttpResponse> {
+    let interval = query.interval.as_deref().unwrap_or("hour");
+    if interval != "hour" && interval != "day" {
+        return Ok(problem(ErrorCode::InvalidParameter, "Invalid interval, expected 'hour' or 'day'"));
+    }
+    let limit = query.limit.unwrap_or(168).min(1000); // Default 168 buckets, max 1000
+
+    let key = format!("tvl:{}:{}", interval, limit);
+    let (db, interval) = (db.get_ref().clone(), interval.to_string());
+    let result = cache.get_ref().get(key, move || async move {
+        let points = db.get_tvl_history(&interval, limit).await?;
+        Ok::<_, eyre::Report>(serde_json::to_value(points)?)
+    }).await;
+
+    match result {
+        Ok(cached) => Ok(cached_response(cached)),
+        Err(e) => {
+            request_eprintln!("Error getting TVL history: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch TVL history"))
+        }
+    }
+}
+
+// Get unique stakers and growth metrics endpoint
+#[get("/metrics/stakers")]
+async fn get_staker_metrics(
+    query: web::Query<StakerMetricsQuery>,
+    db: web::Data<Database>,
+    cache: web::Data<ResponseCache>,
+) -> Result<HttpResponse> {
+    let days = query.days.unwrap_or(30).clamp(1, 365); // Default 30 days, max 365
+
+    let db = db.get_ref().clone();
+    let result = cache.get_ref().get(format!("stakers:{}", days), move || async move {
+        let metrics = db.get_staker_metrics(days).await?;
+        Ok::<_, eyre::Report>(serde_json::to_value(metrics)?)
+    }).await;
+
+    match result {
+        Ok(cached) => Ok(cached_response(cached)),
+        Err(e) => {
+            request_eprintln!("Error getting staker metrics: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch staker metrics"))
+        }
+    }
+}
+
+// Points emitted per day, by point type and source
+#[get("/metrics/emissions")]
+async fn get_emissions(
+    query: web::Query<EmissionsQuery>,
+    db: web::Data<Database>,
+    rules: web::Data<SharedRules>,
+    tracker: web::Data<SharedTracker>,
+    cache: web::Data<ResponseCache>,
+) -> Result<HttpResponse> {
+    let days = query.days.unwrap_or(30).clamp(1, 365); // Default 30 days, max 365
+
+    let (db, rules, tracker) = (db.get_ref().clone(), rules.get_ref().clone(), tracker.get_ref().clone());
+    let result = cache.get_ref().get(format!("emissions:{}", days), move || async move {
+        let rules = rules.read().unwrap().clone();
+
+        let in_memory = tracker.try_read().ok().map(|tracker| {
+            let now = chrono::Utc::now().timestamp() as u64;
+            emissions_from(tracker.positions(), days, &rules, now)
+        });
+        let emissions = match in_memory {
+            Some(emissions) => emissions,
+            None => db.get_emissions(days, &rules).await?,
+        };
+        Ok::<_, eyre::Report>(serde_json::to_value(emissions)?)
+    }).await;
+
+    match result {
+        Ok(cached) => Ok(cached_response(cached)),
+        Err(e) => {
+            request_eprintln!("Error getting emissions: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to compute emissions"))
         }
     }
 }
 
 // Get leaderboard endpoint
-#[get("/api/leaderboard")]
+#[get("/leaderboard")]
 async fn get_leaderboard(
     query: web::Query<LeaderboardQuery>,
     db: web::Data<Database>,
+    rules: web::Data<SharedRules>,
+    tracker: web::Data<SharedTracker>,
+    cache: web::Data<ResponseCache>,
+    profiles: web::Data<Profiles>,
 ) -> Result<HttpResponse> {
     let limit = query.limit.unwrap_or(10).min(100); // Default 10, max 100
-    
-    match db.get_leaderboard(limit).await {
-        Ok(leaderboard) => Ok(HttpResponse::Ok().json(ApiResponse::success(leaderboard))),
+    let (sort, window) = (query.sort, query.window);
+
+    let key = format!("leaderboard:{}:{:?}:{:?}", limit, sort, window);
+    let (db, rules, tracker, profiles) = (db.get_ref().clone(), rules.get_ref().clone(), tracker.get_ref().clone(), profiles.get_ref().clone());
+    let result = cache.get_ref().get(key, move || async move {
+        let rules = rules.read().unwrap().clone();
+
+        // Answer from the indexer's memory, or the database while it is busy writing
+        let in_memory = tracker.try_read().ok().map(|tracker| {
+            let now = chrono::Utc::now().timestamp() as u64;
+            leaderboard_from(tracker.positions(), limit, sort, window, &rules, now)
+        });
+        let mut leaderboard = match in_memory {
+            Some(leaderboard) => leaderboard,
+            None => db.get_leaderboard(limit, sort, window, &rules).await?,
+        };
+        // The board still works without names
+        if let Err(e) = profiles.attach(&mut leaderboard).await {
+            eprintln!("⚠️  Failed to load profiles for the leaderboard: {}", e);
+        }
+        Ok::<_, eyre::Report>(serde_json::to_value(leaderboard)?)
+    }).await;
+
+    match result {
+        Ok(cached) => Ok(cached_response(cached)),
         Err(e) => {
-            eprintln!("Error getting leaderboard: {}", e);
-            Ok(HttpResponse::InternalServerError().json(ApiResponse::<Vec<LeaderboardEntry>>::error(
-                "Failed to fetch leaderboard".to_string()
-            )))
+            request_eprintln!("Error getting leaderboard: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch leaderboard"))
         }
     }
 }
 
-// Health check endpoint
+// Weekly or monthly competition standings, `{period}` being a 1-based period number
+// or `current`. Archived winners once a period ended, live standings before that.
+#[get("/leaderboard/{board}/{period}")]
+async fn get_competition(
+    path: web::Path<(String, String)>,
+    db: web::Data<Database>,
+    rules: web::Data<SharedRules>,
+    tracker: web::Data<SharedTracker>,
+    cache: web::Data<ResponseCache>,
+) -> Result<HttpResponse> {
+    let (board, period) = path.into_inner();
+    let Some(board) = CompetitionBoard::parse(&board) else {
+        return Ok(problem(ErrorCode::NotFound, "Competition boards are weekly and monthly"));
+    };
+    let Some(config) = rules.read().unwrap().competitions.clone() else {
+        return Ok(problem(ErrorCode::NotFound, "No competitions are configured"));
+    };
+
+    let now = chrono::Utc::now().timestamp() as u64;
+    let period = match period.as_str() {
+        "current" => match config.period_at(board, now) {
+            Some(period) => period,
+            None => return Ok(problem(ErrorCode::NotFound, "Competitions have not started yet")),
+        },
+        number => match number.parse::<u64>() {
+            Ok(period) if period >= 1 => period,
+            _ => return Ok(problem(ErrorCode::InvalidParameter, "period must be a positive number or 'current'")),
+        },
+    };
+    if config.period_bounds(board, period).is_none_or(|(start, _)| start > now) {
+        return Ok(problem(ErrorCode::NotFound, format!("{} competition #{} has not started", board.as_str(), period)));
+    }
+
+    match db.load_competition_standings(board, period).await {
+        Ok(Some(archived)) => return Ok(HttpResponse::Ok().json(ApiResponse::success(archived))),
+        Ok(None) => {}
+        Err(e) => {
+            request_eprintln!("Error getting competition: {}", e);
+            return Ok(problem(ErrorCode::Internal, "Failed to fetch competition"));
+        }
+    }
+
+    let key = format!("competition:{}:{}", board.as_str(), period);
+    let (db, rules, tracker) = (db.get_ref().clone(), rules.get_ref().clone(), tracker.get_ref().clone());
+    let result = cache.get_ref().get(key, move || async move {
+        let rules = rules.read().unwrap().clone();
+
+        // Answer from the indexer's memory, or the database while it is busy writing
+        let in_memory = tracker.try_read().ok().map(|tracker| {
+            let now = chrono::Utc::now().timestamp() as u64;
+            competition_from(tracker.positions(), board, period, &rules, now)
+        });
+        let standings = match in_memory {
+            Some(standings) => standings,
+            None => db.get_competition_standings(board, period, &rules).await?,
+        };
+        Ok::<_, eyre::Report>(serde_json::to_value(standings)?)
+    }).await;
+
+    match result {
+        Ok(cached) => Ok(cached_response(cached)),
+        Err(e) => {
+            request_eprintln!("Error getting competition: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch competition"))
+        }
+    }
+}
+
+// Quest-platform check: does the address have at least `min_amount` tokens in active
+// positions deposited at least `min_days` ago
+#[get("/staked")]
+async fn verify_staked(
+    req: HttpRequest,
+    query: web::Query<VerifyStakedQuery>,
+    db: web::Data<Database>,
+    rules: web::Data<SharedRules>,
+    tracker: web::Data<SharedTracker>,
+) -> Result<HttpResponse> {
+    let address = match lookup_address(&req, &query.address).await {
+        Ok(address) => address,
+        Err(response) => return Ok(response),
+    };
+    let (min_amount, min_days) = (query.min_amount.unwrap_or(0.0), query.min_days.unwrap_or(0.0));
+    if !(min_amount.is_finite() && min_amount >= 0.0 && min_days.is_finite() && min_days >= 0.0) {
+        return Ok(problem(ErrorCode::InvalidParameter, "min_amount and min_days must be non-negative numbers"));
+    }
+
+    // Positions held by the address, including history migrated to it
+    let sources = rules.read().unwrap().addresses_owned_by(address, true);
+    let positions: Vec<Position> = match tracker.try_read() {
+        Ok(tracker) => tracker.positions().filter(|p| sources.contains(&p.user)).cloned().collect(),
+        Err(_) => {
+            let mut positions = Vec::new();
+            for source in &sources {
+                match db.fetch_positions(Some(&source.to_string())).await {
+                    Ok(found) => positions.extend(found),
+                    Err(e) => {
+                        request_eprintln!("Error verifying stake: {}", e);
+                        return Ok(problem(ErrorCode::Internal, "Failed to fetch positions"));
+                    }
+                }
+            }
+            positions
+        }
+    };
+
+    let now = chrono::Utc::now().timestamp() as u64;
+    let deposited_by = now.saturating_sub((min_days * 86400.0) as u64);
+    let staked = positions.iter()
+        .filter(|p| matches!(p.status, PositionStatus::Active) && p.deposit_timestamp <= deposited_by)
+        .fold(U256::ZERO, |acc, p| acc + p.amount);
+    let staked_amount = format_token_amount_as_float(staked);
+
+    Ok(HttpResponse::Ok().json(VerifyResult {
+        result: staked > U256::ZERO && staked_amount >= min_amount,
+        address: address.to_string(),
+        staked_amount: Some(staked_amount),
+        total_points: None,
+    }))
+}
+
+// Quest-platform check: has the address earned at least `min_points` in total
+#[get("/points")]
+async fn verify_points(
+    req: HttpRequest,
+    query: web::Query<VerifyPointsQuery>,
+    db: web::Data<Database>,
+    rules: web::Data<SharedRules>,
+    tracker: web::Data<SharedTracker>,
+) -> Result<HttpResponse> {
+    let address = match lookup_address(&req, &query.address).await {
+        Ok(address) => address,
+        Err(response) => return Ok(response),
+    };
+    let min_points = query.min_points.unwrap_or(0.0);
+    if !(min_points.is_finite() && min_points >= 0.0) {
+        return Ok(problem(ErrorCode::InvalidParameter, "min_points must be a non-negative number"));
+    }
+
+    let rules = rules.read().unwrap().clone();
+    let points = match tracker.try_read() {
+        Ok(tracker) => user_points_from(address, tracker.positions(), &rules, chrono::Utc::now().timestamp() as u64),
+        Err(_) => match db.get_user_points(address, &rules).await {
+            Ok(points) => points,
+            Err(e) => {
+                request_eprintln!("Error verifying points: {}", e);
+                return Ok(problem(ErrorCode::Internal, "Failed to fetch user points"));
+            }
+        },
+    };
+
+    Ok(HttpResponse::Ok().json(VerifyResult {
+        result: points.total_points > 0.0 && points.total_points >= min_points,
+        address: address.to_string(),
+        staked_amount: None,
+        total_points: Some(points.total_points),
+    }))
+}
+
+// Get rank history endpoint
+#[get("/rank/{address}/history")]
+async fn get_rank_history(
+    req: HttpRequest,
+    address: web::Path<String>,
+    query: web::Query<RankHistoryQuery>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let address = match lookup_address(&req, &address).await {
+        Ok(address) => address,
+        Err(response) => return Ok(response),
+    };
+    let days = query.days.unwrap_or(30).clamp(1, 365); // Default 30 days, max 365
+
+    match db.get_rank_history(address, days).await {
+        Ok(history) => Ok(HttpResponse::Ok().json(ApiResponse::success(history))),
+        Err(e) => {
+            request_eprintln!("Error getting rank history: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch rank history"))
+        }
+    }
+}
+
+// Stored rank snapshots, newest first
+#[get("/snapshots")]
+async fn get_snapshots(db: web::Data<Database>) -> Result<HttpResponse> {
+    match db.list_rank_snapshots().await {
+        Ok(snapshots) => Ok(HttpResponse::Ok().json(ApiResponse::success(snapshots))),
+        Err(e) => {
+            request_eprintln!("Error listing rank snapshots: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to list snapshots"))
+        }
+    }
+}
+
+// Per-owner point deltas and rank changes between two rank snapshots, paginated
+#[get("/snapshots/diff")]
+async fn get_snapshot_diff(query: web::Query<SnapshotDiffQuery>, db: web::Data<Database>) -> Result<HttpResponse> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 500); // Default 50, max 500
+    let offset = query.offset.unwrap_or(0);
+
+    match db.get_snapshot_diff(query.from, query.to, query.sort, offset, limit).await {
+        Ok(Some(diff)) => Ok(HttpResponse::Ok().json(ApiResponse::success(diff))),
+        Ok(None) => Ok(problem(
+            ErrorCode::NotFound,
+            format!("No rank snapshot at {} or {}, see /snapshots for the stored ones", query.from, query.to),
+        )),
+        Err(e) => {
+            request_eprintln!("Error diffing snapshots: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to diff snapshots"))
+        }
+    }
+}
+
+// Projected token allocation endpoint
+#[get("/allocation/{address}")]
+async fn get_allocation(
+    req: HttpRequest,
+    address: web::Path<String>,
+    db: web::Data<Database>,
+    rules: web::Data<SharedRules>,
+    tracker: web::Data<SharedTracker>,
+) -> Result<HttpResponse> {
+    let address = match lookup_address(&req, &address).await {
+        Ok(address) => address,
+        Err(response) => return Ok(response),
+    };
+
+    let rules = rules.read().unwrap().clone();
+    if rules.allocation.is_none() {
+        return Ok(problem(ErrorCode::NotFound, "No allocation is configured"));
+    }
+
+    // Answer from the indexer's memory, or the database while it is busy writing
+    if let Ok(tracker) = tracker.try_read() {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let allocation = allocation_from(address, tracker.positions(), &rules, now);
+        return Ok(HttpResponse::Ok().json(ApiResponse::success(allocation)));
+    }
+
+    match db.get_allocation(address, &rules).await {
+        Ok(allocation) => Ok(HttpResponse::Ok().json(ApiResponse::success(allocation))),
+        Err(e) => {
+            request_eprintln!("Error getting allocation: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to compute allocation"))
+        }
+    }
+}
+
+// Program parameters in effect right now, so frontends don't hardcode them
+#[get("/program")]
+async fn get_program(rules: web::Data<SharedRules>) -> Result<HttpResponse> {
+    let now = chrono::Utc::now().timestamp() as u64;
+    let program = rules.read().unwrap().program_info(now);
+    Ok(HttpResponse::Ok().json(ApiResponse::success(program)))
+}
+
+// Health check endpoint, unhealthy while the indexer is stalled
 #[get("/health")]
-async fn health() -> Result<HttpResponse> {
-    Ok(HttpResponse::Ok().json(serde_json::json!({
-        "status": "healthy",
-        "service": "points-calculator"
+async fn health(status: web::Data<Arc<IndexerStatus>>) -> Result<HttpResponse> {
+    let indexer = status.health();
+    let body = serde_json::json!({
+        "status": if indexer.stalled { "stalled" } else { "healthy" },
+        "service": "points-calculator",
+        "indexer": indexer
+    });
+
+    if indexer.stalled {
+        Ok(HttpResponse::ServiceUnavailable().json(body))
+    } else {
+        Ok(HttpResponse::Ok().json(body))
+    }
+}
+
+// Fallback for unknown routes
+async fn not_found(req: HttpRequest) -> HttpResponse {
+    problem(ErrorCode::NotFound, format!("No route for {}", req.path()))
+}
+
+// Resolve the API version from the `/vN` path prefix, or from the `Api-Version`
+// header on unversioned routes, and echo it back on the response
+async fn negotiate_version(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>> {
+    let path_version = req.path()
+        .strip_prefix("/v")
+        .and_then(|rest| rest.split('/').next())
+        .and_then(ApiVersion::parse);
+
+    let requested = req.headers()
+        .get(API_VERSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let version = match path_version {
+        Some(version) => version,
+        None => match requested {
+            Some(requested) => match ApiVersion::parse(&requested) {
+                Some(version) => version,
+                None => {
+                    let response = problem(
+                        ErrorCode::UnsupportedVersion,
+                        format!("Unsupported API version '{}'", requested),
+                    );
+                    return Ok(req.into_response(response).map_into_right_body());
+                }
+            },
+            // Legacy routes stay on v1 so existing consumers never see new shapes
+            None => ApiVersion::V1,
+        },
+    };
+
+    req.extensions_mut().insert(version);
+    let mut res = next.call(req).await?;
+    res.headers_mut().insert(
+        HeaderName::from_static(API_VERSION_HEADER),
+        HeaderValue::from_static(version.as_str()),
+    );
+
+    Ok(res.map_into_left_body())
+}
+
+// Key sent in the `X-API-Key` header or the `api_key` query parameter
+fn provided_api_key(req: &ServiceRequest) -> Option<String> {
+    req.headers()
+        .get(API_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .or_else(|| web::Query::<ApiKeyQuery>::from_query(req.query_string()).ok().and_then(|q| q.into_inner().api_key))
+}
+
+// Only let requests with a configured verification key through
+async fn require_api_key(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>> {
+    let authorized = match (req.app_data::<web::Data<VerifyApiKeys>>(), provided_api_key(&req)) {
+        (Some(keys), Some(key)) => keys.0.contains(&key),
+        _ => false,
+    };
+    if !authorized {
+        let response = problem(ErrorCode::Unauthorized, "Missing or invalid API key");
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+
+    Ok(next.call(req).await?.map_into_left_body())
+}
+
+/// Requests per client in fixed one-minute windows, for `API_RATE_LIMIT`
+#[derive(Clone)]
+struct RateLimiter {
+    per_minute: u32,
+    window: Arc<Mutex<(u64, HashMap<String, u32>)>>, // Current minute, requests per client in it
+}
+
+impl RateLimiter {
+    fn new(per_minute: u32) -> Self {
+        Self { per_minute, window: Arc::default() }
+    }
+
+    // Count a request from `client` at `now`, the seconds until the next window when it is over the limit
+    fn check(&self, client: &str, now: u64) -> Option<u64> {
+        let mut window = self.window.lock().unwrap();
+        let (minute, requests) = &mut *window;
+        if *minute != now / 60 {
+            *minute = now / 60;
+            requests.clear();
+        }
+        let count = requests.entry(client.to_string()).or_default();
+        *count += 1;
+        (*count > self.per_minute).then(|| 60 - now % 60)
+    }
+}
+
+// Answer clients over the rate limit with 429, health checks are never limited
+async fn rate_limit(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>> {
+    let limiter = req.app_data::<web::Data<RateLimiter>>().cloned();
+    if let Some(limiter) = limiter.filter(|_| req.path() != "/health") {
+        // Behind a proxy, the first X-Forwarded-For address
+        let client = req.connection_info().realip_remote_addr().unwrap_or("unknown").to_string();
+        let now = chrono::Utc::now().timestamp() as u64;
+        if let Some(retry_after) = limiter.check(&client, now) {
+            let detail = format!("More than {} requests per minute, retry in {} seconds", limiter.per_minute, retry_after);
+            let mut response = problem(ErrorCode::RateLimited, detail);
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+            return Ok(req.into_response(response).map_into_right_body());
+        }
+    }
+
+    Ok(next.call(req).await?.map_into_left_body())
+}
+
+// Only let requests with a configured admin key through
+async fn require_admin_key(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>> {
+    let authorized = match (req.app_data::<web::Data<AdminApiKeys>>(), provided_api_key(&req)) {
+        (Some(keys), Some(key)) => keys.0.contains(&key),
+        _ => false,
+    };
+    if !authorized {
+        let response = problem(ErrorCode::Unauthorized, "Missing or invalid admin API key");
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+
+    Ok(next.call(req).await?.map_into_left_body())
+}
+
+// Answer 504 when a request outlives the query timeout. Dropping the handler cancels
+// the queries it is waiting on, just like a client disconnecting does.
+async fn limit_request_time(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>> {
+    let Some(timeout) = req.app_data::<web::Data<QueryTimeout>>().map(|t| t.0) else {
+        return Ok(next.call(req).await?.map_into_left_body());
+    };
+
+    let request = req.request().clone();
+    match tokio::time::timeout(timeout, next.call(req)).await {
+        Ok(res) => Ok(res?.map_into_left_body()),
+        Err(_) => {
+            request_eprintln!("{} {} cancelled after {}s", request.method(), request.path(), timeout.as_secs());
+            let response = problem(ErrorCode::Timeout, format!("The request took longer than {}s and was cancelled", timeout.as_secs()));
+            Ok(ServiceResponse::new(request, response).map_into_right_body())
+        }
+    }
+}
+
+// Attach indexer freshness to success envelopes, and answer 503 instead when the
+// data is stale and the client asked for fresh data only (`X-Require-Fresh: true`
+// header or `require_fresh=true` query parameter)
+async fn annotate_freshness(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>> {
+    let (status, stale_after) = match (req.app_data::<web::Data<Arc<IndexerStatus>>>(), req.app_data::<web::Data<StaleAfter>>()) {
+        (Some(status), Some(stale_after)) => (status.clone(), stale_after.0),
+        _ => return Ok(next.call(req).await?.map_into_left_body()),
+    };
+    let freshness = status.freshness(stale_after);
+
+    let require_fresh = req.headers()
+        .get(REQUIRE_FRESH_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+        || web::Query::<RequireFreshQuery>::from_query(req.query_string()).map(|q| q.require_fresh).unwrap_or(false);
+
+    if freshness.stale && require_fresh {
+        let response = problem(
+            ErrorCode::StaleData,
+            format!(
+                "Indexed data is {}s behind the chain (last indexed block {})",
+                freshness.lag_seconds, freshness.last_indexed_block
+            ),
+        );
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+
+    Ok(FRESHNESS.scope(freshness, next.call(req)).await?.map_into_left_body())
+}
+
+// Scale points for display in success envelopes when the program's settings configure it
+async fn scale_display_points(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>> {
+    let display = req.app_data::<web::Data<SharedRules>>().and_then(|rules| rules.read().unwrap().display.clone());
+    match display {
+        Some(display) => DISPLAY_POINTS.scope(display, next.call(req)).await,
+        None => next.call(req).await,
+    }
+}
+
+// Echo the ENS name a handler resolved and its address, so clients see what was looked up
+async fn expose_resolved_name(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>> {
+    let mut res = next.call(req).await?;
+
+    let resolved = res.request().extensions().get::<ResolvedName>().cloned();
+    if let Some(resolved) = resolved {
+        if let Ok(name) = HeaderValue::from_str(&resolved.name) {
+            res.headers_mut().insert(HeaderName::from_static(ENS_NAME_HEADER), name);
+        }
+        if let Ok(address) = HeaderValue::from_str(&resolved.address.to_string()) {
+            res.headers_mut().insert(HeaderName::from_static(RESOLVED_ADDRESS_HEADER), address);
+        }
+    }
+
+    Ok(res)
+}
+
+// Nonce to embed in a Sign-In with Ethereum message
+#[get("/auth/nonce")]
+async fn get_siwe_nonce(auth: Option<web::Data<SiweAuth>>) -> Result<HttpResponse> {
+    let Some(auth) = auth else {
+        return Ok(problem(ErrorCode::NotFound, "Sign-in is not enabled"));
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::success(SiweNonce {
+        nonce: auth.issue_nonce(),
+        domain: auth.domain().to_string(),
     })))
 }
 
+// Exchange a signed SIWE message for a session token
+#[post("/auth/siwe")]
+async fn siwe_sign_in(body: web::Json<SiweSignIn>, auth: Option<web::Data<SiweAuth>>) -> Result<HttpResponse> {
+    let Some(auth) = auth else {
+        return Ok(problem(ErrorCode::NotFound, "Sign-in is not enabled"));
+    };
+
+    match auth.sign_in(&body.message, &body.signature) {
+        Ok((token, session)) => Ok(HttpResponse::Ok().json(ApiResponse::success(SiweToken {
+            token,
+            address: session.address.to_string(),
+            expires_at: session.expires_at,
+        }))),
+        Err(e) => Ok(problem(ErrorCode::Unauthorized, format!("Sign-in failed: {}", e))),
+    }
+}
+
+// Notification channels and events of the signed-in user
+#[get("/notifications")]
+async fn get_notification_preferences(session: SiweSession, db: web::Data<Database>) -> Result<HttpResponse> {
+    match db.get_notification_preferences(&session.0).await {
+        Ok(Some(preferences)) => Ok(HttpResponse::Ok().json(ApiResponse::success(preferences))),
+        Ok(None) => Ok(problem(ErrorCode::NotFound, "No notification preferences saved")),
+        Err(e) => {
+            request_eprintln!("Error getting notification preferences: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch notification preferences"))
+        }
+    }
+}
+
+// Replace the notification channels and events of the signed-in user
+#[put("/notifications")]
+async fn put_notification_preferences(
+    session: SiweSession,
+    body: web::Json<NotificationPreferences>,
+    db: web::Data<Database>,
+) -> Result<HttpResponse> {
+    let mut preferences = body.into_inner();
+    preferences.address = session.0;
+    preferences.events.sort();
+    preferences.events.dedup();
+    if let Err(e) = preferences.validate() {
+        return Ok(problem(ErrorCode::InvalidParameter, e.to_string()));
+    }
+
+    match db.save_notification_preferences(&preferences).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(ApiResponse::success(preferences))),
+        Err(e) => {
+            request_eprintln!("Error saving notification preferences: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to save notification preferences"))
+        }
+    }
+}
+
+// Stop all notifications for the signed-in user
+#[delete("/notifications")]
+async fn delete_notification_preferences(session: SiweSession, db: web::Data<Database>) -> Result<HttpResponse> {
+    match db.delete_notification_preferences(&session.0).await {
+        Ok(true) => Ok(HttpResponse::NoContent().finish()),
+        Ok(false) => Ok(problem(ErrorCode::NotFound, "No notification preferences saved")),
+        Err(e) => {
+            request_eprintln!("Error deleting notification preferences: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to delete notification preferences"))
+        }
+    }
+}
+
+// Scheduled jobs with their schedules, leases and last runs
+#[get("/jobs")]
+async fn get_jobs(scheduler: web::Data<Arc<Scheduler>>) -> Result<HttpResponse> {
+    match scheduler.status().await {
+        Ok(jobs) => Ok(HttpResponse::Ok().json(ApiResponse::success(jobs))),
+        Err(e) => {
+            request_eprintln!("Error loading job states: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch job states"))
+        }
+    }
+}
+
+// Public profile of the signed-in user
+#[get("/profile")]
+async fn get_profile(session: SiweSession, profiles: web::Data<Profiles>) -> Result<HttpResponse> {
+    match profiles.db.get_profile(&session.0).await {
+        Ok(Some(profile)) => Ok(HttpResponse::Ok().json(ApiResponse::success(profile))),
+        Ok(None) => Ok(problem(ErrorCode::NotFound, "No profile saved")),
+        Err(e) => {
+            request_eprintln!("Error getting profile: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to fetch profile"))
+        }
+    }
+}
+
+// Set the display name and avatar of the signed-in user, after validation and moderation
+#[put("/profile")]
+async fn put_profile(session: SiweSession, body: web::Json<Profile>, profiles: web::Data<Profiles>) -> Result<HttpResponse> {
+    let mut profile = body.into_inner();
+    profile.address = session.0;
+    profile.normalize();
+    if let Err(e) = profile.validate() {
+        return Ok(problem(ErrorCode::InvalidParameter, e.to_string()));
+    }
+
+    match profiles.moderation.review(&profile).await {
+        Ok(None) => {}
+        Ok(Some(reason)) => return Ok(problem(ErrorCode::InvalidParameter, format!("Profile rejected: {}", reason))),
+        Err(e) => {
+            request_eprintln!("Profile moderation failed: {}", e);
+            return Ok(problem(ErrorCode::Internal, "Profile moderation is unavailable, try again later"));
+        }
+    }
+
+    match profiles.db.save_profile(&profile).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(ApiResponse::success(profile))),
+        Err(e) => {
+            request_eprintln!("Error saving profile: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to save profile"))
+        }
+    }
+}
+
+// Remove the profile of the signed-in user, the leaderboard shows the bare address again
+#[delete("/profile")]
+async fn delete_profile(session: SiweSession, profiles: web::Data<Profiles>) -> Result<HttpResponse> {
+    match profiles.db.delete_profile(&session.0).await {
+        Ok(true) => Ok(HttpResponse::NoContent().finish()),
+        Ok(false) => Ok(problem(ErrorCode::NotFound, "No profile saved")),
+        Err(e) => {
+            request_eprintln!("Error deleting profile: {}", e);
+            Ok(problem(ErrorCode::Internal, "Failed to delete profile"))
+        }
+    }
+}
+
+// Operator endpoints behind `ADMIN_API_KEYS`, main deployment only
+fn configure_admin(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/admin")
+            .wrap(from_fn(require_admin_key))
+            .service(get_jobs)
+    );
+}
+
+// Sign-in and per-user settings, main program only. Registered ahead of the program
+// routes, whose catch-all inner scope would otherwise take these paths
+fn configure_accounts(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_siwe_nonce)
+        .service(siwe_sign_in)
+        .service(get_notification_preferences)
+        .service(put_notification_preferences)
+        .service(delete_notification_preferences)
+        .service(get_profile)
+        .service(put_profile)
+        .service(delete_profile);
+}
+
+// Routes of one program, with indexer freshness and display points attached. Wrapped in an inner scope so
+// the middleware sees program data registered on the enclosing scope
+fn configure_program(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("")
+            .wrap(from_fn(annotate_freshness))
+            .wrap(from_fn(scale_display_points))
+            .wrap(from_fn(expose_resolved_name))
+            .wrap(from_fn(limit_request_time))
+            .configure(configure_routes)
+    );
+}
+
+// Routes served by every API version
+fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_user_points)
+        .service(get_points_statement)
+        .service(get_user_events)
+        .service(get_user_positions)
+        .service(get_position_detail)
+        .service(get_user_withdrawable)
+        .service(get_all_withdrawable)
+        .service(get_tx_events)
+        .service(get_tvl)
+        .service(get_staker_metrics)
+        .service(get_emissions)
+        .service(get_leaderboard)
+        .service(get_competition)
+        .service(get_user_summary)
+        .service(get_program)
+        .service(get_allocation)
+        .service(get_rank_history)
+        .service(get_snapshots)
+        .service(get_snapshot_diff)
+        .service(
+            web::scope("/verify")
+                .wrap(from_fn(require_api_key))
+                .service(verify_staked)
+                .service(verify_points)
+        );
+}
+
 // Configure and start the API server
-pub async fn run_api_server(db: Database, port: u16) -> std::io::Result<()> {
+pub async fn run_api_server(
+    db: Database,
+    rules: SharedRules,
+    tracker: SharedTracker,
+    status: Arc<IndexerStatus>,
+    programs: Vec<ProgramState>,
+    scheduler: Arc<Scheduler>,
+    config: ApiConfig,
+) -> std::io::Result<()> {
+    let port = config.port;
     println!("🌐 API server running on http://localhost:{}", port);
+
+    // Seconds before cached leaderboard, TVL and staker metrics payloads are refreshed
+    let cache_ttl = config.cache_ttl_secs;
+    let cache: ResponseCache = SwrCache::new(Duration::from_secs(cache_ttl));
+    // Each program caches separately, payload keys don't name the program
+    let programs: Vec<(ProgramState, ResponseCache)> = programs
+        .into_iter()
+        .map(|program| {
+            println!("🧩 Serving program '{}' under /v1/{}", program.name, program.name);
+            (program, SwrCache::new(Duration::from_secs(cache_ttl)))
+        })
+        .collect();
+
+    let stale_after = StaleAfter(config.stale_after_secs);
+    let query_timeout = QueryTimeout(config.query_timeout);
+
+    let ens = EnsResolver::from_env().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?;
+    if ens.is_some() {
+        println!("🔎 Resolving ENS names in address lookups");
+    }
+
+    let siwe = SiweAuth::from_env(config.session_ttl_secs);
+    match &siwe {
+        Some(auth) => println!("🔑 Sign-In with Ethereum enabled for {}", auth.domain()),
+        None => println!("🔒 Notification preferences and profiles disabled (SIWE_DOMAIN not set)"),
+    }
+    // Shared by every program, so a name set once shows on all of their leaderboards
+    let profiles = Profiles { db: db.clone(), moderation: ProfileModeration::from_env() };
+
+    let verify_keys = VerifyApiKeys::from_env();
+    if verify_keys.0.is_empty() {
+        println!("🔒 Verification endpoints disabled (VERIFY_API_KEYS not set)");
+    }
+    let admin_keys = AdminApiKeys::from_env();
+    if admin_keys.0.is_empty() {
+        println!("🔒 Admin endpoints disabled (ADMIN_API_KEYS not set)");
+    }
+    let rate_limiter = config.rate_limit.map(RateLimiter::new);
+    if let Some(limit) = config.rate_limit {
+        println!("🚦 Rate limiting clients to {} requests per minute", limit);
+    }
     
     HttpServer::new(move || {
         // Configure CORS
@@ -125,17 +1591,92 @@ pub async fn run_api_server(db: Database, port: u16) -> std::io::Result<()> {
             .allow_any_origin()
             .allow_any_method()
             .allow_any_header()
+            .expose_any_header()
             .max_age(3600);
 
-        App::new()
+        let mut app = App::new()
+            .wrap(from_fn(negotiate_version))
+            .wrap(from_fn(rate_limit))
+            .wrap(Logger::new("%{x-request-id}i %r %s %b %Dms"))
+            .wrap(from_fn(request_id::propagate))
             .wrap(cors)
             .app_data(web::Data::new(db.clone()))
-            .service(health)
-            .service(get_user_points)
-            .service(get_user_events)
-            .service(get_leaderboard)
+            .app_data(web::Data::new(rules.clone()))
+            .app_data(web::Data::new(tracker.clone()))
+            .app_data(web::Data::new(status.clone()))
+            .app_data(web::Data::new(verify_keys.clone()))
+            .app_data(web::Data::new(admin_keys.clone()))
+            .app_data(web::Data::new(scheduler.clone()))
+            .app_data(web::Data::new(stale_after))
+            .app_data(web::Data::new(query_timeout))
+            .app_data(web::Data::new(cache.clone()))
+            .app_data(web::Data::new(profiles.clone()))
+            .app_data(web::QueryConfig::default().error_handler(|err, _req| {
+                let detail = err.to_string();
+                InternalError::from_response(err, problem(ErrorCode::InvalidParameter, detail)).into()
+            }))
+            .app_data(web::JsonConfig::default().error_handler(|err, _req| {
+                let detail = err.to_string();
+                InternalError::from_response(err, problem(ErrorCode::InvalidParameter, detail)).into()
+            }))
+            .service(health);
+        if let Some(ens) = &ens {
+            app = app.app_data(web::Data::new(ens.clone()));
+        }
+        if let Some(siwe) = &siwe {
+            app = app.app_data(web::Data::new(siwe.clone()));
+        }
+        if let Some(limiter) = &rate_limiter {
+            app = app.app_data(web::Data::new(limiter.clone()));
+        }
+
+        // Additional programs go first, the main program's scopes would match their paths too
+        for (program, program_cache) in &programs {
+            for prefix in ["/v1", "/api"] {
+                app = app.service(
+                    web::scope(&format!("{}/{}", prefix, program.name))
+                        .app_data(web::Data::new(program.db.clone()))
+                        .app_data(web::Data::new(program.rules.clone()))
+                        .app_data(web::Data::new(program.tracker.clone()))
+                        .app_data(web::Data::new(program.status.clone()))
+                        .app_data(web::Data::new(program_cache.clone()))
+                        .configure(configure_program)
+                );
+            }
+        }
+
+        app.service(web::scope("/v1").configure(configure_admin).configure(configure_accounts).configure(configure_program))
+            // Compatibility shim for the original unversioned paths
+            .service(
+                web::scope("/api")
+                    .wrap(DefaultHeaders::new()
+                        .add(("Deprecation", "true"))
+                        .add(("Link", "</v1>; rel=\"successor-version\"")))
+                    .configure(configure_accounts)
+                    .configure(configure_program)
+            )
+            .default_service(web::to(not_found))
     })
     .bind(("0.0.0.0", port))?
     .run()
     .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limiter_counts_each_client_per_minute() {
+        let limiter = RateLimiter::new(2);
+        let now = 1_700_000_020; // 20 seconds before the next minute
+
+        assert_eq!(limiter.check("1.1.1.1", now), None);
+        assert_eq!(limiter.check("1.1.1.1", now + 1), None);
+        assert_eq!(limiter.check("1.1.1.1", now + 2), Some(18));
+        // Other clients have their own count
+        assert_eq!(limiter.check("2.2.2.2", now + 2), None);
+        // A new minute starts over
+        assert_eq!(limiter.check("1.1.1.1", now + 20), None);
+    }
+}
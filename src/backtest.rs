@@ -0,0 +1,226 @@
+use alloy::primitives::Address;
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::db::{Database, StoredEvent};
+use crate::source::{EventSource, FileEventSource};
+use crate::points::{AddressMigration, BoostWindow, PointsRules, RatePeriod, DEFAULT_FORMATION_RATE, DEFAULT_SAGE_RATE};
+use crate::{PointsTracker, Position, PositionStatus};
+
+fn default_sage_rate() -> f64 {
+    DEFAULT_SAGE_RATE
+}
+
+fn default_formation_rate() -> f64 {
+    DEFAULT_FORMATION_RATE
+}
+
+/// Alternative rate configuration to evaluate
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default = "default_sage_rate")]
+    pub sage_rate: f64,          // SAGE points per token per day
+    #[serde(default = "default_formation_rate")]
+    pub formation_rate: f64,     // Formation points per token per day
+    #[serde(default)]
+    pub boosts: Vec<BoostWindow>, // Overlapping boosts add their bonus
+    pub max_points_per_user: Option<f64>, // Cap on total points per address
+}
+
+/// Leaderboard row produced by a scenario
+#[derive(Debug, Serialize)]
+pub struct BacktestEntry {
+    pub rank: usize,
+    pub address: String,
+    pub sage_points: f64,
+    pub formation_points: f64,
+    pub total_points: f64,
+}
+
+/// Leaderboard and totals produced by a scenario
+#[derive(Debug, Serialize)]
+pub struct BacktestResult {
+    pub scenario: String,
+    pub total_sage_points: f64,
+    pub total_formation_points: f64,
+    pub leaderboard: Vec<BacktestEntry>,
+}
+
+impl Scenario {
+    // Accrual rules equivalent to this scenario
+    fn rules(&self) -> PointsRules {
+        PointsRules {
+            rate_periods: vec![RatePeriod {
+                effective_from: 0,
+                sage_rate: self.sage_rate,
+                formation_rate: self.formation_rate,
+            }],
+            boosts: self.boosts.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Rebuild all positions by replaying stored events through an in-memory tracker
+pub async fn replay_events(events: &[StoredEvent]) -> PointsTracker {
+    let mut tracker = PointsTracker::in_memory(PointsRules::default());
+
+    for event in events {
+        let key = (event.user, event.nonce);
+        tracker.total_events_processed += 1;
+        tracker.current_block = event.block_number;
+
+        match event.event_type.as_str() {
+            "Deposit" => {
+                let position = Position {
+                    user: event.user,
+                    nonce: event.nonce,
+                    amount: event.amount.unwrap_or_default(),
+                    deposit_timestamp: event.timestamp,
+                    status: PositionStatus::Active,
+                    withdrawal_initiated_timestamp: None,
+                    unlocks_at: None,
+                    block_number: event.block_number,
+                    lock_duration: event.lock_duration.unwrap_or(0),
+                };
+                tracker.add_active_position(key, position).await;
+            }
+            "InitiateWithdraw" => {
+                tracker.move_to_unstaking(key, event.timestamp, event.unlocks_at.unwrap_or_default()).await;
+            }
+            "Withdraw" => tracker.move_to_withdrawn(key).await,
+            "RestakeFromWithdrawalInitiated" => tracker.move_to_active(key, event.timestamp).await,
+            _ => {}
+        }
+    }
+
+    tracker
+}
+
+/// Rebuild all positions by running recorded logs through the indexer itself
+pub async fn replay_logs(source: &FileEventSource) -> Result<PointsTracker> {
+    let mut tracker = PointsTracker::in_memory(PointsRules::default());
+
+    if let Some(first_block) = source.first_block() {
+        let head = source.head().await?;
+        crate::sync_historical(source, &mut tracker, first_block.saturating_sub(1), head).await?;
+    }
+
+    Ok(tracker)
+}
+
+/// Compute the leaderboard for a scenario over a replayed tracker,
+/// crediting points to the current owner of each address
+pub fn evaluate_scenario(tracker: &PointsTracker, scenario: &Scenario, migrations: &[AddressMigration], as_of: u64) -> BacktestResult {
+    let rules = PointsRules {
+        migrations: migrations.to_vec(),
+        ..scenario.rules()
+    };
+    let positions = tracker.active_positions.values()
+        .chain(tracker.unstaking_positions.values())
+        .chain(tracker.withdrawn_positions.values());
+    let mut user_points: HashMap<Address, (f64, f64)> = rules.owner_totals(positions, 0, as_of)
+        .into_iter()
+        .map(|(owner, totals)| (owner, (totals.points.sage_points, totals.points.formation_points)))
+        .collect();
+
+    // Scale both point types down proportionally when a user exceeds the cap
+    if let Some(cap) = scenario.max_points_per_user {
+        for (sage, formation) in user_points.values_mut() {
+            let total = *sage + *formation;
+            if total > cap {
+                *sage *= cap / total;
+                *formation *= cap / total;
+            }
+        }
+    }
+
+    let mut ranked: Vec<(Address, (f64, f64))> = user_points.into_iter().collect();
+    ranked.sort_by(|a, b| (b.1.0 + b.1.1).partial_cmp(&(a.1.0 + a.1.1)).unwrap());
+
+    BacktestResult {
+        scenario: scenario.name.clone(),
+        total_sage_points: ranked.iter().map(|(_, p)| p.0).sum(),
+        total_formation_points: ranked.iter().map(|(_, p)| p.1).sum(),
+        leaderboard: ranked
+            .into_iter()
+            .enumerate()
+            .map(|(i, (user, (sage, formation)))| BacktestEntry {
+                rank: i + 1,
+                address: user.to_string(),
+                sage_points: sage,
+                formation_points: formation,
+                total_points: sage + formation,
+            })
+            .collect(),
+    }
+}
+
+fn print_result(result: &BacktestResult, limit: usize) {
+    println!("\n🧪 BACKTEST: {}", result.scenario);
+    println!("{}", "=".repeat(100));
+    println!("  {:4} {:42} {:>14} {:>14} {:>14}", "Rank", "Address", "SAGE Points", "FORM Points", "Total");
+    println!("  {}", "-".repeat(95));
+
+    for entry in result.leaderboard.iter().take(limit) {
+        println!("  #{:3} {} {:>14.4} {:>14.4} {:>14.4}",
+            entry.rank, entry.address, entry.sage_points, entry.formation_points, entry.total_points);
+    }
+
+    println!("\n  Total SAGE Points: {:.4}", result.total_sage_points);
+    println!("  Total Formation Points: {:.4}", result.total_formation_points);
+    println!("  Users: {}", result.leaderboard.len());
+    println!("{}\n", "=".repeat(100));
+}
+
+/// `backtest <scenarios.json> [--limit N] [--at TIMESTAMP] [--output FILE] [--logs FILE]`
+pub async fn run_backtest_command(db: &Database, args: &[String]) -> Result<()> {
+    let scenario_path = args.first()
+        .ok_or_else(|| eyre!("Usage: backtest <scenarios.json> [--limit N] [--at TIMESTAMP] [--output FILE] [--logs FILE]"))?;
+
+    let flag = |name: &str| -> Option<&String> {
+        args.iter().position(|a| a == name).and_then(|i| args.get(i + 1))
+    };
+    let limit = flag("--limit").and_then(|v| v.parse::<usize>().ok()).unwrap_or(20);
+    let as_of = flag("--at")
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
+
+    // Accept either a single scenario or a list of scenarios
+    let contents = std::fs::read_to_string(scenario_path)?;
+    let scenarios: Vec<Scenario> = match serde_json::from_str::<Vec<Scenario>>(&contents) {
+        Ok(scenarios) => scenarios,
+        Err(_) => vec![serde_json::from_str::<Scenario>(&contents)?],
+    };
+
+    // Recorded logs replace the stored event history when given
+    let tracker = match flag("--logs") {
+        Some(path) => {
+            println!("📼 Replaying recorded logs from {}...", path);
+            replay_logs(&FileEventSource::open(path)?).await?
+        }
+        None => {
+            let events = db.load_events().await?;
+            println!("⏪ Replaying {} events...", events.len());
+            replay_events(&events).await
+        }
+    };
+    let migrations = db.load_address_migrations().await?;
+
+    let mut results = Vec::new();
+    for scenario in &scenarios {
+        let result = evaluate_scenario(&tracker, scenario, &migrations, as_of);
+        print_result(&result, limit);
+        results.push(result);
+    }
+
+    if let Some(output) = flag("--output") {
+        std::fs::write(output, serde_json::to_string_pretty(&results)?)?;
+        println!("💾 Wrote backtest results to {}", output);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,265 @@
+use alloy::primitives::Address;
+use eyre::{eyre, Result};
+use futures::TryStreamExt;
+use object_store::aws::{AmazonS3, AmazonS3Builder};
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, PutPayload};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use crate::db::{AddressLabel, CompetitionStandings, Database, LeaderboardSort, LeaderboardWindow, PointsRoot, RankSnapshot, TvlSnapshot};
+use crate::notifications::NotificationPreferences;
+use crate::points::SharedRules;
+use crate::profiles::Profile;
+use crate::scheduler::{Schedule, Scheduler};
+use crate::snapshot::{self, Snapshot};
+
+// Longest a scheduled backup may run
+const BACKUP_LEASE_SECS: u64 = 3600;
+
+/// Settings for scheduled backups to S3-compatible storage, checked by `config::load`.
+/// Credentials, region and endpoint (for R2 and other S3-compatible stores) come
+/// from the standard AWS_* variables.
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    pub bucket: String,
+    pub prefix: String,
+    pub interval_secs: u64,
+    pub retention: usize, // Number of backups to keep
+}
+
+impl BackupConfig {
+    fn store(&self) -> Result<AmazonS3> {
+        Ok(AmazonS3Builder::from_env().with_bucket_name(&self.bucket).build()?)
+    }
+
+    fn object(&self, id: &str, name: &str) -> ObjectPath {
+        ObjectPath::from(format!("{}/{}/{}", self.prefix, id, name))
+    }
+}
+
+// A rank snapshot with the `(owner, rank, total points)` it recorded
+type RankRows = (RankSnapshot, Vec<(Address, i32, f64)>);
+
+/// Tables a snapshot leaves out because they aren't indexed state, or shouldn't reach
+/// staging clones, backed up next to it so a restore brings the whole service back
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ServiceTables {
+    pub points_roots: Vec<PointsRoot>,
+    pub address_labels: Vec<AddressLabel>,
+    pub tvl_history: Vec<TvlSnapshot>,
+    pub rank_history: Vec<RankRows>,
+    pub competitions: Vec<CompetitionStandings>,
+    // Addresses kept apart, the API types never deserialize them
+    pub profiles: Vec<(Address, Profile)>,
+    pub notification_preferences: Vec<(Address, NotificationPreferences)>, // Only users subscribed to something
+}
+
+impl ServiceTables {
+    async fn create(db: &Database) -> Result<Self> {
+        let mut rank_history = Vec::new();
+        for snapshot in db.list_rank_snapshots().await?.into_iter().rev() {
+            rank_history.extend(db.load_rank_snapshot(snapshot.timestamp).await?);
+        }
+        let mut competitions = Vec::new();
+        for (board, period) in db.list_competition_periods().await? {
+            competitions.extend(db.load_competition_standings(board, period).await?);
+        }
+
+        Ok(Self {
+            points_roots: db.load_points_roots().await?,
+            address_labels: db.load_address_labels().await?,
+            tvl_history: db.load_tvl_snapshots().await?,
+            rank_history,
+            competitions,
+            profiles: db.load_profiles().await?.into_iter().map(|p| (p.address, p)).collect(),
+            notification_preferences: db.load_notification_preferences().await?.into_iter().map(|p| (p.address, p)).collect(),
+        })
+    }
+
+    // Into a database `snapshot::restore` just filled
+    async fn restore(&self, db: &Database) -> Result<()> {
+        for root in &self.points_roots {
+            db.save_points_root(&root.root, root.block_number, root.leaf_count, &root.tx_hash).await?;
+        }
+        if !self.address_labels.is_empty() {
+            db.save_address_labels(&self.address_labels, "backup restore").await?;
+        }
+        for tvl in &self.tvl_history {
+            db.save_tvl_snapshot(tvl.timestamp, tvl.block_number, tvl.active_amount, tvl.unstaking_amount).await?;
+        }
+        for (snapshot, ranks) in &self.rank_history {
+            db.save_rank_snapshot(snapshot.timestamp, snapshot.block_number, ranks).await?;
+        }
+        for standings in &self.competitions {
+            db.save_competition_standings(standings).await?;
+        }
+        for (address, profile) in &self.profiles {
+            db.save_profile(&Profile { address: *address, ..profile.clone() }).await?;
+        }
+        for (address, preferences) in &self.notification_preferences {
+            db.save_notification_preferences(&NotificationPreferences { address: *address, ..preferences.clone() }).await?;
+        }
+        Ok(())
+    }
+}
+
+// Ids of stored backups, oldest first (ids are UTC timestamps, so they sort by age)
+async fn list_backups(store: &AmazonS3, config: &BackupConfig) -> Result<Vec<String>> {
+    let prefix = ObjectPath::from(config.prefix.as_str());
+    let objects: Vec<_> = store.list(Some(&prefix)).try_collect().await?;
+
+    // Backups are `<prefix>/<id>/snapshot.json`
+    let ids: BTreeSet<String> = objects
+        .iter()
+        .filter(|o| o.location.filename() == Some("snapshot.json"))
+        .filter_map(|o| {
+            let parts: Vec<_> = o.location.parts().collect();
+            parts.len().checked_sub(2).map(|i| parts[i].as_ref().to_string())
+        })
+        .collect();
+    Ok(ids.into_iter().collect())
+}
+
+/// Upload a snapshot of the core tables, the service tables and the current points of
+/// every user, then delete backups beyond the retention count. Returns the backup id.
+pub async fn run_backup(db: &Database, rules: &SharedRules, settings_path: Option<&str>, config: &BackupConfig) -> Result<String> {
+    let store = config.store()?;
+    let id = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+    let snapshot = snapshot::create(db, settings_path).await?;
+    let tables = ServiceTables::create(db).await?;
+    let rules = rules.read().unwrap().clone();
+    let points = db.get_leaderboard(i64::MAX, LeaderboardSort::Total, LeaderboardWindow::All, &rules).await?;
+
+    // Snapshot last: a backup only counts once its snapshot exists
+    store.put(&config.object(&id, "points.json"), PutPayload::from(serde_json::to_vec(&points)?)).await?;
+    store.put(&config.object(&id, "tables.json"), PutPayload::from(serde_json::to_vec(&tables)?)).await?;
+    store.put(&config.object(&id, "snapshot.json"), PutPayload::from(serde_json::to_vec(&snapshot)?)).await?;
+
+    let backups = list_backups(&store, config).await?;
+    let expired = backups.len().saturating_sub(config.retention);
+    for old in &backups[..expired] {
+        for name in ["snapshot.json", "points.json", "tables.json"] {
+            store.delete(&config.object(old, name)).await?;
+        }
+        println!("🗑️  Deleted expired backup {}", old);
+    }
+
+    Ok(id)
+}
+
+/// Back up on the configured interval as the `backup` job
+pub fn register(scheduler: &mut Scheduler, db: Database, rules: SharedRules, settings_path: Option<String>, config: BackupConfig) {
+    let schedule = Schedule::Every(config.interval_secs);
+    scheduler.register("backup", schedule, Duration::from_secs(BACKUP_LEASE_SECS), move || {
+        let (db, rules, settings_path, config) = (db.clone(), rules.clone(), settings_path.clone(), config.clone());
+        async move {
+            let id = run_backup(&db, &rules, settings_path.as_deref(), &config).await?;
+            Ok(format!("Uploaded backup {} to s3://{}/{}", id, config.bucket, config.prefix))
+        }
+    });
+}
+
+/// `backup now`, `backup list`, `backup restore <id|latest>`
+pub async fn run_backup_command(db: &Database, rules: &SharedRules, settings_path: Option<&str>, args: &[String]) -> Result<()> {
+    let usage = "Usage: backup now | backup list | backup restore <id|latest>";
+    let config = crate::config::load_backup()?;
+
+    match args.first().map(String::as_str) {
+        Some("now") => {
+            let id = run_backup(db, rules, settings_path, &config).await?;
+            println!("☁️  Uploaded backup {} to s3://{}/{}", id, config.bucket, config.prefix);
+        }
+        Some("list") => {
+            for id in list_backups(&config.store()?, &config).await? {
+                println!("  {}", id);
+            }
+        }
+        Some("restore") => {
+            let store = config.store()?;
+            let id = match args.get(1).map(String::as_str) {
+                Some("latest") => list_backups(&store, &config).await?
+                    .pop()
+                    .ok_or_else(|| eyre!("No backups in s3://{}/{}", config.bucket, config.prefix))?,
+                Some(id) => id.to_string(),
+                None => return Err(eyre!(usage)),
+            };
+
+            let bytes = store.get(&config.object(&id, "snapshot.json")).await?.bytes().await?;
+            let snapshot: Snapshot = serde_json::from_slice(&bytes)?;
+            snapshot::restore(db, &snapshot).await?;
+            println!("📥 Restored backup {} ({} positions, {} events, cursor {:?})",
+                id, snapshot.positions.len(), snapshot.events.len(), snapshot.last_processed_block);
+
+            // Backups from before service tables were included only have the snapshot
+            match store.get(&config.object(&id, "tables.json")).await {
+                Ok(object) => {
+                    let tables: ServiceTables = serde_json::from_slice(&object.bytes().await?)?;
+                    tables.restore(db).await?;
+                    println!("📥 Restored {} published roots, {} labels, {} TVL and {} rank snapshots, {} competition periods, {} profiles and {} notification preferences",
+                        tables.points_roots.len(), tables.address_labels.len(), tables.tvl_history.len(), tables.rank_history.len(),
+                        tables.competitions.len(), tables.profiles.len(), tables.notification_preferences.len());
+                }
+                Err(object_store::Error::NotFound { .. }) => println!("⚠️  Backup {} has no service tables, only indexed state was restored", id),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        _ => return Err(eyre!(usage)),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::CompetitionEntry;
+    use crate::notifications::NotificationEvent;
+    use crate::points::CompetitionBoard;
+    use alloy::primitives::U256;
+
+    const ALICE: Address = Address::repeat_byte(0xa1);
+
+    #[tokio::test]
+    async fn service_tables_survive_a_restore() {
+        let db = Database::in_memory();
+        db.save_points_root("0x01", 100, 3, "0xaa").await.unwrap();
+        db.save_address_label(&AddressLabel { address: ALICE, label: "Fund".into(), category: None }).await.unwrap();
+        db.save_tvl_snapshot(1_000, 100, U256::from(5), U256::from(1)).await.unwrap();
+        db.save_rank_snapshot(1_000, 100, &[(ALICE, 1, 42.0)]).await.unwrap();
+        db.save_competition_standings(&CompetitionStandings {
+            board: CompetitionBoard::Weekly,
+            period: 7,
+            start: 0,
+            end: 604_800,
+            is_final: true,
+            archived_at: Some(604_900),
+            entries: vec![CompetitionEntry { rank: 1, address: ALICE.to_string(), sage_points: 1.0, formation_points: 2.0, total_points: 3.0 }],
+        }).await.unwrap();
+        db.save_profile(&Profile { address: ALICE, display_name: Some("alice".into()), avatar_url: None }).await.unwrap();
+        db.save_notification_preferences(&NotificationPreferences {
+            address: ALICE,
+            webhook_url: Some("https://example.com/hook".into()),
+            discord_user_id: None,
+            email: None,
+            events: vec![NotificationEvent::CooldownComplete],
+        }).await.unwrap();
+
+        // Through JSON like an uploaded backup
+        let original = ServiceTables::create(&db).await.unwrap();
+        let tables: ServiceTables = serde_json::from_slice(&serde_json::to_vec(&original).unwrap()).unwrap();
+        let restored = Database::in_memory();
+        tables.restore(&restored).await.unwrap();
+
+        let copy = ServiceTables::create(&restored).await.unwrap();
+        assert_eq!(serde_json::to_value(&copy).unwrap(), serde_json::to_value(&original).unwrap());
+        assert_eq!(copy.points_roots.len(), 1);
+        assert_eq!(copy.tvl_history[0].active_amount, U256::from(5));
+        assert_eq!(copy.rank_history[0].1, vec![(ALICE, 1, 42.0)]);
+        assert_eq!(copy.competitions[0].entries[0].total_points, 3.0);
+        assert_eq!(restored.get_profile(&ALICE).await.unwrap().unwrap().display_name.as_deref(), Some("alice"));
+        assert!(restored.get_notification_preferences(&ALICE).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,261 @@
+use alloy::{
+    eips::BlockId,
+    primitives::{Address, U256},
+    providers::RootProvider,
+    sol,
+    sol_types::SolCall,
+    transports::http::{Client, Http},
+};
+use eyre::{eyre, Result};
+use std::collections::HashMap;
+
+use crate::db::{Database, EventData};
+use crate::reconcile::{IMulticall3, MULTICALL3_ADDRESS, MULTICALL_BATCH_SIZE};
+use crate::{format_token_amount, PointsTracker, Position, PositionStatus};
+
+// Enumeration getters the contract has to expose for a view bootstrap. A user's
+// positions are nonces `0..positionCount(user)`.
+sol!(
+    #[sol(rpc)]
+    contract SageStakingViews {
+        function stakerCount() external view returns (uint256);
+        function stakerAt(uint256 index) external view returns (address);
+        function positionCount(address user) external view returns (uint256);
+        function getPositionDetails(address user, uint256 nonce) external view returns (
+            uint256 amount,
+            uint256 depositedAt,
+            uint256 lockDuration,
+            uint256 withdrawalInitiatedAt,
+            uint256 unlocksAt,
+            uint8 status
+        );
+    }
+);
+
+/// Cold start from contract state: reads every current position through the contract's
+/// view getters at one block, instead of replaying events from the deployment block.
+/// Event history before that block (events, rank and TVL history, delegations) is not
+/// recovered. Positions are stored with synthetic events at that block, so commands
+/// that rebuild positions from events still find them.
+pub struct ViewBootstrap {
+    provider: RootProvider<Http<Client>>,
+    contract_address: Address,
+}
+
+impl ViewBootstrap {
+    /// Bootstrap for `contract_address` when `BOOTSTRAP_FROM_VIEWS=true`, None otherwise
+    pub fn from_env(provider: RootProvider<Http<Client>>, contract_address: Address) -> Option<Self> {
+        let enabled = std::env::var("BOOTSTRAP_FROM_VIEWS").map(|v| v == "true").unwrap_or(false);
+        enabled.then_some(Self { provider, contract_address })
+    }
+
+    /// Replace the tracker's positions with contract state at `block` and move the
+    /// cursor there. Returns the block indexing continues from.
+    pub async fn run(&self, tracker: &mut PointsTracker, block: u64) -> Result<u64> {
+        println!("🥾 Bootstrapping positions from contract state at block {}...", block);
+        let positions = self.read_positions(block).await?;
+
+        if let Some(db) = &tracker.db {
+            save(db, &positions, block).await?;
+        }
+
+        tracker.active_positions.clear();
+        tracker.unstaking_positions.clear();
+        tracker.withdrawn_positions.clear();
+        for position in positions {
+            let key = (position.user, position.nonce);
+            match position.status {
+                PositionStatus::Active => tracker.active_positions.insert(key, position),
+                PositionStatus::Unstaking => tracker.unstaking_positions.insert(key, position),
+                PositionStatus::Withdrawn => tracker.withdrawn_positions.insert(key, position),
+            };
+        }
+        tracker.current_block = block;
+
+        let (active, unstaking) = tracker.get_total_staked();
+        println!("✅ Bootstrapped {} active, {} unstaking, {} withdrawn positions ({} staked, {} unstaking)",
+            tracker.active_positions.len(), tracker.unstaking_positions.len(), tracker.withdrawn_positions.len(),
+            format_token_amount(active), format_token_amount(unstaking));
+        Ok(block)
+    }
+
+    // Every position of every staker at `block`
+    async fn read_positions(&self, block: u64) -> Result<Vec<Position>> {
+        let contract = SageStakingViews::new(self.contract_address, &self.provider);
+        let staker_count = contract.stakerCount().block(BlockId::number(block)).call().await?._0;
+        let staker_count: u64 = staker_count.try_into().map_err(|_| eyre!("staker count {} out of range", staker_count))?;
+
+        let stakers: Vec<Address> = self
+            .call_all(block, (0..staker_count).map(|i| SageStakingViews::stakerAtCall { index: U256::from(i) }).collect())
+            .await?
+            .into_iter()
+            .map(|r| r._0)
+            .collect();
+        println!("   {} stakers", stakers.len());
+
+        let counts = self
+            .call_all(block, stakers.iter().map(|&user| SageStakingViews::positionCountCall { user }).collect())
+            .await?;
+        let keys: Vec<(Address, U256)> = stakers
+            .iter()
+            .zip(counts)
+            .flat_map(|(&user, count)| (0..count._0.saturating_to::<u64>()).map(move |nonce| (user, U256::from(nonce))))
+            .collect();
+        println!("   {} positions", keys.len());
+
+        let details = self
+            .call_all(block, keys.iter().map(|&(user, nonce)| SageStakingViews::getPositionDetailsCall { user, nonce }).collect())
+            .await?;
+
+        let mut positions = Vec::with_capacity(keys.len());
+        for ((user, nonce), details) in keys.into_iter().zip(details) {
+            // Contract status: 1 = active, 2 = unstaking, 3 = withdrawn
+            let status = match details.status {
+                1 => PositionStatus::Active,
+                2 => PositionStatus::Unstaking,
+                3 => PositionStatus::Withdrawn,
+                _ => continue,
+            };
+            let optional = |value: U256| (value > U256::ZERO).then(|| value.saturating_to::<u64>());
+
+            positions.push(Position {
+                user,
+                nonce,
+                amount: details.amount,
+                deposit_timestamp: details.depositedAt.saturating_to::<u64>(),
+                withdrawal_initiated_timestamp: if status == PositionStatus::Active { None } else { optional(details.withdrawalInitiatedAt) },
+                unlocks_at: optional(details.unlocksAt),
+                status,
+                block_number: block,
+                lock_duration: details.lockDuration.saturating_to::<u64>(),
+            });
+        }
+
+        Ok(positions)
+    }
+
+    // Run `calls` against the contract at `block` in batched multicalls. Any failed
+    // call fails the whole bootstrap, a partial position set would be wrong.
+    async fn call_all<C: SolCall>(&self, block: u64, calls: Vec<C>) -> Result<Vec<C::Return>> {
+        let multicall = IMulticall3::new(MULTICALL3_ADDRESS, &self.provider);
+        let mut returns = Vec::with_capacity(calls.len());
+
+        for chunk in calls.chunks(MULTICALL_BATCH_SIZE) {
+            let batch: Vec<IMulticall3::Call3> = chunk
+                .iter()
+                .map(|call| IMulticall3::Call3 {
+                    target: self.contract_address,
+                    allowFailure: false,
+                    callData: call.abi_encode().into(),
+                })
+                .collect();
+
+            let results = multicall.aggregate3(batch).block(BlockId::number(block)).call().await?.returnData;
+            for result in results {
+                returns.push(C::abi_decode_returns(&result.returnData, true)?);
+            }
+        }
+
+        Ok(returns)
+    }
+}
+
+// Store bootstrapped positions, the events that rebuild them and the bootstrap block,
+// then move the cursor to `block`
+pub(crate) async fn save(db: &Database, positions: &[Position], block: u64) -> Result<()> {
+    db.replace_positions(positions).await?;
+
+    // Earliest current deposit per user stands in for the first-seen time
+    let mut first_seen: HashMap<Address, u64> = HashMap::new();
+    for position in positions {
+        let seen = first_seen.entry(position.user).or_insert(position.deposit_timestamp);
+        *seen = (*seen).min(position.deposit_timestamp);
+    }
+    for (user, timestamp) in first_seen {
+        db.record_staker(&user, timestamp, block).await?;
+    }
+
+    for event in synthetic_events(positions, block) {
+        db.save_event(event).await?;
+    }
+    db.set_bootstrap_block(block).await?;
+    db.update_last_processed_block(block).await?;
+    Ok(())
+}
+
+// Events that replay into `positions`, all at `block` with a `bootstrap:<block>` hash.
+// Timestamps are the positions' own, so accrual matches the contract state.
+fn synthetic_events(positions: &[Position], block: u64) -> Vec<EventData> {
+    let tx_hash = format!("bootstrap:{}", block);
+    let mut events = Vec::new();
+    for position in positions {
+        let event = |event_type: &str, timestamp: u64| EventData {
+            event_type: event_type.to_string(),
+            user: position.user,
+            nonce: Some(position.nonce),
+            amount: (event_type == "Deposit").then_some(position.amount),
+            unlocks_at: (event_type == "InitiateWithdraw").then_some(position.unlocks_at).flatten(),
+            lock_duration: (event_type == "Deposit").then_some(position.lock_duration),
+            block_number: block,
+            log_index: None,
+            tx_hash: tx_hash.clone(),
+            timestamp,
+        };
+
+        events.push(event("Deposit", position.deposit_timestamp));
+        if position.status != PositionStatus::Active {
+            let initiated = position.withdrawal_initiated_timestamp.unwrap_or(position.deposit_timestamp);
+            events.push(event("InitiateWithdraw", initiated));
+        }
+        if position.status == PositionStatus::Withdrawn {
+            events.push(event("Withdraw", position.unlocks_at.unwrap_or_default()));
+        }
+    }
+
+    // Keep each position's events in order when replayed
+    for (index, event) in events.iter_mut().enumerate() {
+        event.log_index = Some(index as u64);
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rollback::run_rollback_command;
+
+    fn position(nonce: u64, status: PositionStatus) -> Position {
+        let withdrawn = status != PositionStatus::Active;
+        Position {
+            user: Address::repeat_byte(0xa1),
+            nonce: U256::from(nonce),
+            amount: U256::from(1_000 + nonce),
+            deposit_timestamp: 1_700_000_000 + nonce,
+            status,
+            withdrawal_initiated_timestamp: withdrawn.then_some(1_700_100_000),
+            unlocks_at: withdrawn.then_some(1_700_700_000),
+            block_number: 100,
+            lock_duration: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn rollbacks_keep_bootstrapped_positions() {
+        let db = Database::in_memory();
+        let bootstrapped = vec![
+            position(0, PositionStatus::Active),
+            position(1, PositionStatus::Unstaking),
+            position(2, PositionStatus::Withdrawn),
+        ];
+        save(&db, &bootstrapped, 100).await.unwrap();
+        db.update_last_processed_block(150).await.unwrap();
+
+        let args = |block: &str| vec!["--to-block".to_string(), block.to_string()];
+        assert!(run_rollback_command(&db, &args("99")).await.is_err());
+        run_rollback_command(&db, &args("100")).await.unwrap();
+
+        let json = |positions: Vec<Position>| serde_json::to_value(positions).unwrap();
+        assert_eq!(json(db.fetch_positions(None).await.unwrap()), json(bootstrapped));
+        assert_eq!(db.get_last_processed_block().await.unwrap(), Some(100));
+    }
+}
@@ -0,0 +1,108 @@
+use eyre::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// A computed value and when it was computed
+#[derive(Debug)]
+pub struct Cached<V> {
+    pub value: Arc<V>,
+    pub computed_at: SystemTime,
+}
+
+// Not derived, which would require `V: Clone`
+impl<V> Clone for Cached<V> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            computed_at: self.computed_at,
+        }
+    }
+}
+
+impl<V> Cached<V> {
+    pub fn age(&self) -> Duration {
+        self.computed_at.elapsed().unwrap_or_default()
+    }
+}
+
+struct Entry<V> {
+    cached: Cached<V>,
+    stored: Instant,
+    refreshing: bool, // A background refresh is in flight
+}
+
+/// Stale-while-revalidate cache: entries older than `ttl` are still served while a
+/// single background task recomputes them, so only cold keys wait for a computation
+pub struct SwrCache<V> {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, Entry<V>>>,
+}
+
+impl<V: Send + Sync + 'static> SwrCache<V> {
+    pub fn new(ttl: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Cached value for `key`, computed with `compute` on a miss and refreshed in the
+    /// background with it once stale
+    pub async fn get<F, Fut>(self: &Arc<Self>, key: String, compute: F) -> Result<Cached<V>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V>> + Send + 'static,
+    {
+        let stale = {
+            let mut entries = self.entries.lock().unwrap();
+            match entries.get_mut(&key) {
+                Some(entry) if entry.stored.elapsed() < self.ttl => return Ok(entry.cached.clone()),
+                Some(entry) => {
+                    let start_refresh = !entry.refreshing;
+                    entry.refreshing = true;
+                    Some((entry.cached.clone(), start_refresh))
+                }
+                None => None,
+            }
+        };
+
+        match stale {
+            Some((cached, start_refresh)) => {
+                if start_refresh {
+                    let cache = self.clone();
+                    let refresh = compute();
+                    tokio::spawn(async move {
+                        match refresh.await {
+                            Ok(value) => {
+                                cache.store(key, value);
+                            }
+                            Err(e) => {
+                                eprintln!("⚠️  Failed to refresh cached '{}', serving stale data: {}", key, e);
+                                if let Some(entry) = cache.entries.lock().unwrap().get_mut(&key) {
+                                    entry.refreshing = false;
+                                }
+                            }
+                        }
+                    });
+                }
+                Ok(cached)
+            }
+            None => Ok(self.store(key, compute().await?)),
+        }
+    }
+
+    fn store(&self, key: String, value: V) -> Cached<V> {
+        let cached = Cached {
+            value: Arc::new(value),
+            computed_at: SystemTime::now(),
+        };
+        self.entries.lock().unwrap().insert(key, Entry {
+            cached: cached.clone(),
+            stored: Instant::now(),
+            refreshing: false,
+        });
+        cached
+    }
+}
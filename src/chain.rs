@@ -0,0 +1,177 @@
+use alloy::{
+    eips::BlockId,
+    providers::{Provider, RootProvider},
+    rpc::types::{Block, BlockTransactionsKind, Filter, Log},
+    transports::http::{Client, Http},
+};
+use async_trait::async_trait;
+use eyre::Result;
+
+/// Chain reads the indexer depends on. Implemented by the HTTP RPC provider;
+/// scripted implementations can replay canned logs, errors and reorgs.
+#[async_trait]
+pub trait ChainProvider: Send + Sync {
+    async fn get_block_number(&self) -> Result<u64>;
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>>;
+    async fn get_block(&self, number: u64) -> Result<Option<Block>>;
+}
+
+#[async_trait]
+impl ChainProvider for RootProvider<Http<Client>> {
+    async fn get_block_number(&self) -> Result<u64> {
+        Ok(Provider::get_block_number(self).await?)
+    }
+
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
+        Ok(Provider::get_logs(self, filter).await?)
+    }
+
+    async fn get_block(&self, number: u64) -> Result<Option<Block>> {
+        Ok(Provider::get_block(self, BlockId::number(number), BlockTransactionsKind::Hashes).await?)
+    }
+}
+
+/// Scripted chain and contract logs for testing the indexer without a node
+#[cfg(test)]
+pub mod testing {
+    use alloy::primitives::{Address, B256, U256};
+    use alloy::rpc::json_rpc::ErrorPayload;
+    use alloy::rpc::types::{Block, Filter, Header, Log};
+    use alloy::sol_types::SolEvent;
+    use alloy::transports::{RpcError, TransportError};
+    use async_trait::async_trait;
+    use eyre::{eyre, Result};
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::{Arc, Mutex};
+
+    use super::ChainProvider;
+    use crate::SageStaking;
+
+    #[derive(Default)]
+    struct Script {
+        head: u64,
+        logs: Vec<Log>,
+        hashes: HashMap<u64, B256>,
+        failures: VecDeque<String>, // Returned by the next `get_logs` calls, in order
+        max_range: Option<u64>,     // Widest range `get_logs` serves
+        log_requests: Vec<(u64, u64)>,
+    }
+
+    /// Chain whose head, logs, block hashes and failures are set by the test. Clones
+    /// share the script, so a test can change the chain while the indexer reads it.
+    #[derive(Clone, Default)]
+    pub struct ScriptedChain {
+        script: Arc<Mutex<Script>>,
+    }
+
+    impl ScriptedChain {
+        pub fn new(head: u64, logs: Vec<Log>) -> Self {
+            let chain = Self::default();
+            chain.set_head(head);
+            chain.script.lock().unwrap().logs = logs;
+            chain
+        }
+
+        pub fn set_head(&self, head: u64) {
+            self.script.lock().unwrap().head = head;
+        }
+
+        pub fn set_hash(&self, block: u64, hash: B256) {
+            self.script.lock().unwrap().hashes.insert(block, hash);
+        }
+
+        /// Fail the next `get_logs` call with `message`
+        pub fn fail_logs(&self, message: &str) {
+            self.script.lock().unwrap().failures.push_back(message.to_string());
+        }
+
+        /// Reject `get_logs` calls over more than `blocks` blocks the way providers do
+        pub fn limit_range(&self, blocks: u64) {
+            self.script.lock().unwrap().max_range = Some(blocks);
+        }
+
+        /// Replace every log from `block` on with `logs`, and the hash of `block`
+        pub fn reorg(&self, block: u64, hash: B256, logs: Vec<Log>) {
+            let mut script = self.script.lock().unwrap();
+            script.logs.retain(|log| log.block_number.is_some_and(|b| b < block));
+            script.logs.extend(logs);
+            script.hashes.insert(block, hash);
+        }
+
+        /// Ranges of every `get_logs` call so far, in order
+        pub fn log_requests(&self) -> Vec<(u64, u64)> {
+            self.script.lock().unwrap().log_requests.clone()
+        }
+    }
+
+    #[async_trait]
+    impl ChainProvider for ScriptedChain {
+        async fn get_block_number(&self) -> Result<u64> {
+            Ok(self.script.lock().unwrap().head)
+        }
+
+        async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
+            let mut script = self.script.lock().unwrap();
+            let (from, to) = filter.get_from_block().zip(filter.get_to_block()).ok_or_else(|| eyre!("Filter without a block range"))?;
+            script.log_requests.push((from, to));
+
+            if let Some(message) = script.failures.pop_front() {
+                return Err(eyre!("{}", message));
+            }
+            if script.max_range.is_some_and(|max| to - from + 1 > max) {
+                let error: TransportError = RpcError::ErrorResp(ErrorPayload {
+                    code: -32005,
+                    message: "query returned more than 10000 results".into(),
+                    data: None,
+                });
+                return Err(error.into());
+            }
+
+            Ok(script.logs
+                .iter()
+                .filter(|log| log.block_number.is_some_and(|b| b >= from && b <= to))
+                .cloned()
+                .collect())
+        }
+
+        async fn get_block(&self, number: u64) -> Result<Option<Block>> {
+            Ok(self.script.lock().unwrap().hashes.get(&number).map(|&hash| Block {
+                header: Header { hash, ..Default::default() },
+                ..Default::default()
+            }))
+        }
+    }
+
+    pub const CONTRACT: Address = Address::repeat_byte(0x5a);
+
+    /// Whole tokens in wei
+    pub fn tokens(amount: u64) -> U256 {
+        U256::from(amount) * U256::from(10u64).pow(U256::from(18))
+    }
+
+    // Contract log of `event` at `block`, with a transaction hash unique to its position
+    fn log_at<E: SolEvent>(event: &E, block: u64, log_index: u64) -> Log {
+        Log {
+            inner: alloy::primitives::Log { address: CONTRACT, data: event.encode_log_data() },
+            block_number: Some(block),
+            log_index: Some(log_index),
+            transaction_hash: Some(B256::from(U256::from(block << 16 | log_index))),
+            ..Default::default()
+        }
+    }
+
+    pub fn deposit(user: Address, nonce: u64, amount: U256, timestamp: u64, block: u64, log_index: u64) -> Log {
+        let event = SageStaking::Deposit { user, amount, nonce: U256::from(nonce), timestamp: U256::from(timestamp) };
+        log_at(&event, block, log_index)
+    }
+
+    pub fn initiate_withdraw(user: Address, nonce: u64, unlocks_at: u64, timestamp: u64, block: u64, log_index: u64) -> Log {
+        let event = SageStaking::InitiateWithdraw {
+            user,
+            nonce: U256::from(nonce),
+            unlocksAt: U256::from(unlocks_at),
+            timestamp: U256::from(timestamp),
+        };
+        log_at(&event, block, log_index)
+    }
+}
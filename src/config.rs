@@ -0,0 +1,357 @@
+use alloy::primitives::Address;
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::signers::local::PrivateKeySigner;
+use eyre::{eyre, Result};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::alerts::WhaleAlertConfig;
+use crate::api::ApiConfig;
+use crate::backup::BackupConfig;
+use crate::db::Database;
+use crate::programs::{self, ProgramConfig};
+use crate::publisher::PublisherConfig;
+use crate::reconcile::ReconcileConfig;
+use crate::scheduler::Schedule;
+use crate::trace::TraceMode;
+use crate::watchdog::WatchdogConfig;
+
+/// Settings the indexer and API need, checked together at startup
+pub struct Config {
+    pub base_rpc_url: String,
+    pub contract_address: Address,
+    pub deployment_block: u64,
+    pub api: ApiConfig,
+    pub reconcile: ReconcileConfig,
+    pub watchdog: WatchdogConfig,
+    pub whale_alerts: WhaleAlertConfig,
+    pub publisher: Option<PublisherConfig>,
+    pub backup: Option<BackupConfig>,
+    pub job_schedules: HashMap<String, Schedule>, // Overrides from `JOB_SCHEDULES`
+    pub trace: Option<TraceMode>,
+    pub programs: Vec<Program>,
+    pub db: Database,     // Connected and migrated, in memory with `--no-db`
+    pub api_db: Database, // View of `db` for API reads, see `Database::for_api`
+}
+
+/// An additional program with its databases connected
+pub struct Program {
+    pub config: ProgramConfig,
+    pub db: Database,
+    pub api_db: Database,
+}
+
+// Problems found so far, reported together
+#[derive(Default)]
+struct Report {
+    problems: Vec<String>,
+}
+
+impl Report {
+    fn fail(&mut self, problem: impl Into<String>) {
+        self.problems.push(problem.into());
+    }
+
+    // Value of a variable that has to be set
+    fn required(&mut self, name: &str, hint: &str) -> Option<String> {
+        let value = std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        if value.is_none() {
+            self.fail(format!("{} is not set: {}", name, hint));
+        }
+        value
+    }
+
+    fn parse<T: FromStr>(&mut self, name: &str, value: Option<String>, expected: &str) -> Option<T> {
+        let value = value?;
+        let parsed = value.trim().parse::<T>().ok();
+        if parsed.is_none() {
+            self.fail(format!("{} is '{}', expected {}", name, value, expected));
+        }
+        parsed
+    }
+
+    // `parse` of an optional variable, unset when empty
+    fn parse_var<T: FromStr>(&mut self, name: &str, expected: &str) -> Option<T> {
+        self.parse(name, optional(name), expected)
+    }
+
+    // Print every problem and fail if there are any
+    fn finish(&self) -> Result<()> {
+        if self.problems.is_empty() {
+            return Ok(());
+        }
+        eprintln!("❌ Found {} configuration problem(s):", self.problems.len());
+        for problem in &self.problems {
+            eprintln!("   • {}", problem);
+        }
+        eprintln!("   See env.example for every setting.");
+        Err(eyre!("Invalid configuration"))
+    }
+}
+
+fn optional(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+// Root publisher, None when POINTS_ORACLE_ADDRESS is unset
+fn publisher(report: &mut Report, rpc_url: Option<&str>) -> Option<PublisherConfig> {
+    let oracle_address = report.parse_var::<Address>("POINTS_ORACLE_ADDRESS", "a 0x-prefixed 20-byte address");
+    optional("POINTS_ORACLE_ADDRESS")?;
+
+    let dry_run = report.parse_var("PUBLISHER_DRY_RUN", "true or false").unwrap_or(false);
+    let private_key = optional("PUBLISHER_PRIVATE_KEY");
+    match &private_key {
+        Some(key) if PrivateKeySigner::from_str(key).is_err() => report.fail("PUBLISHER_PRIVATE_KEY is not a valid hex private key"),
+        None if !dry_run => report.fail("PUBLISHER_PRIVATE_KEY is not set: the publisher signs root updates with it, or set PUBLISHER_DRY_RUN=true"),
+        _ => {}
+    }
+    let gwei = |report: &mut Report, name: &str| report.parse_var::<f64>(name, "an amount of gwei").map(|v| (v * 1e9) as u128);
+    let interval_secs = report.parse_var("PUBLISH_INTERVAL_SECS", "a number of seconds").unwrap_or(86400);
+    let gas_limit = report.parse_var("PUBLISHER_GAS_LIMIT", "an amount of gas");
+    let max_fee_per_gas = gwei(report, "PUBLISHER_MAX_FEE_GWEI");
+    let max_priority_fee_per_gas = gwei(report, "PUBLISHER_PRIORITY_FEE_GWEI");
+
+    Some(PublisherConfig {
+        oracle_address: oracle_address?,
+        rpc_url: rpc_url?.to_string(),
+        private_key,
+        interval_secs,
+        dry_run,
+        gas_limit,
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+    })
+}
+
+// Scheduled backups, None when BACKUP_BUCKET is unset
+fn backup(report: &mut Report) -> Option<BackupConfig> {
+    let bucket = optional("BACKUP_BUCKET")?;
+    Some(BackupConfig {
+        bucket,
+        prefix: optional("BACKUP_PREFIX").unwrap_or_else(|| "sage-points".to_string()),
+        interval_secs: report.parse_var("BACKUP_INTERVAL_SECS", "a number of seconds").filter(|&v| v > 0).unwrap_or(86400),
+        retention: report.parse_var("BACKUP_RETENTION", "a number of backups").filter(|&v| v > 0).unwrap_or(30),
+    })
+}
+
+/// Backup settings for the `backup` command, which runs without `load`
+pub fn load_backup() -> Result<BackupConfig> {
+    let mut report = Report::default();
+    let config = backup(&mut report);
+    report.finish()?;
+    config.ok_or_else(|| eyre!("BACKUP_BUCKET must be set"))
+}
+
+// `JOB_SCHEDULES`: comma-separated `name=schedule` entries
+fn job_schedules(report: &mut Report) -> HashMap<String, Schedule> {
+    let mut overrides = HashMap::new();
+    for entry in optional("JOB_SCHEDULES").unwrap_or_default().split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let Some((name, schedule)) = entry.split_once('=') else {
+            report.fail(format!("JOB_SCHEDULES entry '{}' is not name=schedule", entry));
+            continue;
+        };
+        match Schedule::parse(schedule) {
+            Ok(schedule) => {
+                overrides.insert(name.trim().to_string(), schedule);
+            }
+            Err(e) => report.fail(format!("JOB_SCHEDULES entry '{}': {}", entry, e)),
+        }
+    }
+    overrides
+}
+
+// Connect to a database and its API view, noting a failure as a problem
+async fn connect(
+    report: &mut Report,
+    what: &str,
+    url: &str,
+    read_url: Option<&str>,
+    schema: Option<&str>,
+    query_timeout: Duration,
+) -> Option<(Database, Database)> {
+    let db = match Database::with_schema(url, schema).await {
+        Ok(db) => db,
+        Err(e) => {
+            report.fail(format!("Failed to connect to or migrate {}: {}", what, e));
+            return None;
+        }
+    };
+    match db.for_api(url, read_url, schema, query_timeout).await {
+        Ok(api_db) => Some((db, api_db)),
+        Err(e) => {
+            report.fail(format!("Failed to connect to the API pool of {}: {}", what, e));
+            None
+        }
+    }
+}
+
+/// Read every setting, check the contracts have code on the configured chain and
+/// connect to (and migrate) the databases. Prints all problems at once and fails if
+/// there are any. The chain checks are skipped with `EVENTS_FILE` or `RPC_REPLAY_FILE`,
+/// fixtures and traces don't need a live contract.
+pub async fn load(no_db: bool) -> Result<Config> {
+    println!("🔍 Checking configuration...");
+    let mut report = Report::default();
+
+    let base_rpc_url = report.required("BASE_RPC_URL", "RPC endpoint of the chain the contract is on, e.g. https://mainnet.base.org");
+    let contract_address = report.required("CONTRACT_ADDRESS", "address of the SageStaking contract");
+    let contract_address: Option<Address> = report.parse("CONTRACT_ADDRESS", contract_address, "a 0x-prefixed 20-byte address");
+    let deployment_block = report.required("DEPLOYMENT_BLOCK", "block the contract was deployed at, where indexing starts");
+    let deployment_block: Option<u64> = report.parse("DEPLOYMENT_BLOCK", deployment_block, "a block number");
+    let api = ApiConfig {
+        port: report.parse_var("PORT", "a port number").unwrap_or(3000),
+        cache_ttl_secs: report.parse_var("API_CACHE_TTL_SECS", "a number of seconds").unwrap_or(15),
+        session_ttl_secs: report.parse_var("SIWE_SESSION_TTL_SECS", "a number of seconds").unwrap_or(86400),
+        rate_limit: report.parse_var("API_RATE_LIMIT", "a number of requests per minute").filter(|&v| v > 0),
+        stale_after_secs: report.parse_var("STALE_AFTER_SECS", "a number of seconds").unwrap_or(300),
+        query_timeout: Duration::from_secs(
+            report.parse_var("API_QUERY_TIMEOUT_SECS", "a number of seconds").filter(|&v| v > 0).unwrap_or(10)
+        ),
+    };
+    // 0 disables reconciliation, like leaving it unset
+    let reconcile = ReconcileConfig {
+        interval_secs: report.parse_var("RECONCILE_INTERVAL_SECS", "a number of seconds").filter(|&v| v > 0),
+        auto_repair: report.parse_var("RECONCILE_AUTO_REPAIR", "true or false").unwrap_or(false),
+    };
+    let stall_minutes: u64 = report.parse_var("STALL_ALERT_MINUTES", "a number of minutes")
+        .filter(|&v| v > 0)
+        .unwrap_or(10);
+    let watchdog = WatchdogConfig { stall_after_secs: stall_minutes * 60 };
+    // 0 turns a threshold off, like leaving it unset
+    let whale_alerts = WhaleAlertConfig {
+        deposit_threshold: report.parse_var("WHALE_DEPOSIT_THRESHOLD", "an amount of tokens").filter(|&v: &f64| v > 0.0),
+        unstake_threshold: report.parse_var("WHALE_UNSTAKE_THRESHOLD", "an amount of tokens").filter(|&v: &f64| v > 0.0),
+    };
+    let publisher = publisher(&mut report, base_rpc_url.as_deref());
+    let backup = backup(&mut report);
+    let job_schedules = job_schedules(&mut report);
+    let trace = match (optional("RPC_RECORD_FILE"), optional("RPC_REPLAY_FILE")) {
+        (Some(_), Some(_)) => {
+            report.fail("RPC_RECORD_FILE and RPC_REPLAY_FILE are both set, set only one");
+            None
+        }
+        (Some(record), None) => Some(TraceMode::Record(record)),
+        (None, Some(replay)) => Some(TraceMode::Replay(replay)),
+        (None, None) => None,
+    };
+
+    let program_configs = match std::env::var("PROGRAMS_FILE").ok() {
+        Some(path) => programs::load(&path).unwrap_or_else(|e| {
+            report.fail(e.to_string());
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+
+    // The contracts must exist on the chain behind the RPC, a wrong network is the usual mistake
+    let offline = std::env::var("EVENTS_FILE").is_ok() || trace.as_ref().is_some_and(TraceMode::is_replay);
+    if let (Some(url), false) = (&base_rpc_url, offline) {
+        match url.parse() {
+            Ok(url) => {
+                let provider = ProviderBuilder::new().on_http(url);
+                match (provider.get_chain_id().await, provider.get_block_number().await) {
+                    (Ok(chain_id), Ok(head)) => {
+                        let mut contracts: Vec<(String, Address, Option<u64>)> = Vec::new();
+                        if let Some(address) = contract_address {
+                            contracts.push(("CONTRACT_ADDRESS".to_string(), address, deployment_block));
+                        }
+                        for program in &program_configs {
+                            contracts.push((format!("Program '{}'", program.name), program.contract_address, Some(program.deployment_block)));
+                        }
+                        if let Some(publisher) = &publisher {
+                            contracts.push(("POINTS_ORACLE_ADDRESS".to_string(), publisher.oracle_address, None));
+                        }
+
+                        for (name, address, deployment_block) in contracts {
+                            match provider.get_code_at(address).await {
+                                Ok(code) if code.is_empty() => report.fail(format!(
+                                    "{} {} has no contract code on chain {} (BASE_RPC_URL): wrong network or address?",
+                                    name, address, chain_id,
+                                )),
+                                Ok(_) => {}
+                                Err(e) => report.fail(format!("Failed to read the code of {} {}: {}", name, address, e)),
+                            }
+                            if let Some(block) = deployment_block.filter(|&b| b > head) {
+                                report.fail(format!("{} deployment block {} is past the chain head {}", name, block, head));
+                            }
+                        }
+                    }
+                    (Err(e), _) | (_, Err(e)) => report.fail(format!("BASE_RPC_URL is not reachable: {}", e)),
+                }
+            }
+            Err(_) => report.fail(format!("BASE_RPC_URL '{}' is not a valid URL", url)),
+        }
+    }
+
+    // Connecting runs pending migrations, so a reachable database is also a migrated one.
+    // API reads get their own pool with a query timeout, on the replica when there is
+    // one, so public traffic can't contend with ingestion.
+    let database_url = if no_db {
+        None
+    } else {
+        report.required("DATABASE_URL", "PostgreSQL or sqlite:// URL, or pass --no-db to keep state in memory")
+    };
+    let database_read_url = optional("DATABASE_READ_URL");
+    let databases = match &database_url {
+        _ if no_db => Some((Database::in_memory(), None)),
+        Some(url) => connect(&mut report, "DATABASE_URL", url, database_read_url.as_deref(), None, api.query_timeout)
+            .await
+            .map(|(db, api_db)| (db, Some(api_db))),
+        None => None,
+    };
+
+    // Each additional program on its own database, or its own schema of DATABASE_URL
+    let mut programs = Vec::new();
+    for config in program_configs {
+        let connected = match (&config.database_url, &database_url) {
+            _ if no_db => {
+                let db = Database::in_memory();
+                Some((db.clone(), db))
+            }
+            (Some(url), _) => {
+                let what = format!("the database of program '{}'", config.name);
+                connect(&mut report, &what, url, config.database_read_url.as_deref(), config.schema(), api.query_timeout).await
+            }
+            (None, Some(url)) => {
+                let what = format!("schema {} of DATABASE_URL for program '{}'", config.schema().unwrap_or_default(), config.name);
+                let read_url = config.database_read_url.as_deref().or(database_read_url.as_deref());
+                connect(&mut report, &what, url, read_url, config.schema(), api.query_timeout).await
+            }
+            (None, None) => None,
+        };
+        if let Some((db, api_db)) = connected {
+            programs.push(Program { config, db, api_db });
+        }
+    }
+
+    match (base_rpc_url, contract_address, deployment_block, databases) {
+        (Some(base_rpc_url), Some(contract_address), Some(deployment_block), Some((db, api_db))) if report.problems.is_empty() => {
+            if database_read_url.is_some() && !no_db {
+                println!("📖 Serving API reads from the read replica");
+            }
+            println!("✅ Configuration OK");
+            let api_db = api_db.unwrap_or_else(|| db.clone());
+            Ok(Config {
+                base_rpc_url,
+                contract_address,
+                deployment_block,
+                api,
+                reconcile,
+                watchdog,
+                whale_alerts,
+                publisher,
+                backup,
+                job_schedules,
+                trace,
+                programs,
+                db,
+                api_db,
+            })
+        }
+        _ => {
+            report.finish()?;
+            Err(eyre!("Invalid configuration"))
+        }
+    }
+}
@@ -1,24 +1,83 @@
 use alloy::primitives::{Address, U256};
-use bigdecimal::BigDecimal;
-use chrono::{DateTime, Utc};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
 use eyre::Result;
 use serde::{Deserialize, Serialize};
-use sqlx::{PgPool, postgres::PgPoolOptions, Row};
-use std::str::FromStr;
+use std::collections::BTreeMap;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::{Position, PositionStatus};
+use crate::points::{AccrualSegment, AddressMigration, AllocationBasis, BoostWindow, CompetitionBoard, Delegation, Emissions, OwnerTotals, PauseWindow, PointsRules, RatePeriod};
+use crate::notifications::NotificationPreferences;
+use crate::profiles::Profile;
+use crate::scheduler::{JobRun, JobState};
+use crate::{format_token_amount_as_float, Position, PositionStatus};
+
+mod memory;
+mod postgres;
+mod replica;
+mod sqlite;
+
+use memory::MemoryStore;
+use postgres::PgDatabase;
+use replica::ReplicatedStore;
+use sqlite::SqliteDatabase;
 
 // Struct for saving events to avoid too many arguments
 pub struct EventData {
     pub event_type: String,
     pub user: alloy::primitives::Address,
-    pub nonce: Option<u64>,
+    pub nonce: Option<U256>,
     pub amount: Option<alloy::primitives::U256>,
+    pub unlocks_at: Option<u64>,
+    pub lock_duration: Option<u64>, // Deposits only
+    pub block_number: u64,
+    pub log_index: Option<u64>, // Position within the block, orders same-block events
+    pub tx_hash: String,
+    pub timestamp: u64,
+}
+
+// Event as stored in the audit trail, used for replays and snapshots
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredEvent {
+    pub event_type: String,
+    pub user: Address,
+    pub nonce: U256,
+    pub amount: Option<U256>,
+    pub unlocks_at: Option<u64>,
+    #[serde(default)]
+    pub lock_duration: Option<u64>, // Deposits only
     pub block_number: u64,
+    #[serde(default)]
+    pub log_index: Option<u64>, // None for events stored before log indexes were kept
     pub tx_hash: String,
     pub timestamp: u64,
 }
 
+/// Known owner of an address, e.g. an exchange hot wallet or a fund
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressLabel {
+    pub address: Address,
+    pub label: String,
+    pub category: Option<String>,
+}
+
+// Nonces stay JSON numbers for existing clients; values beyond u64 are sent as decimal strings
+fn serialize_nonce<S: serde::Serializer>(nonce: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+    match u64::try_from(*nonce) {
+        Ok(nonce) => serializer.serialize_u64(nonce),
+        Err(_) => serializer.serialize_str(&nonce.to_string()),
+    }
+}
+
+fn serialize_optional_nonce<S: serde::Serializer>(nonce: &Option<U256>, serializer: S) -> Result<S::Ok, S::Error> {
+    match nonce {
+        Some(nonce) => serialize_nonce(nonce, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
 /// Response structure for user points data
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserPoints {
@@ -26,9 +85,71 @@ pub struct UserPoints {
     pub sage_points: f64,
     pub formation_points: f64,
     pub total_points: f64,
+    pub points_24h: f64, // Total points earned over the trailing window
+    pub points_7d: f64,
+    pub points_30d: f64,
     pub active_amount: f64,
     pub unstaking_amount: f64,
     pub withdrawn_amount: f64,
+    pub active_amount_wei: String, // Exact amounts in wei
+    pub unstaking_amount_wei: String,
+    pub withdrawn_amount_wei: String,
+}
+
+/// Line of a points statement: an accrual period of one position, or the points
+/// daily caps removed
+#[derive(Debug, Serialize)]
+pub struct StatementLine {
+    pub kind: &'static str, // "accrual" or "daily_cap"
+    pub position_owner: Option<String>, // Address holding the position on chain
+    #[serde(serialize_with = "serialize_optional_nonce")]
+    pub nonce: Option<U256>,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub amount: Option<f64>, // Tokens in the position
+    pub sage_rate: Option<f64>,
+    pub formation_rate: Option<f64>,
+    pub multiplier: Option<f64>, // Boosts times lock tier, zero while paused
+    pub sage_points: f64,
+    pub formation_points: f64,
+    pub running_total: f64,
+}
+
+/// Every accrual period behind an address's points, in time order
+#[derive(Debug, Serialize)]
+pub struct PointsStatement {
+    pub address: String,
+    pub generated_at: DateTime<Utc>,
+    pub sage_points: f64,
+    pub formation_points: f64,
+    pub total_points: f64,
+    pub lines: Vec<StatementLine>,
+}
+
+impl PointsStatement {
+    /// One CSV row per line, with a header
+    pub fn to_csv(&self) -> String {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        let mut csv = String::from("kind,position_owner,nonce,start,end,amount,sage_rate,formation_rate,multiplier,sage_points,formation_points,running_total\n");
+        for line in &self.lines {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{},{},{}\n",
+                line.kind,
+                optional(line.position_owner.clone()),
+                optional(line.nonce.map(|n| n.to_string())),
+                optional(line.start.map(|t| t.to_rfc3339())),
+                optional(line.end.map(|t| t.to_rfc3339())),
+                optional(line.amount.map(|v| v.to_string())),
+                optional(line.sage_rate.map(|v| v.to_string())),
+                optional(line.formation_rate.map(|v| v.to_string())),
+                optional(line.multiplier.map(|v| v.to_string())),
+                line.sage_points,
+                line.formation_points,
+                line.running_total,
+            ));
+        }
+        csv
+    }
 }
 
 /// Historical event data for a user
@@ -36,10 +157,259 @@ pub struct UserPoints {
 pub struct UserEvent {
     pub event_type: String,
     pub amount: String,
-    pub nonce: i64,
+    pub amount_wei: String, // Exact amount in wei
+    #[serde(serialize_with = "serialize_nonce")]
+    pub nonce: U256,
     pub timestamp: DateTime<Utc>,
     pub block_number: i64,
     pub status: String,
+    pub unlocks_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributed_from: Option<String>, // Original address when shown via an address migration
+}
+
+/// Staking event in a transaction and its effect on the position
+#[derive(Debug, Serialize)]
+pub struct TxEvent {
+    pub event_type: String,
+    pub address: String,
+    #[serde(serialize_with = "serialize_nonce")]
+    pub nonce: U256,
+    pub amount: String,
+    pub amount_wei: String, // Exact amount in wei
+    pub unlocks_at: Option<DateTime<Utc>>,
+    pub block_number: i64,
+    pub timestamp: DateTime<Utc>,
+    pub status_before: Option<String>, // None when the event opened the position
+    pub status_after: String,
+    pub position_status: Option<String>, // Current status of the position
+    pub position_sage_points: f64,       // Points the position has earned so far
+    pub position_formation_points: f64,
+    pub points_owner: String, // Address credited with the position's points
+}
+
+/// Everything the profile page shows for an address
+#[derive(Debug, Serialize)]
+pub struct UserSummary {
+    #[serde(flatten)]
+    pub points: UserPoints,
+    pub rank: Option<i32>, // Rank by total points, None without positions or when blocklisted
+    pub positions: Vec<UserPosition>,
+    pub recent_events: Vec<UserEvent>, // Newest first
+    pub next_unlock_at: Option<DateTime<Utc>>, // Earliest pending unlock among unstaking positions
+    pub active_boosts: Vec<BoostWindow>,
+}
+
+/// Full lifecycle of one position
+#[derive(Debug, Serialize)]
+pub struct PositionDetail {
+    pub address: String,
+    #[serde(flatten)]
+    pub position: UserPosition,
+    pub points_owner: String, // Address credited with the position's points
+    pub sage_points: f64,
+    pub formation_points: f64,
+    pub total_points: f64,
+    pub events: Vec<PositionEvent>,      // Chain order
+    pub segments: Vec<AccrualSegment>,   // How the points were accrued
+}
+
+/// Event in a position's lifecycle and the status change it caused
+#[derive(Debug, Serialize)]
+pub struct PositionEvent {
+    pub event_type: String,
+    pub amount: String,
+    pub amount_wei: String, // Exact amount in wei
+    pub unlocks_at: Option<DateTime<Utc>>,
+    pub block_number: i64,
+    pub tx_hash: String,
+    pub timestamp: DateTime<Utc>,
+    pub status_before: Option<String>, // None when the event opened the position
+    pub status_after: String,
+}
+
+fn status_name(status: &PositionStatus) -> &'static str {
+    match status {
+        PositionStatus::Active => "active",
+        PositionStatus::Unstaking => "unstaking",
+        PositionStatus::Withdrawn => "withdrawn",
+    }
+}
+
+// Position status transition caused by a contract event
+fn status_transition(event_type: &str) -> (Option<&'static str>, &'static str) {
+    match event_type {
+        "Deposit" => (None, "active"),
+        "InitiateWithdraw" => (Some("active"), "unstaking"),
+        "Withdraw" => (Some("unstaking"), "withdrawn"),
+        "RestakeFromWithdrawalInitiated" => (Some("unstaking"), "active"),
+        _ => (None, ""),
+    }
+}
+
+/// Staking position data for a user
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserPosition {
+    #[serde(serialize_with = "serialize_nonce")]
+    pub nonce: U256,
+    pub amount: String,
+    pub amount_wei: String, // Exact amount in wei
+    pub status: String,
+    pub deposit_timestamp: DateTime<Utc>,
+    pub withdrawal_initiated_timestamp: Option<DateTime<Utc>>,
+    pub unlocks_at: Option<DateTime<Utc>>,
+    pub block_number: i64,
+    pub lock_duration: u64,   // Seconds locked at deposit
+    pub lock_multiplier: f64, // Accrual multiplier of the position's lock tier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributed_from: Option<String>, // Original address when shown via an address migration
+}
+
+/// Unstaking position whose cooldown has elapsed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WithdrawablePosition {
+    pub address: String,
+    #[serde(serialize_with = "serialize_nonce")]
+    pub nonce: U256,
+    pub amount: String,
+    pub amount_wei: String, // Exact amount in wei
+    pub unlocks_at: DateTime<Utc>,
+    pub claimable_for_seconds: i64,
+}
+
+/// Total staked amount at a point in time
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TvlPoint {
+    pub timestamp: DateTime<Utc>,
+    pub block_number: i64,
+    pub active_amount: f64,
+    pub unstaking_amount: f64,
+    pub total_staked: f64,
+}
+
+/// One row of `tvl_history` with the exact amounts, for backups
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TvlSnapshot {
+    pub timestamp: u64,
+    pub block_number: u64,
+    pub active_amount: U256,    // wei
+    pub unstaking_amount: U256, // wei
+}
+
+/// A points Merkle root published on-chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointsRoot {
+    pub root: String,
+    pub block_number: u64,
+    pub leaf_count: usize,
+    pub tx_hash: String,
+}
+
+/// An owner's rank by total points at a rank snapshot
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RankPoint {
+    pub timestamp: DateTime<Utc>,
+    pub block_number: i64,
+    pub rank: i32,
+    pub total_points: f64,
+}
+
+/// Rank snapshots of one address, oldest first
+#[derive(Debug, Serialize)]
+pub struct RankHistory {
+    pub address: String,
+    pub current_rank: Option<i32>,   // At the latest snapshot
+    pub rank_change_7d: Option<i32>, // Places climbed since a week ago, negative when dropped
+    pub history: Vec<RankPoint>,
+}
+
+/// A stored rank snapshot and how many owners it ranked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankSnapshot {
+    pub timestamp: u64,
+    pub block_number: u64,
+    pub owners: u64,
+}
+
+/// How one owner moved between two rank snapshots. Owners in only one of them have
+/// no rank in the other and count as 0 points there.
+#[derive(Debug, Serialize)]
+pub struct SnapshotDiffEntry {
+    pub address: String,
+    pub from_rank: Option<i32>,
+    pub to_rank: Option<i32>,
+    pub rank_change: Option<i32>, // Places climbed, negative when dropped
+    pub from_points: f64,
+    pub to_points: f64,
+    pub points_delta: f64,
+}
+
+/// One page of the per-owner changes between two rank snapshots
+#[derive(Debug, Serialize)]
+pub struct SnapshotDiff {
+    pub from: RankSnapshot,
+    pub to: RankSnapshot,
+    pub sort: SnapshotDiffSort,
+    pub total: usize, // Owners in either snapshot
+    pub offset: usize,
+    pub limit: usize,
+    pub entries: Vec<SnapshotDiffEntry>,
+}
+
+/// Order of snapshot diff entries, largest first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotDiffSort {
+    #[default]
+    Points,    // Biggest gainers
+    Rank,      // Most places climbed, owners new to the board last
+    Magnitude, // Largest change either way, for auditing unexpected jumps
+}
+
+/// Staker growth for a single day
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailyStakerMetrics {
+    pub date: NaiveDate,
+    pub new_stakers: i64,
+    pub churned_stakers: i64,
+    pub cumulative_stakers: i64,
+}
+
+/// Unique staker and growth metrics
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StakerMetrics {
+    pub total_unique_stakers: i64,
+    pub current_stakers: i64,
+    pub churned_stakers: i64,
+    pub daily: Vec<DailyStakerMetrics>,
+}
+
+/// Points emitted by source, summing to the day's total
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmissionSources {
+    pub base: f64,
+    pub boosts: f64,
+    pub lock_tiers: f64,
+    pub daily_caps: f64, // Zero or negative
+}
+
+/// Points emitted on one UTC day
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailyEmissions {
+    pub date: NaiveDate,
+    pub sage_points: f64,
+    pub formation_points: f64,
+    pub total_points: f64,
+    pub sources: EmissionSources,
+}
+
+/// Points emitted per day over a window, for monitoring emissions against the model
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmissionsReport {
+    pub sage_points: f64,
+    pub formation_points: f64,
+    pub total_points: f64,
+    pub daily: Vec<DailyEmissions>,
 }
 
 /// Entry in the points leaderboard
@@ -50,84 +420,670 @@ pub struct LeaderboardEntry {
     pub sage_points: f64,
     pub formation_points: f64,
     pub total_points: f64,
+    pub active_amount: f64,
+    pub active_amount_wei: String,
+    #[serde(default)]
+    pub display_name: Option<String>, // From the address's profile, if it set one
+    #[serde(default)]
+    pub avatar_url: Option<String>,
+}
+
+/// Place on a weekly or monthly competition board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompetitionEntry {
+    pub rank: i32,
+    pub address: String,
+    pub sage_points: f64,      // Earned within the period
+    pub formation_points: f64,
+    pub total_points: f64,
 }
 
-/// Database connection and operations handler
+/// Standings of one competition period. Final once the period ended and its winners
+/// were archived; until then computed from current positions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompetitionStandings {
+    pub board: CompetitionBoard,
+    pub period: u64,
+    pub start: u64,
+    pub end: u64,
+    #[serde(rename = "final")]
+    pub is_final: bool,
+    pub archived_at: Option<u64>,
+    pub entries: Vec<CompetitionEntry>,
+}
+
+/// Projected token allocation of one address under the configured formula
+#[derive(Debug, Serialize)]
+pub struct AllocationPreview {
+    pub address: String,
+    pub basis: AllocationBasis,
+    pub points: f64,
+    pub eligible: bool,         // At least `min_points`
+    pub pool_points: f64,       // Points of every eligible owner
+    pub share: f64,             // Of `total_tokens`, after `max_share`
+    pub projected_tokens: f64,
+    pub total_tokens: f64,
+}
+
+/// Column the leaderboard is ranked by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardSort {
+    Sage,
+    Formation,
+    #[default]
+    Total,
+    ActiveAmount, // Tokens currently staked and earning
+}
+
+/// Period over which leaderboard points are counted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum LeaderboardWindow {
+    #[serde(rename = "7d")]
+    Week,
+    #[serde(rename = "30d")]
+    Month,
+    #[default]
+    #[serde(rename = "all")]
+    All, // Season to date
+}
+
+impl LeaderboardWindow {
+    /// First timestamp counted by the window ending at `now`
+    pub fn start(self, now: u64) -> u64 {
+        match self {
+            Self::Week => now.saturating_sub(7 * 86400),
+            Self::Month => now.saturating_sub(30 * 86400),
+            Self::All => 0,
+        }
+    }
+}
+
+// Aggregate per-day new stakers and churn dates into growth metrics for the last `days` days
+fn build_staker_metrics(new_stakers: Vec<(NaiveDate, i64)>, churned: Vec<NaiveDate>, days: i64) -> StakerMetrics {
+    // (new, churned) per day
+    let mut per_day: BTreeMap<NaiveDate, (i64, i64)> = BTreeMap::new();
+    let mut total_unique_stakers = 0;
+    for (day, count) in new_stakers {
+        per_day.entry(day).or_default().0 += count;
+        total_unique_stakers += count;
+    }
+
+    let churned_stakers = churned.len() as i64;
+    for day in churned {
+        per_day.entry(day).or_default().1 += 1;
+    }
+
+    let mut daily = Vec::new();
+    let mut cumulative_stakers = 0;
+    for (date, (new_stakers, churned)) in per_day {
+        cumulative_stakers += new_stakers;
+        daily.push(DailyStakerMetrics {
+            date,
+            new_stakers,
+            churned_stakers: churned,
+            cumulative_stakers,
+        });
+    }
+
+    // Only keep days inside the requested window
+    let cutoff = Utc::now().date_naive() - chrono::Duration::days(days);
+    daily.retain(|d| d.date > cutoff);
+
+    StakerMetrics {
+        total_unique_stakers,
+        current_stakers: total_unique_stakers - churned_stakers,
+        churned_stakers,
+        daily,
+    }
+}
+
+/// Points and deposit summary of `owner` computed from `positions`, which may include
+/// positions of other users. Shared by database reads and the in-memory tracker.
+pub fn user_points_from<'a>(
+    owner: Address,
+    positions: impl IntoIterator<Item = &'a Position>,
+    rules: &PointsRules,
+    now: u64,
+) -> UserPoints {
+    let point_sources = rules.point_sources(owner);
+    let history_sources = rules.addresses_owned_by(owner, true);
+
+    let owned: Vec<&Position> = positions.into_iter().filter(|p| point_sources.contains(&p.user)).collect();
+    let points = rules.owner_totals(owned.iter().copied(), 0, now)
+        .remove(&owner)
+        .map(|totals| totals.points)
+        .unwrap_or_default();
+    let (sage_points, formation_points) = (points.sage_points, points.formation_points);
+
+    // Caps and delegations apply exactly as for the lifetime total
+    let points_since = |since: u64| rules.owner_totals(owned.iter().copied(), since, now)
+        .remove(&owner)
+        .map_or(0.0, |totals| totals.points.total());
+    let points_24h = points_since(now.saturating_sub(86400));
+    let points_7d = points_since(now.saturating_sub(7 * 86400));
+    let points_30d = points_since(now.saturating_sub(30 * 86400));
+
+    let mut active_amount = U256::ZERO;
+    let mut unstaking_amount = U256::ZERO;
+    let mut withdrawn_amount = U256::ZERO;
+
+    for position in owned {
+        if !history_sources.contains(&position.user) {
+            continue;
+        }
+
+        // Sum exact amounts by status
+        match position.status {
+            PositionStatus::Active => active_amount += position.amount,
+            PositionStatus::Unstaking => unstaking_amount += position.amount,
+            PositionStatus::Withdrawn => withdrawn_amount += position.amount,
+        }
+    }
+
+    UserPoints {
+        address: owner.to_string(),
+        sage_points,
+        formation_points,
+        total_points: sage_points + formation_points,
+        points_24h,
+        points_7d,
+        points_30d,
+        active_amount: format_token_amount_as_float(active_amount),
+        unstaking_amount: format_token_amount_as_float(unstaking_amount),
+        withdrawn_amount: format_token_amount_as_float(withdrawn_amount),
+        active_amount_wei: active_amount.to_string(),
+        unstaking_amount_wei: unstaking_amount.to_string(),
+        withdrawn_amount_wei: withdrawn_amount.to_string(),
+    }
+}
+
+/// Statement of how `owner` earned its points up to `now`, from `positions`, which may
+/// include positions of other users. Shared by database reads and the in-memory tracker.
+pub fn statement_from<'a>(
+    owner: Address,
+    positions: impl IntoIterator<Item = &'a Position>,
+    rules: &PointsRules,
+    now: u64,
+) -> PointsStatement {
+    let point_sources = rules.point_sources(owner);
+    let owned: Vec<&Position> = positions.into_iter().filter(|p| point_sources.contains(&p.user)).collect();
+
+    // Periods of every stretch of a position credited to the owner
+    let mut periods: Vec<(&Position, AccrualSegment)> = Vec::new();
+    for position in &owned {
+        for (earner, slice) in rules.earner_slices(position) {
+            if earner == owner {
+                periods.extend(rules.position_segments(&slice, now).into_iter().map(|segment| (*position, segment)));
+            }
+        }
+    }
+    periods.sort_by_key(|(position, segment)| (segment.start, position.user, position.nonce));
+
+    let timestamp = |t: u64| DateTime::from_timestamp(t as i64, 0);
+    let mut running_total = 0.0;
+    let mut lines: Vec<StatementLine> = periods
+        .into_iter()
+        .map(|(position, segment)| {
+            running_total += segment.sage_points + segment.formation_points;
+            StatementLine {
+                kind: "accrual",
+                position_owner: Some(position.user.to_string()),
+                nonce: Some(position.nonce),
+                start: timestamp(segment.start),
+                end: timestamp(segment.end),
+                amount: Some(format_token_amount_as_float(position.amount)),
+                sage_rate: Some(segment.sage_rate),
+                formation_rate: Some(segment.formation_rate),
+                multiplier: Some(segment.multiplier),
+                sage_points: segment.sage_points,
+                formation_points: segment.formation_points,
+                running_total,
+            }
+        })
+        .collect();
+
+    // Whatever caps removed shows up as one adjustment, so the statement adds up
+    let points = rules.owner_totals(owned.iter().copied(), 0, now)
+        .remove(&owner)
+        .map(|totals| totals.points)
+        .unwrap_or_default();
+    let accrued_sage: f64 = lines.iter().map(|l| l.sage_points).sum();
+    let accrued_formation: f64 = lines.iter().map(|l| l.formation_points).sum();
+    let (capped_sage, capped_formation) = (points.sage_points - accrued_sage, points.formation_points - accrued_formation);
+    if capped_sage + capped_formation < -1e-9 {
+        lines.push(StatementLine {
+            kind: "daily_cap",
+            position_owner: None,
+            nonce: None,
+            start: None,
+            end: None,
+            amount: None,
+            sage_rate: None,
+            formation_rate: None,
+            multiplier: None,
+            sage_points: capped_sage,
+            formation_points: capped_formation,
+            running_total: points.total(),
+        });
+    }
+
+    PointsStatement {
+        address: owner.to_string(),
+        generated_at: timestamp(now).unwrap_or_default(),
+        sage_points: points.sage_points,
+        formation_points: points.formation_points,
+        total_points: points.total(),
+        lines,
+    }
+}
+
+/// Top `limit` owners of `positions`, ranked by `sort` (highest first), counting only
+/// points earned within `window`. Shared by database reads and the in-memory tracker.
+pub fn leaderboard_from<'a>(
+    positions: impl IntoIterator<Item = &'a Position>,
+    limit: i64,
+    sort: LeaderboardSort,
+    window: LeaderboardWindow,
+    rules: &PointsRules,
+    now: u64,
+) -> Vec<LeaderboardEntry> {
+    let mut ranked: Vec<(Address, OwnerTotals)> = rules.owner_totals(positions, window.start(now), now)
+        .into_iter()
+        .collect();
+    ranked.sort_by(|(_, a), (_, b)| match sort {
+        LeaderboardSort::Sage => b.points.sage_points.total_cmp(&a.points.sage_points),
+        LeaderboardSort::Formation => b.points.formation_points.total_cmp(&a.points.formation_points),
+        LeaderboardSort::Total => b.points.total().total_cmp(&a.points.total()),
+        LeaderboardSort::ActiveAmount => b.active_amount.cmp(&a.active_amount),
+    });
+
+    ranked
+        .into_iter()
+        .take(limit.max(0) as usize)
+        .enumerate()
+        .map(|(i, (user, totals))| LeaderboardEntry {
+            rank: i as i32 + 1,
+            address: user.to_string(),
+            sage_points: totals.points.sage_points,
+            formation_points: totals.points.formation_points,
+            total_points: totals.points.total(),
+            active_amount: format_token_amount_as_float(totals.active_amount),
+            active_amount_wei: totals.active_amount.to_string(),
+            display_name: None,
+            avatar_url: None,
+        })
+        .collect()
+}
+
+// An owner's `(rank, total points)` in the older and newer snapshot
+type RankPair = (Option<(i32, f64)>, Option<(i32, f64)>);
+
+/// Page of the changes between the ranks of two snapshots, each given as
+/// `(owner, rank, total points)`
+pub fn snapshot_diff_from(
+    from: (RankSnapshot, Vec<(Address, i32, f64)>),
+    to: (RankSnapshot, Vec<(Address, i32, f64)>),
+    sort: SnapshotDiffSort,
+    offset: usize,
+    limit: usize,
+) -> SnapshotDiff {
+    let mut owners: BTreeMap<Address, RankPair> = BTreeMap::new();
+    for (owner, rank, points) in from.1 {
+        owners.entry(owner).or_default().0 = Some((rank, points));
+    }
+    for (owner, rank, points) in to.1 {
+        owners.entry(owner).or_default().1 = Some((rank, points));
+    }
+
+    let mut entries: Vec<SnapshotDiffEntry> = owners
+        .into_iter()
+        .map(|(owner, (before, after))| {
+            let from_points = before.map_or(0.0, |(_, p)| p);
+            let to_points = after.map_or(0.0, |(_, p)| p);
+            SnapshotDiffEntry {
+                address: owner.to_string(),
+                from_rank: before.map(|(r, _)| r),
+                to_rank: after.map(|(r, _)| r),
+                rank_change: before.zip(after).map(|((then, _), (now, _))| then - now),
+                from_points,
+                to_points,
+                points_delta: to_points - from_points,
+            }
+        })
+        .collect();
+    // Stable sort over address order, so pages don't shift between requests
+    entries.sort_by(|a, b| match sort {
+        SnapshotDiffSort::Points => b.points_delta.total_cmp(&a.points_delta),
+        SnapshotDiffSort::Rank => b.rank_change.map_or(i64::MIN, i64::from).cmp(&a.rank_change.map_or(i64::MIN, i64::from)),
+        SnapshotDiffSort::Magnitude => b.points_delta.abs().total_cmp(&a.points_delta.abs()),
+    });
+
+    SnapshotDiff {
+        from: from.0,
+        to: to.0,
+        sort,
+        total: entries.len(),
+        offset,
+        limit,
+        entries: entries.into_iter().skip(offset).take(limit).collect(),
+    }
+}
+
+/// Live standings of `period` of a competition board, ranked by total points earned
+/// within the period up to `now`. None without competitions or for a period that
+/// hasn't started. Shared by database reads, the in-memory tracker and archiving.
+pub fn competition_from<'a>(
+    positions: impl IntoIterator<Item = &'a Position>,
+    board: CompetitionBoard,
+    period: u64,
+    rules: &PointsRules,
+    now: u64,
+) -> Option<CompetitionStandings> {
+    let config = rules.competitions.as_ref()?;
+    let (start, end) = config.period_bounds(board, period)?;
+    if start > now {
+        return None;
+    }
+
+    let mut ranked: Vec<(Address, OwnerTotals)> = rules.owner_totals(positions, start, end.min(now))
+        .into_iter()
+        .filter(|(_, totals)| totals.points.total() > 0.0)
+        .collect();
+    ranked.sort_by(|(_, a), (_, b)| b.points.total().total_cmp(&a.points.total()));
+
+    let entries = ranked
+        .into_iter()
+        .take(config.winners)
+        .enumerate()
+        .map(|(i, (owner, totals))| CompetitionEntry {
+            rank: i as i32 + 1,
+            address: owner.to_string(),
+            sage_points: totals.points.sage_points,
+            formation_points: totals.points.formation_points,
+            total_points: totals.points.total(),
+        })
+        .collect();
+
+    Some(CompetitionStandings { board, period, start, end, is_final: false, archived_at: None, entries })
+}
+
+/// Points emitted across `positions` on each of the last `days` UTC days, today
+/// included up to `now`. Shared by database reads and the in-memory tracker.
+pub fn emissions_from<'a>(
+    positions: impl IntoIterator<Item = &'a Position>,
+    days: i64,
+    rules: &PointsRules,
+    now: u64,
+) -> EmissionsReport {
+    let positions: Vec<&Position> = positions.into_iter().collect();
+    let today = now / 86400 * 86400;
+    let first_day = today.saturating_sub((days.max(1) as u64 - 1) * 86400);
+
+    let mut daily = Vec::new();
+    let mut total = Emissions::default();
+    let mut day_start = first_day;
+    while day_start <= today {
+        let emissions = rules.emissions(&positions, day_start, (day_start + 86400).min(now));
+        total.points += emissions.points.clone();
+
+        daily.push(DailyEmissions {
+            date: DateTime::from_timestamp(day_start as i64, 0).unwrap_or_default().date_naive(),
+            sage_points: emissions.points.sage_points,
+            formation_points: emissions.points.formation_points,
+            total_points: emissions.points.total(),
+            sources: EmissionSources {
+                base: emissions.base,
+                boosts: emissions.boosts,
+                lock_tiers: emissions.lock_tiers,
+                daily_caps: emissions.daily_caps,
+            },
+        });
+        day_start += 86400;
+    }
+
+    EmissionsReport {
+        sage_points: total.points.sage_points,
+        formation_points: total.points.formation_points,
+        total_points: total.points.total(),
+        daily,
+    }
+}
+
+/// Projected allocation of `owner` from its share of everyone's points up to `now`,
+/// None when no allocation is configured
+pub fn allocation_from<'a>(
+    owner: Address,
+    positions: impl IntoIterator<Item = &'a Position>,
+    rules: &PointsRules,
+    now: u64,
+) -> Option<AllocationPreview> {
+    let config = rules.allocation.as_ref()?;
+    let totals = rules.owner_totals(positions, 0, now);
+
+    let points = totals.get(&owner).map_or(0.0, |t| config.points(&t.points));
+    let pool_points: f64 = totals.values()
+        .map(|t| config.points(&t.points))
+        .filter(|&p| p > 0.0 && p >= config.min_points)
+        .sum();
+    let eligible = points > 0.0 && points >= config.min_points;
+
+    let share = if eligible && pool_points > 0.0 {
+        (points / pool_points).min(config.max_share.unwrap_or(1.0))
+    } else {
+        0.0
+    };
+
+    Some(AllocationPreview {
+        address: owner.to_string(),
+        basis: config.basis,
+        points,
+        eligible,
+        pool_points,
+        share,
+        projected_tokens: share * config.total_tokens,
+        total_tokens: config.total_tokens,
+    })
+}
+
+/// Storage operations the indexer and API depend on. Implemented by the
+/// PostgreSQL, SQLite and in-memory backends; alternative backends and test doubles
+/// only need to implement this trait. Addresses are always passed and stored in
+/// EIP-55 checksummed form (`Address::to_string`), so they can be compared literally.
+#[async_trait]
+pub trait PointsStore: Send + Sync {
+    // Positions
+    async fn fetch_positions(&self, user_address: Option<&str>) -> Result<Vec<Position>>;
+    async fn save_position(&self, position: &Position) -> Result<()>;
+
+    // Event log
+    async fn save_event(&self, event: EventData) -> Result<()>;
+    async fn load_events(&self) -> Result<Vec<StoredEvent>>;
+    /// Events emitted by one transaction, in log order
+    async fn load_tx_events(&self, tx_hash: &str) -> Result<Vec<StoredEvent>>;
+    /// Events of one position, in chain order
+    async fn load_position_events(&self, user_address: &str, nonce: U256) -> Result<Vec<StoredEvent>>;
+
+    // Sync cursor
+    async fn get_last_processed_block(&self) -> Result<Option<u64>>;
+    async fn update_last_processed_block(&self, block: u64) -> Result<()>;
+    // Block a view bootstrap read positions at; no real events exist before it
+    async fn get_bootstrap_block(&self) -> Result<Option<u64>>;
+    async fn set_bootstrap_block(&self, block: u64) -> Result<()>;
+
+    // Accrual rules
+    async fn load_rate_periods(&self) -> Result<Vec<RatePeriod>>;
+    async fn save_rate_period(&self, period: &RatePeriod) -> Result<()>;
+    async fn load_pause_windows(&self) -> Result<Vec<PauseWindow>>;
+    async fn add_pause_window(&self, start: u64, end: u64, reason: &str) -> Result<i32>;
+    async fn remove_pause_window(&self, id: i32) -> Result<bool>;
+    async fn load_address_migrations(&self) -> Result<Vec<AddressMigration>>;
+    /// Must store the migration and its audit log entry atomically
+    async fn migrate_address(&self, migration: &AddressMigration) -> Result<i32>;
+    /// Delegations in chain order
+    async fn load_delegations(&self) -> Result<Vec<Delegation>>;
+    /// Must ignore a delegation that is already stored (same transaction and delegator)
+    async fn save_delegation(&self, delegation: &Delegation) -> Result<()>;
+    /// Must atomically replace all stored positions
+    async fn replace_positions(&self, positions: &[Position]) -> Result<()>;
+    /// Must atomically drop events, delegations, stakers, TVL and rank snapshots after `block`, replace all
+    /// positions with `positions`, reset the cursor to `block` and log the rollback.
+    /// Returns the number of events removed.
+    async fn rollback_to_block(&self, block: u64, positions: &[Position]) -> Result<u64>;
+
+    // Address labels
+    async fn get_address_label(&self, address: &Address) -> Result<Option<AddressLabel>>;
+    async fn save_address_label(&self, label: &AddressLabel) -> Result<()>;
+    /// Must store all labels atomically and log the import, `source` names where they came from
+    async fn save_address_labels(&self, labels: &[AddressLabel], source: &str) -> Result<()>;
+    async fn load_address_labels(&self) -> Result<Vec<AddressLabel>>;
+
+    // Notification preferences
+    async fn get_notification_preferences(&self, address: &Address) -> Result<Option<NotificationPreferences>>;
+    async fn save_notification_preferences(&self, preferences: &NotificationPreferences) -> Result<()>;
+    /// Returns whether the address had preferences
+    async fn delete_notification_preferences(&self, address: &Address) -> Result<bool>;
+    /// Preferences of every user subscribed to at least one event
+    async fn load_notification_preferences(&self) -> Result<Vec<NotificationPreferences>>;
+
+    // Profiles
+    async fn get_profile(&self, address: &Address) -> Result<Option<Profile>>;
+    async fn save_profile(&self, profile: &Profile) -> Result<()>;
+    /// Returns whether the address had a profile
+    async fn delete_profile(&self, address: &Address) -> Result<bool>;
+    /// Profiles of those of `addresses` that have one
+    async fn get_profiles(&self, addresses: &[Address]) -> Result<Vec<Profile>>;
+    async fn load_profiles(&self) -> Result<Vec<Profile>>;
+
+    // Scheduled jobs
+    /// Must atomically take the job's lease until `lease_until`, unless another lease is
+    /// still held at `now` or a run already started at or after `due`. Returns whether it was taken.
+    async fn try_lock_job(&self, name: &str, owner: &str, now: u64, due: u64, lease_until: u64) -> Result<bool>;
+    /// Record the outcome of a run and release the lease, if `owner` still holds it
+    async fn finish_job(&self, name: &str, owner: &str, run: &JobRun) -> Result<()>;
+    async fn load_job_states(&self) -> Result<Vec<JobState>>;
+
+    // Analytics
+    async fn record_staker(&self, user: &Address, timestamp: u64, block_number: u64) -> Result<()>;
+    async fn save_tvl_snapshot(&self, timestamp: u64, block_number: u64, active: U256, unstaking: U256) -> Result<()>;
+    async fn get_last_tvl_timestamp(&self) -> Result<Option<u64>>;
+    /// Every TVL snapshot, oldest first
+    async fn load_tvl_snapshots(&self) -> Result<Vec<TvlSnapshot>>;
+    async fn save_points_root(&self, root: &str, block_number: u64, leaf_count: usize, tx_hash: &str) -> Result<()>;
+    /// Every published root, oldest first
+    async fn load_points_roots(&self) -> Result<Vec<PointsRoot>>;
+    /// Must store the ranks of one snapshot atomically
+    async fn save_rank_snapshot(&self, timestamp: u64, block_number: u64, ranks: &[(Address, i32, f64)]) -> Result<()>;
+    async fn get_last_rank_timestamp(&self) -> Result<Option<u64>>;
+    /// Rank snapshots of one owner from `since` on, oldest first
+    async fn load_rank_history(&self, user_address: &str, since: u64) -> Result<Vec<RankPoint>>;
+    /// Every rank snapshot, newest first
+    async fn list_rank_snapshots(&self) -> Result<Vec<RankSnapshot>>;
+    /// `(owner, rank, total points)` of every owner in the snapshot taken at `timestamp`,
+    /// None when there is no such snapshot
+    async fn load_rank_snapshot(&self, timestamp: u64) -> Result<Option<(RankSnapshot, Vec<(Address, i32, f64)>)>>;
+    /// Must store a period and all its winners atomically; a period already archived is kept as is
+    async fn save_competition_standings(&self, standings: &CompetitionStandings) -> Result<()>;
+    async fn load_competition_standings(&self, board: CompetitionBoard, period: u64) -> Result<Option<CompetitionStandings>>;
+    async fn get_last_archived_period(&self, board: CompetitionBoard) -> Result<Option<u64>>;
+    /// `(board, period)` of every archived period, oldest first
+    async fn list_competition_periods(&self) -> Result<Vec<(CompetitionBoard, u64)>>;
+
+    // Read queries
+    async fn get_user_events(&self, user_address: &str) -> Result<Vec<UserEvent>>;
+    async fn get_user_positions(&self, user_address: &str) -> Result<Vec<UserPosition>>;
+    async fn get_withdrawable_positions(&self, user_address: Option<&str>, limit: i64) -> Result<Vec<WithdrawablePosition>>;
+    async fn get_tvl_history(&self, interval: &str, limit: i64) -> Result<Vec<TvlPoint>>;
+    async fn get_staker_metrics(&self, days: i64) -> Result<StakerMetrics>;
+}
+
+/// Database connection and operations handler.
+/// Store operations are reached through `Deref`; points reads are computed here
+/// with the shared accrual engine so every backend agrees.
 #[derive(Clone)]
 pub struct Database {
-    pool: PgPool,
+    store: Arc<dyn PointsStore>,
+}
+
+impl Deref for Database {
+    type Target = dyn PointsStore;
+
+    fn deref(&self) -> &Self::Target {
+        self.store.as_ref()
+    }
 }
 
 impl Database {
-    /// Create a new database connection with migrations
+    /// Create a new database connection with migrations.
+    /// `sqlite:` URLs use SQLite, anything else is treated as PostgreSQL.
     pub async fn new(database_url: &str) -> Result<Self> {
-        // Create connection pool
-        let pool = PgPoolOptions::new()
-            .max_connections(5)
-            .connect(database_url)
-            .await?;
+        Self::with_schema(database_url, None).await
+    }
+
+    /// Like `new`, with all tables in their own PostgreSQL schema so several points
+    /// programs can share one database
+    pub async fn with_schema(database_url: &str, schema: Option<&str>) -> Result<Self> {
+        let store: Arc<dyn PointsStore> = if database_url.starts_with("sqlite:") {
+            if schema.is_some() {
+                return Err(eyre::eyre!("Schemas are PostgreSQL only, give each program its own sqlite: URL"));
+            }
+            Arc::new(SqliteDatabase::new(database_url).await?)
+        } else {
+            Arc::new(PgDatabase::new(database_url, schema).await?)
+        };
+
+        Ok(Self { store })
+    }
+
+    /// View of this database for API traffic. On PostgreSQL, reads get their own pool
+    /// whose statements the server aborts after `statement_timeout`, on the replica at
+    /// `replica_url` if given, else on `database_url` (this database's URL). Writes still
+    /// come here. SQLite has neither, so it is used as is.
+    pub async fn for_api(
+        &self,
+        database_url: &str,
+        replica_url: Option<&str>,
+        schema: Option<&str>,
+        statement_timeout: Duration,
+    ) -> Result<Self> {
+        if replica_url.is_some_and(|url| url.starts_with("sqlite:")) || (database_url.starts_with("sqlite:") && replica_url.is_some()) {
+            return Err(eyre::eyre!("Read replicas are PostgreSQL only"));
+        }
+        if database_url.starts_with("sqlite:") {
+            return Ok(self.clone());
+        }
+
+        let reader = PgDatabase::reader(replica_url.unwrap_or(database_url), schema, statement_timeout).await?;
+        Ok(Self::from_store(Arc::new(ReplicatedStore::new(self.store.clone(), Arc::new(reader)))))
+    }
+
+    /// Ephemeral in-memory store, nothing survives a restart
+    pub fn in_memory() -> Self {
+        Self::from_store(Arc::new(MemoryStore::default()))
+    }
 
-        // Run migrations using sqlx migrate
-        sqlx::migrate!("./migrations")
-            .run(&pool)
-            .await?;
-        
-        Ok(Self { pool })
+    /// Wrap any storage backend
+    pub fn from_store(store: Arc<dyn PointsStore>) -> Self {
+        Self { store }
     }
 
     // Load all positions from database on startup
     pub async fn load_positions(&self) -> Result<(
-        Vec<((Address, u64), Position)>,  // active
-        Vec<((Address, u64), Position)>,  // unstaking
-        Vec<((Address, u64), Position)>,  // withdrawn
+        Vec<((Address, U256), Position)>,  // active
+        Vec<((Address, U256), Position)>,  // unstaking
+        Vec<((Address, U256), Position)>,  // withdrawn
     )> {
-        let rows = sqlx::query(
-            "SELECT user_address, nonce, amount, deposit_timestamp, status::text as status, 
-             withdrawal_initiated_timestamp, block_number 
-             FROM positions"
-        )
-        .fetch_all(&self.pool)
-        .await?;
-
         let mut active = Vec::new();
         let mut unstaking = Vec::new();
         let mut withdrawn = Vec::new();
 
-        for row in rows {
-            let user_address: String = row.get("user_address");
-            let nonce: i64 = row.get("nonce");
-            let amount_str: BigDecimal = row.get("amount");
-            let deposit_timestamp: i64 = row.get("deposit_timestamp");
-            let status: String = row.get("status");
-            let withdrawal_timestamp: Option<i64> = row.get("withdrawal_initiated_timestamp");
-            let block_number: i64 = row.get("block_number");
-
-            // Convert BigDecimal to U256
-            let amount = U256::from_str(&amount_str.to_string()).unwrap_or_default();
-            let address = Address::from_str(&user_address)?;
-            
-            let position = Position {
-                user: address,
-                nonce: nonce as u64,
-                amount,
-                deposit_timestamp: deposit_timestamp as u64,
-                status: match status.as_str() {
-                    "active" => PositionStatus::Active,
-                    "unstaking" => PositionStatus::Unstaking,
-                    "withdrawn" => PositionStatus::Withdrawn,
-                    _ => PositionStatus::Active,
-                },
-                withdrawal_initiated_timestamp: withdrawal_timestamp.map(|t| t as u64),
-                block_number: block_number as u64,
-            };
-
-            let key = (address, nonce as u64);
+        for position in self.fetch_positions(None).await? {
+            let key = (position.user, position.nonce);
             
-            match status.as_str() {
-                "active" => active.push((key, position)),
-                "unstaking" => unstaking.push((key, position)),
-                "withdrawn" => withdrawn.push((key, position)),
-                _ => {}
+            match position.status {
+                PositionStatus::Active => active.push((key, position)),
+                PositionStatus::Unstaking => unstaking.push((key, position)),
+                PositionStatus::Withdrawn => withdrawn.push((key, position)),
             }
         }
 
@@ -137,259 +1093,485 @@ impl Database {
         Ok((active, unstaking, withdrawn))
     }
 
-    // Save or update a position
-    pub async fn save_position(&self, position: &Position) -> Result<()> {
-        let status_str = match position.status {
-            PositionStatus::Active => "active",
-            PositionStatus::Unstaking => "unstaking",
-            PositionStatus::Withdrawn => "withdrawn",
-        };
-
-        let amount_str = position.amount.to_string();
-
-        sqlx::query(
-            "INSERT INTO positions 
-             (user_address, nonce, amount, deposit_timestamp, status, 
-              withdrawal_initiated_timestamp, block_number, updated_at)
-             VALUES ($1, $2, $3, $4, $5::position_status, $6, $7, CURRENT_TIMESTAMP)
-             ON CONFLICT (user_address, nonce) 
-             DO UPDATE SET 
-                amount = EXCLUDED.amount,
-                deposit_timestamp = EXCLUDED.deposit_timestamp,
-                status = EXCLUDED.status,
-                withdrawal_initiated_timestamp = EXCLUDED.withdrawal_initiated_timestamp,
-                block_number = EXCLUDED.block_number,
-                updated_at = CURRENT_TIMESTAMP"
-        )
-        .bind(position.user.to_string())
-        .bind(position.nonce as i64)
-        .bind(BigDecimal::from_str(&amount_str).unwrap_or_else(|_| BigDecimal::from(0)))
-        .bind(position.deposit_timestamp as i64)
-        .bind(status_str)
-        .bind(position.withdrawal_initiated_timestamp.map(|t| t as i64))
-        .bind(position.block_number as i64)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
-    }
-
-    // Save an event for audit trail
-    pub async fn save_event(&self, event: EventData) -> Result<()> {
-        let amount_str = event.amount.and_then(|a| BigDecimal::from_str(&a.to_string()).ok());
-
-        sqlx::query(
-            "INSERT INTO events 
-             (event_type, user_address, nonce, amount, block_number, transaction_hash, timestamp)
-             VALUES ($1, $2, $3, $4, $5, $6, $7)"
-        )
-        .bind(event.event_type)
-        .bind(event.user.to_string())
-        .bind(event.nonce.map(|n| n as i64))
-        .bind(amount_str)
-        .bind(event.block_number as i64)
-        .bind(event.tx_hash)
-        .bind(event.timestamp as i64)
-        .execute(&self.pool)
-        .await?;
-
-        Ok(())
-    }
-
-    // Get last processed block
-    pub async fn get_last_processed_block(&self) -> Result<Option<u64>> {
-        let row = sqlx::query(
-            "SELECT value FROM sync_metadata WHERE key = 'last_processed_block'"
-        )
-        .fetch_optional(&self.pool)
-        .await?;
-
-        if let Some(row) = row {
-            let value: String = row.get("value");
-            Ok(value.parse::<u64>().ok())
-        } else {
-            Ok(None)
+    // API Methods
+    
+    /// Get user points and deposit summary for a specific address.
+    /// Includes points migrated or delegated from other addresses; deposit amounts follow position history.
+    pub async fn get_user_points(&self, owner: Address, rules: &PointsRules) -> Result<UserPoints> {
+        // Get all positions whose points can belong to the user
+        let mut positions = Vec::new();
+        for source in rules.point_sources(owner) {
+            positions.extend(self.fetch_positions(Some(&source.to_string())).await?);
         }
+
+        let current_time = chrono::Utc::now().timestamp() as u64;
+        Ok(user_points_from(owner, &positions, rules, current_time))
     }
 
-    // Update last processed block
-    pub async fn update_last_processed_block(&self, block: u64) -> Result<()> {
-        sqlx::query(
-            "INSERT INTO sync_metadata (key, value, updated_at) 
-             VALUES ('last_processed_block', $1, CURRENT_TIMESTAMP)
-             ON CONFLICT (key) 
-             DO UPDATE SET value = EXCLUDED.value, updated_at = CURRENT_TIMESTAMP"
-        )
-        .bind(block.to_string())
-        .execute(&self.pool)
-        .await?;
+    /// Statement of every accrual period behind the points of `owner`
+    pub async fn get_points_statement(&self, owner: Address, rules: &PointsRules) -> Result<PointsStatement> {
+        let mut positions = Vec::new();
+        for source in rules.point_sources(owner) {
+            positions.extend(self.fetch_positions(Some(&source.to_string())).await?);
+        }
 
-        Ok(())
+        let current_time = chrono::Utc::now().timestamp() as u64;
+        Ok(statement_from(owner, &positions, rules, current_time))
     }
 
-    // API Methods
-    
-    /// Get user points and deposit summary for a specific address
-    pub async fn get_user_points(&self, user_address: &str) -> Result<UserPoints> {
-        // Get all positions for the user
-        let rows = sqlx::query(
-            "SELECT nonce, amount, deposit_timestamp, status::text as status, 
-                    withdrawal_initiated_timestamp, block_number
-             FROM positions 
-             WHERE user_address = $1"
-        )
-        .bind(user_address)
-        .fetch_all(&self.pool)
-        .await?;
-
-        let mut sage_points = 0.0;
-        let mut formation_points = 0.0;
-        let mut active_amount = 0.0;
-        let mut unstaking_amount = 0.0;
-        let mut withdrawn_amount = 0.0;
-
-        let current_time = chrono::Utc::now().timestamp();
-
-        for row in rows {
-            let amount: BigDecimal = row.get("amount");
-            let amount_float = amount.to_string().parse::<f64>().unwrap_or(0.0) / 1e18;
-            let deposit_timestamp: i64 = row.get("deposit_timestamp");
-            let status: String = row.get("status");
-            let withdrawal_initiated_timestamp: Option<i64> = row.get("withdrawal_initiated_timestamp");
-
-            // Calculate points based on status
-            let end_timestamp = if let Some(withdrawal_ts) = withdrawal_initiated_timestamp {
-                withdrawal_ts
-            } else if status == "active" {
-                current_time
-            } else {
-                deposit_timestamp
-            };
-
-            let seconds_staked = (end_timestamp - deposit_timestamp) as f64;
-            let days_staked = seconds_staked / 86400.0;
-            
-            // Calculate points (0.01 SAGE per token per day, 0.005 Formation per token per day)
-            sage_points += amount_float * days_staked * 0.01;
-            formation_points += amount_float * days_staked * 0.005;
-
-            // Sum amounts by status
-            match status.as_str() {
-                "active" => active_amount += amount_float,
-                "unstaking" => unstaking_amount += amount_float,
-                "withdrawn" => withdrawn_amount += amount_float,
-                _ => {}
+    /// Get events for an address, including history migrated to it
+    pub async fn get_attributed_events(&self, owner: Address, rules: &PointsRules) -> Result<Vec<UserEvent>> {
+        let mut events = Vec::new();
+
+        for source in rules.addresses_owned_by(owner, true) {
+            let attributed_from = (source != owner).then(|| source.to_string());
+            for mut event in self.get_user_events(&source.to_string()).await? {
+                event.attributed_from = attributed_from.clone();
+                events.push(event);
             }
         }
 
-        Ok(UserPoints {
-            address: user_address.to_string(),
-            sage_points,
-            formation_points,
-            total_points: sage_points + formation_points,
-            active_amount,
-            unstaking_amount,
-            withdrawn_amount,
-        })
+        events.sort_by_key(|e| std::cmp::Reverse((e.block_number, e.timestamp)));
+        Ok(events)
     }
 
-    /// Get historical event data for a specific user
-    pub async fn get_user_events(&self, user_address: &str) -> Result<Vec<UserEvent>> {
-        let rows = sqlx::query(
-            "SELECT e.event_type, e.amount, e.nonce, e.timestamp, e.block_number,
-                    COALESCE(p.status::text, '') as status
-             FROM events e
-             LEFT JOIN positions p ON p.user_address = e.user_address AND p.nonce = e.nonce
-             WHERE e.user_address = $1
-             ORDER BY e.block_number DESC, e.timestamp DESC"
-        )
-        .bind(user_address)
-        .fetch_all(&self.pool)
-        .await?;
+    /// Get positions for an address, including history migrated to it
+    pub async fn get_attributed_positions(&self, owner: Address, rules: &PointsRules) -> Result<Vec<UserPosition>> {
+        let mut positions = Vec::new();
 
+        for source in rules.addresses_owned_by(owner, true) {
+            let attributed_from = (source != owner).then(|| source.to_string());
+            for mut position in self.get_user_positions(&source.to_string()).await? {
+                position.attributed_from = attributed_from.clone();
+                position.lock_multiplier = rules.lock_multiplier(position.lock_duration);
+                positions.push(position);
+            }
+        }
+
+        Ok(positions)
+    }
+
+    /// Get the staking events of a transaction with their effect on each position
+    pub async fn get_tx_events(&self, tx_hash: &str, rules: &PointsRules) -> Result<Vec<TxEvent>> {
+        let current_time = chrono::Utc::now().timestamp() as u64;
         let mut events = Vec::new();
-        for row in rows {
-            let amount: Option<BigDecimal> = row.get("amount");
-            let amount_str = if let Some(amt) = amount {
-                format!("{:.6}", amt.to_string().parse::<f64>().unwrap_or(0.0) / 1e18)
-            } else {
-                "0.000000".to_string()
-            };
-            
-            events.push(UserEvent {
-                event_type: row.get("event_type"),
-                amount: amount_str,
-                nonce: row.get("nonce"),
-                timestamp: DateTime::from_timestamp(row.get("timestamp"), 0).unwrap_or_default(),
-                block_number: row.get("block_number"),
-                status: row.get("status"),
+
+        for event in self.load_tx_events(tx_hash).await? {
+            let position = self.fetch_positions(Some(&event.user.to_string())).await?
+                .into_iter()
+                .find(|p| p.nonce == event.nonce);
+            let points = position.as_ref()
+                .map(|p| rules.position_points(p, current_time))
+                .unwrap_or_default();
+            let (status_before, status_after) = status_transition(&event.event_type);
+            let amount = event.amount.unwrap_or_default();
+
+            events.push(TxEvent {
+                address: event.user.to_string(),
+                nonce: event.nonce,
+                amount: format!("{:.6}", format_token_amount_as_float(amount)),
+                amount_wei: amount.to_string(),
+                unlocks_at: event.unlocks_at.and_then(|t| DateTime::from_timestamp(t as i64, 0)),
+                block_number: event.block_number as i64,
+                timestamp: DateTime::from_timestamp(event.timestamp as i64, 0).unwrap_or_default(),
+                status_before: status_before.map(str::to_string),
+                status_after: status_after.to_string(),
+                position_status: position.as_ref().map(|p| status_name(&p.status).to_string()),
+                position_sage_points: points.sage_points,
+                position_formation_points: points.formation_points,
+                points_owner: rules.owner(event.user).to_string(),
+                event_type: event.event_type,
             });
         }
 
         Ok(events)
     }
 
-    /// Get the top users by total points
-    pub async fn get_leaderboard(&self, limit: i64) -> Result<Vec<LeaderboardEntry>> {
-        // Complex query to calculate points for all users
-        let rows = sqlx::query(
-            "WITH user_points AS (
-                SELECT 
-                    user_address,
-                    SUM(
-                        CAST(amount AS FLOAT) / 1e18 * 
-                        (EXTRACT(EPOCH FROM (
-                            CASE 
-                                WHEN withdrawal_initiated_timestamp IS NOT NULL THEN 
-                                    to_timestamp(withdrawal_initiated_timestamp)
-                                WHEN status = 'active' THEN 
-                                    NOW()
-                                ELSE 
-                                    to_timestamp(deposit_timestamp)
-                            END
-                        )) - deposit_timestamp) / 86400.0 * 0.01
-                    ) AS sage_points,
-                    SUM(
-                        CAST(amount AS FLOAT) / 1e18 * 
-                        (EXTRACT(EPOCH FROM (
-                            CASE 
-                                WHEN withdrawal_initiated_timestamp IS NOT NULL THEN 
-                                    to_timestamp(withdrawal_initiated_timestamp)
-                                WHEN status = 'active' THEN 
-                                    NOW()
-                                ELSE 
-                                    to_timestamp(deposit_timestamp)
-                            END
-                        )) - deposit_timestamp) / 86400.0 * 0.005
-                    ) AS formation_points
-                FROM positions
-                GROUP BY user_address
-            )
-            SELECT 
-                user_address,
-                sage_points,
-                formation_points,
-                (sage_points + formation_points) AS total_points,
-                ROW_NUMBER() OVER (ORDER BY (sage_points + formation_points) DESC) AS rank
-            FROM user_points
-            ORDER BY total_points DESC
-            LIMIT $1"
-        )
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await?;
-
-        let mut leaderboard = Vec::new();
-        for row in rows {
-            leaderboard.push(LeaderboardEntry {
-                rank: row.get::<i64, _>("rank") as i32,
-                address: row.get("user_address"),
-                sage_points: row.get::<f64, _>("sage_points"),
-                formation_points: row.get::<f64, _>("formation_points"),
-                total_points: row.get::<f64, _>("total_points"),
-            });
+    /// Get points, rank, positions, recent events, next unlock and active boosts in one call
+    pub async fn get_user_summary(&self, owner: Address, recent_events: usize, rules: &PointsRules) -> Result<UserSummary> {
+        let now = chrono::Utc::now();
+        let current_time = now.timestamp() as u64;
+
+        let points = self.get_user_points(owner, rules).await?;
+        let address = owner.to_string();
+        let rank = self.get_leaderboard(i64::MAX, LeaderboardSort::Total, LeaderboardWindow::All, rules).await?
+            .into_iter()
+            .find(|entry| entry.address == address)
+            .map(|entry| entry.rank);
+
+        let positions = self.get_attributed_positions(owner, rules).await?;
+        let next_unlock_at = positions.iter()
+            .filter(|p| p.status == "unstaking")
+            .filter_map(|p| p.unlocks_at)
+            .filter(|unlocks_at| *unlocks_at > now)
+            .min();
+
+        let mut events = self.get_attributed_events(owner, rules).await?;
+        events.truncate(recent_events);
+
+        Ok(UserSummary {
+            points,
+            rank,
+            positions,
+            recent_events: events,
+            next_unlock_at,
+            active_boosts: rules.boosts.iter()
+                .filter(|b| b.start <= current_time && current_time < b.end)
+                .cloned()
+                .collect(),
+        })
+    }
+
+    /// Get one position with its events, status transitions and accrual segments
+    pub async fn get_position_detail(&self, user: Address, nonce: U256, rules: &PointsRules) -> Result<Option<PositionDetail>> {
+        let address = user.to_string();
+        let Some(position) = self.fetch_positions(Some(&address)).await?
+            .into_iter()
+            .find(|p| p.nonce == nonce)
+        else {
+            return Ok(None);
+        };
+
+        let current_time = chrono::Utc::now().timestamp() as u64;
+        let points = rules.position_points(&position, current_time);
+
+        let events = self.load_position_events(&address, nonce).await?
+            .into_iter()
+            .map(|event| {
+                let (status_before, status_after) = status_transition(&event.event_type);
+                let amount = event.amount.unwrap_or_default();
+                PositionEvent {
+                    amount: format!("{:.6}", format_token_amount_as_float(amount)),
+                    amount_wei: amount.to_string(),
+                    unlocks_at: event.unlocks_at.and_then(|t| DateTime::from_timestamp(t as i64, 0)),
+                    block_number: event.block_number as i64,
+                    tx_hash: event.tx_hash,
+                    timestamp: DateTime::from_timestamp(event.timestamp as i64, 0).unwrap_or_default(),
+                    status_before: status_before.map(str::to_string),
+                    status_after: status_after.to_string(),
+                    event_type: event.event_type,
+                }
+            })
+            .collect();
+
+        Ok(Some(PositionDetail {
+            address,
+            points_owner: rules.owner(user).to_string(),
+            sage_points: points.sage_points,
+            formation_points: points.formation_points,
+            total_points: points.sage_points + points.formation_points,
+            events,
+            segments: rules.position_segments(&position, current_time),
+            position: UserPosition {
+                nonce: position.nonce,
+                amount: format!("{:.6}", format_token_amount_as_float(position.amount)),
+                amount_wei: position.amount.to_string(),
+                status: status_name(&position.status).to_string(),
+                deposit_timestamp: DateTime::from_timestamp(position.deposit_timestamp as i64, 0).unwrap_or_default(),
+                withdrawal_initiated_timestamp: position.withdrawal_initiated_timestamp
+                    .and_then(|t| DateTime::from_timestamp(t as i64, 0)),
+                unlocks_at: position.unlocks_at.and_then(|t| DateTime::from_timestamp(t as i64, 0)),
+                block_number: position.block_number as i64,
+                lock_duration: position.lock_duration,
+                lock_multiplier: rules.lock_multiplier(position.lock_duration),
+                attributed_from: None,
+            },
+        }))
+    }
+
+    /// Get the top users, ranked by `sort` (highest first), counting only points earned within `window`
+    pub async fn get_leaderboard(
+        &self,
+        limit: i64,
+        sort: LeaderboardSort,
+        window: LeaderboardWindow,
+        rules: &PointsRules,
+    ) -> Result<Vec<LeaderboardEntry>> {
+        let current_time = chrono::Utc::now().timestamp() as u64;
+        let positions = self.fetch_positions(None).await?;
+        Ok(leaderboard_from(&positions, limit, sort, window, rules, current_time))
+    }
+
+    /// Standings of a competition period: the archived winners once it has ended, live
+    /// standings before that
+    pub async fn get_competition_standings(
+        &self,
+        board: CompetitionBoard,
+        period: u64,
+        rules: &PointsRules,
+    ) -> Result<Option<CompetitionStandings>> {
+        if let Some(archived) = self.load_competition_standings(board, period).await? {
+            return Ok(Some(archived));
+        }
+        let current_time = chrono::Utc::now().timestamp() as u64;
+        let positions = self.fetch_positions(None).await?;
+        Ok(competition_from(&positions, board, period, rules, current_time))
+    }
+
+    /// Rank snapshots of `owner` over the last `days`, with the change over the last week
+    pub async fn get_rank_history(&self, owner: Address, days: i64) -> Result<RankHistory> {
+        let now = Utc::now().timestamp() as u64;
+        // Look back at least 8 days so the snapshot from a week ago is included
+        let lookback = days.max(8) as u64 * 86400;
+        let mut history = self.load_rank_history(&owner.to_string(), now.saturating_sub(lookback)).await?;
+
+        let current_rank = history.last().map(|p| p.rank);
+        let week_ago = now.saturating_sub(7 * 86400) as i64;
+        let rank_change_7d = history.iter()
+            .rev()
+            .find(|p| p.timestamp.timestamp() <= week_ago)
+            .zip(current_rank)
+            .map(|(then, current)| then.rank - current);
+
+        let since = now.saturating_sub(days as u64 * 86400) as i64;
+        history.retain(|p| p.timestamp.timestamp() >= since);
+
+        Ok(RankHistory {
+            address: owner.to_string(),
+            current_rank,
+            rank_change_7d,
+            history,
+        })
+    }
+
+    /// Changes between the rank snapshots taken at `from` and `to`, None when either
+    /// doesn't exist
+    pub async fn get_snapshot_diff(&self, from: u64, to: u64, sort: SnapshotDiffSort, offset: usize, limit: usize) -> Result<Option<SnapshotDiff>> {
+        let (Some(from), Some(to)) = (self.load_rank_snapshot(from).await?, self.load_rank_snapshot(to).await?) else {
+            return Ok(None);
+        };
+        Ok(Some(snapshot_diff_from(from, to, sort, offset, limit)))
+    }
+
+    /// Points emitted per day over the last `days` days
+    pub async fn get_emissions(&self, days: i64, rules: &PointsRules) -> Result<EmissionsReport> {
+        let current_time = chrono::Utc::now().timestamp() as u64;
+        let positions = self.fetch_positions(None).await?;
+        Ok(emissions_from(&positions, days, rules, current_time))
+    }
+
+    /// Projected token allocation of `owner`, None when no allocation is configured
+    pub async fn get_allocation(&self, owner: Address, rules: &PointsRules) -> Result<Option<AllocationPreview>> {
+        let current_time = chrono::Utc::now().timestamp() as u64;
+        let positions = self.fetch_positions(None).await?;
+        Ok(allocation_from(owner, &positions, rules, current_time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::points::{DailyCap, LockTier, PauseWindow};
+    use std::collections::HashMap;
+
+    const ALICE: Address = Address::repeat_byte(0xa1);
+    const BOB: Address = Address::repeat_byte(0xb0);
+    const DAY: u64 = 86400;
+    const START: u64 = 1_700_006_400; // Midnight UTC
+    const NOW: u64 = START + 10 * DAY;
+
+    fn position(user: Address, nonce: u64, tokens: u64, deposit: u64, withdrawn: Option<u64>, lock_duration: u64) -> Position {
+        Position {
+            user,
+            nonce: U256::from(nonce),
+            amount: U256::from(tokens) * U256::from(10u64).pow(U256::from(18)),
+            deposit_timestamp: deposit,
+            status: if withdrawn.is_some() { PositionStatus::Unstaking } else { PositionStatus::Active },
+            withdrawal_initiated_timestamp: withdrawn,
+            unlocks_at: withdrawn.map(|t| t + 7 * DAY),
+            block_number: 1,
+            lock_duration,
+        }
+    }
+
+    fn positions() -> Vec<Position> {
+        vec![
+            position(ALICE, 1, 1_000, START, None, 0),
+            position(ALICE, 2, 200, START + DAY / 2, Some(START + 6 * DAY), 90 * DAY),
+            position(BOB, 1, 300, START + 3 * DAY, None, 0),
+        ]
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6, "{} != {}", actual, expected);
+    }
+
+    // Points of each owner's positions, each computed on its own
+    fn position_sums(rules: &PointsRules, positions: &[Position]) -> HashMap<Address, f64> {
+        let mut sums = HashMap::new();
+        for position in positions {
+            *sums.entry(position.user).or_default() += rules.position_points(position, NOW).total();
         }
+        sums
+    }
+
+    // The leaderboard, user points and statement all credit what `owner_totals` does
+    fn assert_read_paths_agree(rules: &PointsRules, positions: &[Position]) -> HashMap<Address, OwnerTotals> {
+        let totals = rules.owner_totals(positions, 0, NOW);
+        let leaderboard = leaderboard_from(positions, 100, LeaderboardSort::Total, LeaderboardWindow::All, rules, NOW);
+        assert_eq!(leaderboard.len(), totals.len());
+
+        for entry in &leaderboard {
+            let owner: Address = entry.address.parse().unwrap();
+            let expected = &totals[&owner].points;
+            assert_close(entry.sage_points, expected.sage_points);
+            assert_close(entry.formation_points, expected.formation_points);
+
+            let user = user_points_from(owner, positions, rules, NOW);
+            assert_close(user.sage_points, expected.sage_points);
+            assert_close(user.formation_points, expected.formation_points);
+
+            let statement = statement_from(owner, positions, rules, NOW);
+            assert_close(statement.total_points, expected.total());
+            let lines: f64 = statement.lines.iter().map(|l| l.sage_points + l.formation_points).sum();
+            assert_close(lines, expected.total());
+        }
+        totals
+    }
+
+    #[test]
+    fn uncapped_totals_are_the_sum_of_positions() {
+        let rules = PointsRules::default();
+        let positions = positions();
+
+        let totals = assert_read_paths_agree(&rules, &positions);
+
+        for (owner, sum) in position_sums(&rules, &positions) {
+            assert_close(totals[&owner].points.total(), sum);
+        }
+        // 1000 tokens for 10 days and 200 for 5.5, at 0.015 points per token per day
+        assert_close(totals[&ALICE].points.total(), (10_000.0 + 1_100.0) * 0.015);
+    }
+
+    #[test]
+    fn daily_caps_apply_to_every_read_path() {
+        let rules = PointsRules {
+            daily_caps: vec![DailyCap { start: START + 2 * DAY, end: START + 8 * DAY, max_points: 5.0 }],
+            ..Default::default()
+        };
+        let positions = positions();
+
+        let totals = assert_read_paths_agree(&rules, &positions);
+
+        // Alice earns 18 a day until day 6 and 15 after, so all 6 capped days give 5
+        let uncapped = (10_000.0 + 1_100.0) * 0.015;
+        let capped_days = 4.0 * 18.0 + 2.0 * 15.0;
+        assert_close(totals[&ALICE].points.total(), uncapped - capped_days + 6.0 * 5.0);
+        assert!(position_sums(&rules, &positions)[&ALICE] > totals[&ALICE].points.total());
+        // Bob's 4.5 a day stays under the cap
+        assert_close(totals[&BOB].points.total(), 7.0 * 300.0 * 0.015);
+    }
+
+    #[test]
+    fn windows_starting_mid_day_get_their_share_of_the_daily_cap() {
+        let rules = PointsRules {
+            daily_caps: vec![DailyCap { start: START, end: START + 10 * DAY, max_points: 3.0 }],
+            ..Default::default()
+        };
+        // Bob earns 4.5 a day, capped to 3
+        let positions = vec![position(BOB, 1, 300, START + 3 * DAY, None, 0)];
+        let window = |since| rules.owner_totals(&positions, since, START + 6 * DAY)[&BOB].points.total();
+
+        assert_close(window(START + 4 * DAY), 2.0 * 3.0);
+        // Half of day 4 keeps half of its capped 3, not a whole day's cap
+        assert_close(window(START + 4 * DAY + DAY / 2), 1.5 + 3.0);
+        assert_close(window(0), 3.0 * 3.0);
+    }
+
+    #[test]
+    fn lock_tiers_agree_between_positions_segments_and_totals() {
+        let rules = PointsRules {
+            lock_tiers: vec![LockTier { min_duration: 30 * DAY, multiplier: 2.0 }],
+            ..Default::default()
+        };
+        let positions = positions();
+
+        let totals = assert_read_paths_agree(&rules, &positions);
+
+        for (owner, sum) in position_sums(&rules, &positions) {
+            assert_close(totals[&owner].points.total(), sum);
+        }
+        let locked = &positions[1];
+        let segments: f64 = rules.position_segments(locked, NOW).iter().map(|s| s.sage_points + s.formation_points).sum();
+        assert_close(segments, rules.position_points(locked, NOW).total());
+        assert_close(segments, 2.0 * 1_100.0 * 0.015);
+    }
+
+    #[test]
+    fn pauses_agree_between_positions_and_totals() {
+        let rules = PointsRules {
+            pauses: vec![PauseWindow { id: 1, start: START + 4 * DAY, end: START + 6 * DAY, reason: "incident".into() }],
+            ..Default::default()
+        };
+        let positions = positions();
+
+        let totals = assert_read_paths_agree(&rules, &positions);
+
+        for (owner, sum) in position_sums(&rules, &positions) {
+            assert_close(totals[&owner].points.total(), sum);
+        }
+        // Two days of Alice's first position and Bob's, and her second, are paused
+        assert_close(totals[&ALICE].points.total(), (8_000.0 + 700.0) * 0.015);
+        assert_close(totals[&BOB].points.total(), 5.0 * 300.0 * 0.015);
+    }
 
-        Ok(leaderboard)
+    #[test]
+    fn checkpointed_capped_totals_match_a_full_read() {
+        let mut rules = PointsRules {
+            daily_caps: vec![DailyCap { start: START + 2 * DAY, end: START + 8 * DAY, max_points: 5.0 }],
+            ..Default::default()
+        };
+        let positions = positions();
+        let expected = rules.owner_totals(&positions, 0, NOW);
+
+        // Taken mid-day, then again from the first one
+        rules.checkpoints = Arc::new(rules.checkpoint(&positions, START + 4 * DAY + DAY / 2));
+        rules.checkpoints = Arc::new(rules.checkpoint(&positions, START + 7 * DAY + DAY / 3));
+        let totals = assert_read_paths_agree(&rules, &positions);
+        for owner in [ALICE, BOB] {
+            assert_close(totals[&owner].points.total(), expected[&owner].points.total());
+        }
+
+        // A position that changed since isn't covered by the checkpoint any more
+        let mut changed = positions.clone();
+        changed[2] = position(BOB, 1, 300, START + 3 * DAY, Some(START + 5 * DAY), 0);
+        let fresh = PointsRules { daily_caps: rules.daily_caps.clone(), ..Default::default() };
+        let totals = rules.owner_totals(&changed, 0, NOW);
+        assert_close(totals[&BOB].points.total(), fresh.owner_totals(&changed, 0, NOW)[&BOB].points.total());
+        assert_close(totals[&BOB].points.total(), 2.0 * 300.0 * 0.015);
+    }
+
+    #[test]
+    fn snapshot_diffs_and_allocation_previews_get_display_points() {
+        let display = crate::points::DisplayPoints::default();
+        let diff = SnapshotDiffEntry {
+            address: ALICE.to_string(),
+            from_rank: Some(2),
+            to_rank: Some(1),
+            rank_change: Some(1),
+            from_points: 1_500.0,
+            to_points: 4_200.0,
+            points_delta: 2_700.0,
+        };
+        let preview = AllocationPreview {
+            address: ALICE.to_string(),
+            basis: AllocationBasis::Total,
+            points: 4_200.0,
+            eligible: true,
+            pool_points: 10_000.0,
+            share: 0.42,
+            projected_tokens: 42.0,
+            total_tokens: 100.0,
+        };
+
+        let mut value = serde_json::json!({ "diff": diff, "preview": preview });
+        display.annotate(&mut value);
+        for key in ["from_points", "to_points", "points_delta"] {
+            assert!(value["diff"][format!("{}_display", key)].is_i64(), "{} has no display value", key);
+        }
+        for key in ["points", "pool_points"] {
+            assert!(value["preview"][format!("{}_display", key)].is_i64(), "{} has no display value", key);
+        }
+        assert!(value["preview"].get("share_display").is_none());
     }
 }
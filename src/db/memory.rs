@@ -0,0 +1,578 @@
+use alloy::primitives::{Address, U256};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate};
+use eyre::{eyre, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use super::{
+    build_staker_metrics, AddressLabel, CompetitionStandings, EventData, PointsRoot, PointsStore, RankPoint, RankSnapshot, StakerMetrics, StoredEvent,
+    TvlPoint, TvlSnapshot, UserEvent, UserPosition, WithdrawablePosition,
+};
+use crate::notifications::NotificationPreferences;
+use crate::profiles::Profile;
+use crate::scheduler::{JobRun, JobState};
+use crate::points::{AddressMigration, CompetitionBoard, Delegation, PauseWindow, RatePeriod};
+use crate::{format_token_amount_as_float, Position, PositionStatus};
+
+fn status_str(status: &PositionStatus) -> &'static str {
+    match status {
+        PositionStatus::Active => "active",
+        PositionStatus::Unstaking => "unstaking",
+        PositionStatus::Withdrawn => "withdrawn",
+    }
+}
+
+fn wei_to_display(amount: U256) -> String {
+    format!("{:.6}", format_token_amount_as_float(amount))
+}
+
+fn utc_date(timestamp: u64) -> NaiveDate {
+    DateTime::from_timestamp(timestamp as i64, 0).unwrap_or_default().date_naive()
+}
+
+#[derive(Default)]
+struct MemoryState {
+    positions: BTreeMap<(Address, U256), Position>,
+    events: Vec<StoredEvent>,
+    last_processed_block: Option<u64>,
+    bootstrap_block: Option<u64>,
+    rate_periods: Vec<RatePeriod>,
+    pause_windows: Vec<PauseWindow>,
+    next_pause_id: i32,
+    address_migrations: Vec<AddressMigration>,
+    delegations: Vec<Delegation>,
+    address_labels: HashMap<Address, AddressLabel>,
+    notification_preferences: HashMap<Address, NotificationPreferences>,
+    profiles: HashMap<Address, Profile>,
+    jobs: BTreeMap<String, JobState>,
+    stakers: HashMap<Address, u64>,            // First-seen timestamp
+    tvl_history: Vec<(u64, u64, U256, U256)>, // (timestamp, block, active, unstaking)
+    rank_history: Vec<(u64, u64, Address, i32, f64)>, // (timestamp, block, owner, rank, total points)
+    competitions: BTreeMap<(CompetitionBoard, u64), CompetitionStandings>, // Archived periods
+    points_roots: Vec<PointsRoot>,
+}
+
+/// Ephemeral storage backend for `--no-db` development runs.
+/// Everything the tracker writes is kept in process memory and lost on exit.
+#[derive(Default)]
+pub struct MemoryStore {
+    state: Mutex<MemoryState>,
+}
+
+#[async_trait]
+impl PointsStore for MemoryStore {
+    async fn fetch_positions(&self, user_address: Option<&str>) -> Result<Vec<Position>> {
+        let state = self.state.lock().unwrap();
+
+        Ok(state.positions
+            .values()
+            .filter(|p| user_address.is_none_or(|user| p.user.to_string() == user))
+            .cloned()
+            .collect())
+    }
+
+    async fn load_rate_periods(&self) -> Result<Vec<RatePeriod>> {
+        Ok(self.state.lock().unwrap().rate_periods.clone())
+    }
+
+    async fn save_rate_period(&self, period: &RatePeriod) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.rate_periods.retain(|p| p.effective_from != period.effective_from);
+        state.rate_periods.push(period.clone());
+        state.rate_periods.sort_by_key(|p| p.effective_from);
+        Ok(())
+    }
+
+    async fn save_position(&self, position: &Position) -> Result<()> {
+        self.state.lock().unwrap()
+            .positions
+            .insert((position.user, position.nonce), position.clone());
+        Ok(())
+    }
+
+    async fn save_event(&self, event: EventData) -> Result<()> {
+        self.state.lock().unwrap().events.push(StoredEvent {
+            event_type: event.event_type,
+            user: event.user,
+            nonce: event.nonce.unwrap_or_default(),
+            amount: event.amount,
+            unlocks_at: event.unlocks_at,
+            lock_duration: event.lock_duration,
+            block_number: event.block_number,
+            log_index: event.log_index,
+            tx_hash: event.tx_hash,
+            timestamp: event.timestamp,
+        });
+        Ok(())
+    }
+
+    async fn load_events(&self) -> Result<Vec<StoredEvent>> {
+        let mut events = self.state.lock().unwrap().events.clone();
+        // Stable sort keeps insertion order for events without a log index
+        events.sort_by_key(|e| (e.block_number, e.log_index));
+        Ok(events)
+    }
+
+    async fn load_position_events(&self, user_address: &str, nonce: U256) -> Result<Vec<StoredEvent>> {
+        Ok(self.load_events().await?
+            .into_iter()
+            .filter(|e| e.user.to_string() == user_address && e.nonce == nonce)
+            .collect())
+    }
+
+    async fn load_tx_events(&self, tx_hash: &str) -> Result<Vec<StoredEvent>> {
+        Ok(self.load_events().await?
+            .into_iter()
+            .filter(|e| e.tx_hash == tx_hash)
+            .collect())
+    }
+
+    async fn get_last_processed_block(&self) -> Result<Option<u64>> {
+        Ok(self.state.lock().unwrap().last_processed_block)
+    }
+
+    async fn update_last_processed_block(&self, block: u64) -> Result<()> {
+        self.state.lock().unwrap().last_processed_block = Some(block);
+        Ok(())
+    }
+
+    async fn get_bootstrap_block(&self) -> Result<Option<u64>> {
+        Ok(self.state.lock().unwrap().bootstrap_block)
+    }
+
+    async fn set_bootstrap_block(&self, block: u64) -> Result<()> {
+        self.state.lock().unwrap().bootstrap_block = Some(block);
+        Ok(())
+    }
+
+    async fn load_pause_windows(&self) -> Result<Vec<PauseWindow>> {
+        let mut pauses = self.state.lock().unwrap().pause_windows.clone();
+        pauses.sort_by_key(|p| p.start);
+        Ok(pauses)
+    }
+
+    async fn add_pause_window(&self, start: u64, end: u64, reason: &str) -> Result<i32> {
+        let mut state = self.state.lock().unwrap();
+        state.next_pause_id += 1;
+        let id = state.next_pause_id;
+        state.pause_windows.push(PauseWindow {
+            id,
+            start,
+            end,
+            reason: reason.to_string(),
+        });
+        Ok(id)
+    }
+
+    async fn remove_pause_window(&self, id: i32) -> Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        let before = state.pause_windows.len();
+        state.pause_windows.retain(|p| p.id != id);
+        Ok(state.pause_windows.len() != before)
+    }
+
+    async fn load_address_migrations(&self) -> Result<Vec<AddressMigration>> {
+        Ok(self.state.lock().unwrap().address_migrations.clone())
+    }
+
+    async fn migrate_address(&self, migration: &AddressMigration) -> Result<i32> {
+        let mut state = self.state.lock().unwrap();
+        if state.address_migrations.iter().any(|m| m.from == migration.from) {
+            return Err(eyre!("{} has already been migrated", migration.from));
+        }
+
+        // Ids start at 1 like the SQL backends; there is no audit log in memory
+        let id = state.address_migrations.len() as i32 + 1;
+        state.address_migrations.push(AddressMigration { id, ..migration.clone() });
+        Ok(id)
+    }
+
+    async fn load_delegations(&self) -> Result<Vec<Delegation>> {
+        Ok(self.state.lock().unwrap().delegations.clone())
+    }
+
+    async fn save_delegation(&self, delegation: &Delegation) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let stored = state.delegations.iter()
+            .any(|d| d.tx_hash == delegation.tx_hash && d.delegator == delegation.delegator);
+        if !stored {
+            state.delegations.push(delegation.clone());
+        }
+        Ok(())
+    }
+
+    async fn replace_positions(&self, positions: &[Position]) -> Result<()> {
+        self.state.lock().unwrap().positions = positions.iter().map(|p| ((p.user, p.nonce), p.clone())).collect();
+        Ok(())
+    }
+
+    async fn rollback_to_block(&self, block: u64, positions: &[Position]) -> Result<u64> {
+        let mut state = self.state.lock().unwrap();
+
+        let before = state.events.len();
+        state.events.retain(|e| e.block_number <= block);
+        let removed = (before - state.events.len()) as u64;
+        state.delegations.retain(|d| d.block_number <= block);
+
+        // Stakers are only remembered by first-seen time, keep those with a remaining deposit
+        let state = &mut *state;
+        let events = &state.events;
+        state.stakers.retain(|user, _| events.iter().any(|e| e.user == *user && e.event_type == "Deposit"));
+        state.tvl_history.retain(|snapshot| snapshot.1 <= block);
+        state.rank_history.retain(|snapshot| snapshot.1 <= block);
+
+        state.positions = positions.iter().map(|p| ((p.user, p.nonce), p.clone())).collect();
+        state.last_processed_block = Some(block);
+        Ok(removed)
+    }
+
+    async fn get_address_label(&self, address: &Address) -> Result<Option<AddressLabel>> {
+        Ok(self.state.lock().unwrap().address_labels.get(address).cloned())
+    }
+
+    async fn save_address_label(&self, label: &AddressLabel) -> Result<()> {
+        self.state.lock().unwrap()
+            .address_labels
+            .insert(label.address, label.clone());
+        Ok(())
+    }
+
+    async fn save_address_labels(&self, labels: &[AddressLabel], _source: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        for label in labels {
+            state.address_labels.insert(label.address, label.clone());
+        }
+        Ok(())
+    }
+
+    async fn load_address_labels(&self) -> Result<Vec<AddressLabel>> {
+        Ok(self.state.lock().unwrap().address_labels.values().cloned().collect())
+    }
+
+    async fn get_notification_preferences(&self, address: &Address) -> Result<Option<NotificationPreferences>> {
+        Ok(self.state.lock().unwrap().notification_preferences.get(address).cloned())
+    }
+
+    async fn save_notification_preferences(&self, preferences: &NotificationPreferences) -> Result<()> {
+        self.state.lock().unwrap()
+            .notification_preferences
+            .insert(preferences.address, preferences.clone());
+        Ok(())
+    }
+
+    async fn delete_notification_preferences(&self, address: &Address) -> Result<bool> {
+        Ok(self.state.lock().unwrap().notification_preferences.remove(address).is_some())
+    }
+
+    async fn load_notification_preferences(&self) -> Result<Vec<NotificationPreferences>> {
+        Ok(self.state.lock().unwrap()
+            .notification_preferences
+            .values()
+            .filter(|p| !p.events.is_empty())
+            .cloned()
+            .collect())
+    }
+
+    async fn get_profile(&self, address: &Address) -> Result<Option<Profile>> {
+        Ok(self.state.lock().unwrap().profiles.get(address).cloned())
+    }
+
+    async fn save_profile(&self, profile: &Profile) -> Result<()> {
+        self.state.lock().unwrap().profiles.insert(profile.address, profile.clone());
+        Ok(())
+    }
+
+    async fn delete_profile(&self, address: &Address) -> Result<bool> {
+        Ok(self.state.lock().unwrap().profiles.remove(address).is_some())
+    }
+
+    async fn get_profiles(&self, addresses: &[Address]) -> Result<Vec<Profile>> {
+        let state = self.state.lock().unwrap();
+        Ok(addresses.iter().filter_map(|a| state.profiles.get(a).cloned()).collect())
+    }
+
+    async fn load_profiles(&self) -> Result<Vec<Profile>> {
+        Ok(self.state.lock().unwrap().profiles.values().cloned().collect())
+    }
+
+    async fn try_lock_job(&self, name: &str, owner: &str, now: u64, due: u64, lease_until: u64) -> Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        let job = state.jobs.entry(name.to_string()).or_insert_with(|| JobState { name: name.to_string(), ..Default::default() });
+        if job.locked_until.is_some_and(|t| t >= now) || job.last_started_at.is_some_and(|t| t >= due) {
+            return Ok(false);
+        }
+        job.locked_by = Some(owner.to_string());
+        job.locked_until = Some(lease_until);
+        job.last_started_at = Some(now);
+        Ok(true)
+    }
+
+    async fn finish_job(&self, name: &str, owner: &str, run: &JobRun) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(job) = state.jobs.get_mut(name).filter(|j| j.locked_by.as_deref() == Some(owner)) {
+            job.locked_by = None;
+            job.locked_until = None;
+            job.last_finished_at = Some(run.finished_at);
+            job.last_status = Some(if run.ok { "ok" } else { "failed" }.to_string());
+            job.last_message = Some(run.message.clone());
+            job.last_duration_ms = Some(run.duration_ms);
+            job.run_count += 1;
+        }
+        Ok(())
+    }
+
+    async fn load_job_states(&self) -> Result<Vec<JobState>> {
+        Ok(self.state.lock().unwrap().jobs.values().cloned().collect())
+    }
+
+    async fn record_staker(&self, user: &Address, timestamp: u64, _block_number: u64) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let first_seen = state.stakers.entry(*user).or_insert(timestamp);
+        *first_seen = (*first_seen).min(timestamp);
+        Ok(())
+    }
+
+    async fn save_tvl_snapshot(&self, timestamp: u64, block_number: u64, active: U256, unstaking: U256) -> Result<()> {
+        self.state.lock().unwrap()
+            .tvl_history
+            .push((timestamp, block_number, active, unstaking));
+        Ok(())
+    }
+
+    async fn get_last_tvl_timestamp(&self) -> Result<Option<u64>> {
+        Ok(self.state.lock().unwrap().tvl_history.iter().map(|s| s.0).max())
+    }
+
+    async fn load_tvl_snapshots(&self) -> Result<Vec<TvlSnapshot>> {
+        let mut snapshots: Vec<TvlSnapshot> = self.state.lock().unwrap()
+            .tvl_history
+            .iter()
+            .map(|&(timestamp, block_number, active_amount, unstaking_amount)| TvlSnapshot { timestamp, block_number, active_amount, unstaking_amount })
+            .collect();
+        snapshots.sort_by_key(|s| s.timestamp);
+        Ok(snapshots)
+    }
+
+    async fn save_rank_snapshot(&self, timestamp: u64, block_number: u64, ranks: &[(Address, i32, f64)]) -> Result<()> {
+        self.state.lock().unwrap()
+            .rank_history
+            .extend(ranks.iter().map(|&(owner, rank, points)| (timestamp, block_number, owner, rank, points)));
+        Ok(())
+    }
+
+    async fn get_last_rank_timestamp(&self) -> Result<Option<u64>> {
+        Ok(self.state.lock().unwrap().rank_history.iter().map(|s| s.0).max())
+    }
+
+    async fn load_rank_history(&self, user_address: &str, since: u64) -> Result<Vec<RankPoint>> {
+        Ok(self.state.lock().unwrap()
+            .rank_history
+            .iter()
+            .filter(|s| s.2.to_string() == user_address && s.0 >= since)
+            .map(|&(timestamp, block_number, _, rank, total_points)| RankPoint {
+                timestamp: DateTime::from_timestamp(timestamp as i64, 0).unwrap_or_default(),
+                block_number: block_number as i64,
+                rank,
+                total_points,
+            })
+            .collect())
+    }
+
+    async fn list_rank_snapshots(&self) -> Result<Vec<RankSnapshot>> {
+        let mut snapshots: BTreeMap<u64, RankSnapshot> = BTreeMap::new();
+        for &(timestamp, block_number, ..) in &self.state.lock().unwrap().rank_history {
+            let snapshot = snapshots.entry(timestamp).or_insert(RankSnapshot { timestamp, block_number, owners: 0 });
+            snapshot.block_number = snapshot.block_number.max(block_number);
+            snapshot.owners += 1;
+        }
+        Ok(snapshots.into_values().rev().collect())
+    }
+
+    async fn load_rank_snapshot(&self, timestamp: u64) -> Result<Option<(RankSnapshot, Vec<(Address, i32, f64)>)>> {
+        let state = self.state.lock().unwrap();
+        let rows: Vec<&(u64, u64, Address, i32, f64)> = state.rank_history.iter().filter(|s| s.0 == timestamp).collect();
+        let Some(first) = rows.first() else { return Ok(None) };
+
+        let snapshot = RankSnapshot { timestamp, block_number: first.1, owners: rows.len() as u64 };
+        Ok(Some((snapshot, rows.iter().map(|&&(_, _, owner, rank, points)| (owner, rank, points)).collect())))
+    }
+
+    async fn save_competition_standings(&self, standings: &CompetitionStandings) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.competitions
+            .entry((standings.board, standings.period))
+            .or_insert_with(|| CompetitionStandings { is_final: true, ..standings.clone() });
+        Ok(())
+    }
+
+    async fn load_competition_standings(&self, board: CompetitionBoard, period: u64) -> Result<Option<CompetitionStandings>> {
+        Ok(self.state.lock().unwrap().competitions.get(&(board, period)).cloned())
+    }
+
+    async fn get_last_archived_period(&self, board: CompetitionBoard) -> Result<Option<u64>> {
+        Ok(self.state.lock().unwrap()
+            .competitions
+            .keys()
+            .filter(|(b, _)| *b == board)
+            .map(|&(_, period)| period)
+            .max())
+    }
+
+    async fn list_competition_periods(&self) -> Result<Vec<(CompetitionBoard, u64)>> {
+        let mut periods: Vec<(CompetitionBoard, u64)> = self.state.lock().unwrap().competitions.keys().copied().collect();
+        periods.sort_by_key(|&(board, period)| (period, board));
+        Ok(periods)
+    }
+
+    async fn save_points_root(&self, root: &str, block_number: u64, leaf_count: usize, tx_hash: &str) -> Result<()> {
+        self.state.lock().unwrap().points_roots.push(PointsRoot {
+            root: root.to_string(),
+            block_number,
+            leaf_count,
+            tx_hash: tx_hash.to_string(),
+        });
+        Ok(())
+    }
+
+    async fn load_points_roots(&self) -> Result<Vec<PointsRoot>> {
+        Ok(self.state.lock().unwrap().points_roots.clone())
+    }
+
+    async fn get_user_events(&self, user_address: &str) -> Result<Vec<UserEvent>> {
+        let state = self.state.lock().unwrap();
+
+        let mut events: Vec<UserEvent> = state.events
+            .iter()
+            .filter(|e| e.user.to_string() == user_address)
+            .map(|e| {
+                let position = state.positions.get(&(e.user, e.nonce));
+                UserEvent {
+                    event_type: e.event_type.clone(),
+                    amount: e.amount.map(wei_to_display).unwrap_or_else(|| "0.000000".to_string()),
+                    amount_wei: e.amount.unwrap_or_default().to_string(),
+                    nonce: e.nonce,
+                    timestamp: DateTime::from_timestamp(e.timestamp as i64, 0).unwrap_or_default(),
+                    block_number: e.block_number as i64,
+                    status: position.map(|p| status_str(&p.status)).unwrap_or("").to_string(),
+                    unlocks_at: e.unlocks_at
+                        .or_else(|| position.and_then(|p| p.unlocks_at))
+                        .and_then(|t| DateTime::from_timestamp(t as i64, 0)),
+                    attributed_from: None,
+                }
+            })
+            .collect();
+
+        events.sort_by_key(|e| std::cmp::Reverse((e.block_number, e.timestamp)));
+        Ok(events)
+    }
+
+    async fn get_user_positions(&self, user_address: &str) -> Result<Vec<UserPosition>> {
+        let mut positions: Vec<UserPosition> = self.fetch_positions(Some(user_address)).await?
+            .into_iter()
+            .map(|p| UserPosition {
+                nonce: p.nonce,
+                amount: wei_to_display(p.amount),
+                amount_wei: p.amount.to_string(),
+                status: status_str(&p.status).to_string(),
+                deposit_timestamp: DateTime::from_timestamp(p.deposit_timestamp as i64, 0).unwrap_or_default(),
+                withdrawal_initiated_timestamp: p.withdrawal_initiated_timestamp
+                    .and_then(|t| DateTime::from_timestamp(t as i64, 0)),
+                unlocks_at: p.unlocks_at.and_then(|t| DateTime::from_timestamp(t as i64, 0)),
+                block_number: p.block_number as i64,
+                lock_duration: p.lock_duration,
+                lock_multiplier: 1.0, // Filled in from the rules by `Database`
+                attributed_from: None,
+            })
+            .collect();
+
+        positions.sort_by_key(|p| p.nonce);
+        Ok(positions)
+    }
+
+    async fn get_withdrawable_positions(&self, user_address: Option<&str>, limit: i64) -> Result<Vec<WithdrawablePosition>> {
+        let current_time = chrono::Utc::now().timestamp();
+
+        let mut unlocked: Vec<(Position, i64)> = self.fetch_positions(user_address).await?
+            .into_iter()
+            .filter(|p| matches!(p.status, PositionStatus::Unstaking))
+            .filter_map(|p| {
+                let unlocks_at = p.unlocks_at? as i64;
+                (unlocks_at <= current_time).then_some((p, unlocks_at))
+            })
+            .collect();
+        unlocked.sort_by_key(|(_, unlocks_at)| *unlocks_at);
+
+        Ok(unlocked
+            .into_iter()
+            .take(limit.max(0) as usize)
+            .map(|(p, unlocks_at)| WithdrawablePosition {
+                address: p.user.to_string(),
+                nonce: p.nonce,
+                amount: wei_to_display(p.amount),
+                amount_wei: p.amount.to_string(),
+                unlocks_at: DateTime::from_timestamp(unlocks_at, 0).unwrap_or_default(),
+                claimable_for_seconds: current_time - unlocks_at,
+            })
+            .collect())
+    }
+
+    async fn get_tvl_history(&self, interval: &str, limit: i64) -> Result<Vec<TvlPoint>> {
+        let bucket_secs = if interval == "day" { 86400 } else { 3600 };
+        let state = self.state.lock().unwrap();
+
+        // Last snapshot in each bucket
+        let mut buckets: BTreeMap<u64, (u64, u64, U256, U256)> = BTreeMap::new();
+        for snapshot in &state.tvl_history {
+            let bucket = buckets.entry(snapshot.0 / bucket_secs).or_insert(*snapshot);
+            if snapshot.0 >= bucket.0 {
+                *bucket = *snapshot;
+            }
+        }
+
+        let skip = buckets.len().saturating_sub(limit.max(0) as usize);
+        Ok(buckets
+            .into_values()
+            .skip(skip)
+            .map(|(timestamp, block_number, active, unstaking)| {
+                let active_amount = format_token_amount_as_float(active);
+                let unstaking_amount = format_token_amount_as_float(unstaking);
+                TvlPoint {
+                    timestamp: DateTime::from_timestamp(timestamp as i64, 0).unwrap_or_default(),
+                    block_number: block_number as i64,
+                    active_amount,
+                    unstaking_amount,
+                    total_staked: active_amount + unstaking_amount,
+                }
+            })
+            .collect())
+    }
+
+    async fn get_staker_metrics(&self, days: i64) -> Result<StakerMetrics> {
+        let state = self.state.lock().unwrap();
+
+        let mut new_per_day: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+        for first_seen in state.stakers.values() {
+            *new_per_day.entry(utc_date(*first_seen)).or_default() += 1;
+        }
+
+        // Churned stakers (every position withdrawn) on the day of their final withdrawal
+        let mut fully_withdrawn: HashMap<Address, bool> = HashMap::new();
+        for position in state.positions.values() {
+            *fully_withdrawn.entry(position.user).or_insert(true) &=
+                matches!(position.status, PositionStatus::Withdrawn);
+        }
+
+        let mut last_withdraw: HashMap<Address, u64> = HashMap::new();
+        for event in state.events.iter().filter(|e| e.event_type == "Withdraw") {
+            if fully_withdrawn.get(&event.user).copied().unwrap_or(false) {
+                let last = last_withdraw.entry(event.user).or_default();
+                *last = (*last).max(event.timestamp);
+            }
+        }
+
+        let churned = last_withdraw.into_values().map(utc_date).collect();
+
+        Ok(build_staker_metrics(new_per_day.into_iter().collect(), churned, days))
+    }
+}
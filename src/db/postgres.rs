@@ -0,0 +1,1341 @@
+use alloy::primitives::{Address, U256};
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::DateTime;
+use eyre::Result;
+use sqlx::{Connection, PgConnection, PgPool, postgres::{PgConnectOptions, PgPoolOptions, PgRow}, Row};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::points::{AddressMigration, CompetitionBoard, Delegation, PauseWindow, RatePeriod};
+use crate::notifications::{NotificationEvent, NotificationPreferences};
+use crate::profiles::Profile;
+use crate::scheduler::{JobRun, JobState};
+use crate::{Position, PositionStatus};
+
+use super::{
+    build_staker_metrics, AddressLabel, CompetitionEntry, CompetitionStandings, EventData, PointsRoot, PointsStore, RankPoint, RankSnapshot, StakerMetrics,
+    StoredEvent, TvlPoint, TvlSnapshot, UserEvent, UserPosition, WithdrawablePosition,
+};
+
+// NUMERIC(78, 0) columns hold full uint256 values
+fn numeric_to_u256(value: &BigDecimal) -> U256 {
+    U256::from_str(&value.to_string()).unwrap_or_default()
+}
+
+fn u256_to_numeric(value: U256) -> BigDecimal {
+    BigDecimal::from_str(&value.to_string()).unwrap_or_else(|_| BigDecimal::from(0))
+}
+
+// Map an `events` row to a stored event
+fn stored_event(row: &PgRow) -> Result<StoredEvent> {
+    let user_address: String = row.get("user_address");
+    let amount: Option<BigDecimal> = row.get("amount");
+
+    Ok(StoredEvent {
+        event_type: row.get("event_type"),
+        user: Address::from_str(&user_address)?,
+        nonce: row.get::<Option<BigDecimal>, _>("nonce").as_ref().map(numeric_to_u256).unwrap_or_default(),
+        amount: amount.and_then(|a| U256::from_str(&a.to_string()).ok()),
+        unlocks_at: row.get::<Option<i64>, _>("unlocks_at").map(|t| t as u64),
+        lock_duration: row.get::<Option<i64>, _>("lock_duration").map(|t| t as u64),
+        log_index: row.get::<Option<i64>, _>("log_index").map(|i| i as u64),
+        block_number: row.get::<i64, _>("block_number") as u64,
+        tx_hash: row.get("transaction_hash"),
+        timestamp: row.get::<i64, _>("timestamp") as u64,
+    })
+}
+
+// Map a `notification_preferences` row, skipping event types this build doesn't know
+fn notification_preferences_from_row(row: &PgRow) -> Result<NotificationPreferences> {
+    let address: String = row.get("address");
+    let events: String = row.get("events");
+
+    Ok(NotificationPreferences {
+        address: Address::from_str(&address)?,
+        webhook_url: row.get("webhook_url"),
+        discord_user_id: row.get("discord_user_id"),
+        email: row.get("email"),
+        events: events.split(',').filter_map(NotificationEvent::parse).collect(),
+    })
+}
+
+// Map a `profiles` row
+fn profile_from_row(row: &PgRow) -> Result<Profile> {
+    let address: String = row.get("address");
+
+    Ok(Profile {
+        address: Address::from_str(&address)?,
+        display_name: row.get("display_name"),
+        avatar_url: row.get("avatar_url"),
+    })
+}
+
+// Map a `scheduled_jobs` row
+fn job_state_from_row(row: &PgRow) -> JobState {
+    let timestamp = |column: &str| row.get::<Option<i64>, _>(column).map(|v| v as u64);
+
+    JobState {
+        name: row.get("name"),
+        locked_by: row.get("locked_by"),
+        locked_until: timestamp("locked_until"),
+        last_started_at: timestamp("last_started_at"),
+        last_finished_at: timestamp("last_finished_at"),
+        last_status: row.get("last_status"),
+        last_message: row.get("last_message"),
+        last_duration_ms: timestamp("last_duration_ms"),
+        run_count: row.get::<i64, _>("run_count") as u64,
+    }
+}
+
+// Insert or replace the label of an address, on the pool or inside a transaction
+async fn upsert_label(conn: &mut PgConnection, label: &AddressLabel) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO address_labels (address, label, category, updated_at)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (address) DO UPDATE SET
+            label = EXCLUDED.label,
+            category = EXCLUDED.category,
+            updated_at = EXCLUDED.updated_at"
+    )
+    .bind(label.address.to_string())
+    .bind(&label.label)
+    .bind(&label.category)
+    .bind(chrono::Utc::now().timestamp())
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+// Insert or update a position, on the pool or inside a transaction
+async fn upsert_position(conn: &mut PgConnection, position: &Position) -> Result<()> {
+    let status_str = match position.status {
+        PositionStatus::Active => "active",
+        PositionStatus::Unstaking => "unstaking",
+        PositionStatus::Withdrawn => "withdrawn",
+    };
+
+    let amount_str = position.amount.to_string();
+
+    sqlx::query(
+        "INSERT INTO positions 
+         (user_address, nonce, amount, deposit_timestamp, status, 
+          withdrawal_initiated_timestamp, unlocks_at, block_number, lock_duration, updated_at)
+         VALUES ($1, $2, $3, $4, $5::position_status, $6, $7, $8, $9, CURRENT_TIMESTAMP)
+         ON CONFLICT (user_address, nonce) 
+         DO UPDATE SET 
+            amount = EXCLUDED.amount,
+            deposit_timestamp = EXCLUDED.deposit_timestamp,
+            status = EXCLUDED.status,
+            withdrawal_initiated_timestamp = EXCLUDED.withdrawal_initiated_timestamp,
+            unlocks_at = EXCLUDED.unlocks_at,
+            block_number = EXCLUDED.block_number,
+            lock_duration = EXCLUDED.lock_duration,
+            updated_at = CURRENT_TIMESTAMP"
+    )
+    .bind(position.user.to_string())
+    .bind(u256_to_numeric(position.nonce))
+    .bind(BigDecimal::from_str(&amount_str).unwrap_or_else(|_| BigDecimal::from(0)))
+    .bind(position.deposit_timestamp as i64)
+    .bind(status_str)
+    .bind(position.withdrawal_initiated_timestamp.map(|t| t as i64))
+    .bind(position.unlocks_at.map(|t| t as i64))
+    .bind(position.block_number as i64)
+    .bind(position.lock_duration as i64)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// PostgreSQL storage backend
+#[derive(Clone)]
+pub struct PgDatabase {
+    pool: PgPool,
+}
+
+impl PgDatabase {
+    /// Create a new database connection with migrations, keeping all tables in
+    /// `schema` instead of the default search path when given
+    pub async fn new(database_url: &str, schema: Option<&str>) -> Result<Self> {
+        let mut options = PgConnectOptions::from_str(database_url)?;
+        if let Some(schema) = schema {
+            // Schema names come from validated program names, quoting is just for safety
+            let mut conn = PgConnection::connect_with(&options).await?;
+            sqlx::query(&format!("CREATE SCHEMA IF NOT EXISTS \"{}\"", schema.replace('"', "")))
+                .execute(&mut conn)
+                .await?;
+            conn.close().await?;
+            options = options.options([("search_path", schema)]);
+        }
+
+        // Create connection pool
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .connect_with(options)
+            .await?;
+
+        // Run migrations using sqlx migrate
+        sqlx::migrate!("./migrations")
+            .run(&pool)
+            .await?;
+        
+        Ok(Self { pool })
+    }
+
+    /// Pool for API reads from a database set up by `new`, or a read-only replica of
+    /// it. The server aborts any statement running longer than `statement_timeout`.
+    /// Replicas can't take DDL, so the schema and migrations come from the primary.
+    pub async fn reader(database_url: &str, schema: Option<&str>, statement_timeout: Duration) -> Result<Self> {
+        let mut options = PgConnectOptions::from_str(database_url)?
+            .options([("statement_timeout", format!("{}ms", statement_timeout.as_millis()))]);
+        if let Some(schema) = schema {
+            options = options.options([("search_path", schema)]);
+        }
+
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .connect_with(options)
+            .await?;
+
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl PointsStore for PgDatabase {
+    // Fetch positions, optionally for a single user
+    async fn fetch_positions(&self, user_address: Option<&str>) -> Result<Vec<Position>> {
+        let rows = sqlx::query(
+            "SELECT user_address, nonce, amount, deposit_timestamp, status::text as status, 
+             withdrawal_initiated_timestamp, unlocks_at, block_number, lock_duration
+             FROM positions
+             WHERE ($1::text IS NULL OR user_address = $1)"
+        )
+        .bind(user_address)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut positions = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            let user_address: String = row.get("user_address");
+            let nonce: BigDecimal = row.get("nonce");
+            let amount_str: BigDecimal = row.get("amount");
+            let deposit_timestamp: i64 = row.get("deposit_timestamp");
+            let status: String = row.get("status");
+            let withdrawal_timestamp: Option<i64> = row.get("withdrawal_initiated_timestamp");
+            let unlocks_at: Option<i64> = row.get("unlocks_at");
+            let block_number: i64 = row.get("block_number");
+
+            // Convert BigDecimal to U256
+            let amount = U256::from_str(&amount_str.to_string()).unwrap_or_default();
+            let address = Address::from_str(&user_address)?;
+            
+            positions.push(Position {
+                user: address,
+                nonce: numeric_to_u256(&nonce),
+                amount,
+                deposit_timestamp: deposit_timestamp as u64,
+                status: match status.as_str() {
+                    "active" => PositionStatus::Active,
+                    "unstaking" => PositionStatus::Unstaking,
+                    "withdrawn" => PositionStatus::Withdrawn,
+                    _ => PositionStatus::Active,
+                },
+                withdrawal_initiated_timestamp: withdrawal_timestamp.map(|t| t as u64),
+                unlocks_at: unlocks_at.map(|t| t as u64),
+                block_number: block_number as u64,
+                lock_duration: row.get::<i64, _>("lock_duration") as u64,
+            });
+        }
+
+        Ok(positions)
+    }
+
+    // Load the emission rate history
+    async fn load_rate_periods(&self) -> Result<Vec<RatePeriod>> {
+        let rows = sqlx::query(
+            "SELECT effective_from, sage_rate, formation_rate
+             FROM rate_periods
+             ORDER BY effective_from ASC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| RatePeriod {
+                effective_from: row.get::<i64, _>("effective_from") as u64,
+                sage_rate: row.get("sage_rate"),
+                formation_rate: row.get("formation_rate"),
+            })
+            .collect())
+    }
+
+    // Start a new emission rate period
+    async fn save_rate_period(&self, period: &RatePeriod) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO rate_periods (effective_from, sage_rate, formation_rate)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (effective_from)
+             DO UPDATE SET sage_rate = EXCLUDED.sage_rate, formation_rate = EXCLUDED.formation_rate"
+        )
+        .bind(period.effective_from as i64)
+        .bind(period.sage_rate)
+        .bind(period.formation_rate)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // Save or update a position
+    async fn save_position(&self, position: &Position) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+        upsert_position(&mut conn, position).await
+    }
+
+    // Save an event for audit trail
+    async fn save_event(&self, event: EventData) -> Result<()> {
+        let amount_str = event.amount.and_then(|a| BigDecimal::from_str(&a.to_string()).ok());
+
+        sqlx::query(
+            "INSERT INTO events 
+             (event_type, user_address, nonce, amount, unlocks_at, lock_duration, block_number, log_index, transaction_hash, timestamp)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"
+        )
+        .bind(event.event_type)
+        .bind(event.user.to_string())
+        .bind(event.nonce.map(u256_to_numeric))
+        .bind(amount_str)
+        .bind(event.unlocks_at.map(|t| t as i64))
+        .bind(event.lock_duration.map(|t| t as i64))
+        .bind(event.block_number as i64)
+        .bind(event.log_index.map(|i| i as i64))
+        .bind(event.tx_hash)
+        .bind(event.timestamp as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // Load the full event history in chain order
+    async fn load_events(&self) -> Result<Vec<StoredEvent>> {
+        let rows = sqlx::query(
+            "SELECT event_type, user_address, nonce, amount, unlocks_at, lock_duration, block_number,
+                    log_index, transaction_hash, timestamp
+             FROM events
+             ORDER BY block_number ASC, log_index ASC, id ASC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(stored_event).collect()
+    }
+
+    async fn load_position_events(&self, user_address: &str, nonce: U256) -> Result<Vec<StoredEvent>> {
+        let rows = sqlx::query(
+            "SELECT event_type, user_address, nonce, amount, unlocks_at, lock_duration, block_number,
+                    log_index, transaction_hash, timestamp
+             FROM events
+             WHERE user_address = $1 AND nonce = $2
+             ORDER BY block_number ASC, log_index ASC, id ASC"
+        )
+        .bind(user_address)
+        .bind(u256_to_numeric(nonce))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(stored_event).collect()
+    }
+
+    async fn load_tx_events(&self, tx_hash: &str) -> Result<Vec<StoredEvent>> {
+        let rows = sqlx::query(
+            "SELECT event_type, user_address, nonce, amount, unlocks_at, lock_duration, block_number,
+                    log_index, transaction_hash, timestamp
+             FROM events
+             WHERE transaction_hash = $1
+             ORDER BY log_index ASC, id ASC"
+        )
+        .bind(tx_hash)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(stored_event).collect()
+    }
+
+    // Get last processed block
+    async fn get_last_processed_block(&self) -> Result<Option<u64>> {
+        let row = sqlx::query(
+            "SELECT value FROM sync_metadata WHERE key = 'last_processed_block'"
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(row) = row {
+            let value: String = row.get("value");
+            Ok(value.parse::<u64>().ok())
+        } else {
+            Ok(None)
+        }
+    }
+
+    // Update last processed block
+    async fn update_last_processed_block(&self, block: u64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO sync_metadata (key, value, updated_at) 
+             VALUES ('last_processed_block', $1, CURRENT_TIMESTAMP)
+             ON CONFLICT (key) 
+             DO UPDATE SET value = EXCLUDED.value, updated_at = CURRENT_TIMESTAMP"
+        )
+        .bind(block.to_string())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_bootstrap_block(&self) -> Result<Option<u64>> {
+        let row = sqlx::query("SELECT value FROM sync_metadata WHERE key = 'bootstrap_block'")
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.and_then(|row| row.get::<String, _>("value").parse::<u64>().ok()))
+    }
+
+    async fn set_bootstrap_block(&self, block: u64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO sync_metadata (key, value, updated_at)
+             VALUES ('bootstrap_block', $1, CURRENT_TIMESTAMP)
+             ON CONFLICT (key)
+             DO UPDATE SET value = EXCLUDED.value, updated_at = CURRENT_TIMESTAMP"
+        )
+        .bind(block.to_string())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // Load all declared accrual pause windows
+    async fn load_pause_windows(&self) -> Result<Vec<PauseWindow>> {
+        let rows = sqlx::query(
+            "SELECT id, start_timestamp, end_timestamp, reason
+             FROM pause_windows
+             ORDER BY start_timestamp ASC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| PauseWindow {
+                id: row.get("id"),
+                start: row.get::<i64, _>("start_timestamp") as u64,
+                end: row.get::<i64, _>("end_timestamp") as u64,
+                reason: row.get("reason"),
+            })
+            .collect())
+    }
+
+    // Declare a new accrual pause window, returns its id
+    async fn add_pause_window(&self, start: u64, end: u64, reason: &str) -> Result<i32> {
+        let row = sqlx::query(
+            "INSERT INTO pause_windows (start_timestamp, end_timestamp, reason)
+             VALUES ($1, $2, $3)
+             RETURNING id"
+        )
+        .bind(start as i64)
+        .bind(end as i64)
+        .bind(reason)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.get("id"))
+    }
+
+    // Remove an accrual pause window, returns whether it existed
+    async fn remove_pause_window(&self, id: i32) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM pause_windows WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Load admin address migrations in the order they were applied
+    async fn load_address_migrations(&self) -> Result<Vec<AddressMigration>> {
+        let rows = sqlx::query(
+            "SELECT id, from_address, to_address, include_positions, reason, migrated_at
+             FROM address_migrations
+             ORDER BY id ASC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut migrations = Vec::with_capacity(rows.len());
+        for row in rows {
+            let from_address: String = row.get("from_address");
+            let to_address: String = row.get("to_address");
+
+            migrations.push(AddressMigration {
+                id: row.get("id"),
+                from: Address::from_str(&from_address)?,
+                to: Address::from_str(&to_address)?,
+                include_positions: row.get("include_positions"),
+                reason: row.get("reason"),
+                migrated_at: row.get::<i64, _>("migrated_at") as u64,
+            });
+        }
+
+        Ok(migrations)
+    }
+
+    // Record an address migration and its audit entry atomically, returns its id
+    async fn migrate_address(&self, migration: &AddressMigration) -> Result<i32> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query(
+            "INSERT INTO address_migrations (from_address, to_address, include_positions, reason, migrated_at)
+             VALUES ($1, $2, $3, $4, $5)
+             RETURNING id"
+        )
+        .bind(migration.from.to_string())
+        .bind(migration.to.to_string())
+        .bind(migration.include_positions)
+        .bind(&migration.reason)
+        .bind(migration.migrated_at as i64)
+        .fetch_one(&mut *tx)
+        .await?;
+        let id: i32 = row.get("id");
+
+        let details = serde_json::to_string(&AddressMigration { id, ..migration.clone() })?;
+        sqlx::query(
+            "INSERT INTO admin_audit_log (action, details, created_at)
+             VALUES ('migrate_points', $1, $2)"
+        )
+        .bind(details)
+        .bind(migration.migrated_at as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    async fn load_delegations(&self) -> Result<Vec<Delegation>> {
+        let rows = sqlx::query(
+            "SELECT delegator, delegatee, block_number, transaction_hash, timestamp
+             FROM delegations
+             ORDER BY block_number ASC, id ASC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut delegations = Vec::with_capacity(rows.len());
+        for row in rows {
+            let delegator: String = row.get("delegator");
+            let delegatee: String = row.get("delegatee");
+
+            delegations.push(Delegation {
+                delegator: Address::from_str(&delegator)?,
+                delegatee: Address::from_str(&delegatee)?,
+                block_number: row.get::<i64, _>("block_number") as u64,
+                tx_hash: row.get("transaction_hash"),
+                timestamp: row.get::<i64, _>("timestamp") as u64,
+            });
+        }
+
+        Ok(delegations)
+    }
+
+    async fn save_delegation(&self, delegation: &Delegation) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO delegations (delegator, delegatee, block_number, transaction_hash, timestamp)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (transaction_hash, delegator) DO NOTHING"
+        )
+        .bind(delegation.delegator.to_string())
+        .bind(delegation.delegatee.to_string())
+        .bind(delegation.block_number as i64)
+        .bind(&delegation.tx_hash)
+        .bind(delegation.timestamp as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn replace_positions(&self, positions: &[Position]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM positions").execute(&mut *tx).await?;
+        for position in positions {
+            upsert_position(&mut tx, position).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn rollback_to_block(&self, block: u64, positions: &[Position]) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let removed = sqlx::query("DELETE FROM events WHERE block_number > $1")
+            .bind(block as i64)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        sqlx::query("DELETE FROM delegations WHERE block_number > $1")
+            .bind(block as i64)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM stakers WHERE first_seen_block > $1")
+            .bind(block as i64)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM tvl_history WHERE block_number > $1")
+            .bind(block as i64)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM rank_history WHERE block_number > $1")
+            .bind(block as i64)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM positions").execute(&mut *tx).await?;
+        for position in positions {
+            upsert_position(&mut tx, position).await?;
+        }
+
+        sqlx::query(
+            "INSERT INTO sync_metadata (key, value, updated_at)
+             VALUES ('last_processed_block', $1, CURRENT_TIMESTAMP)
+             ON CONFLICT (key)
+             DO UPDATE SET value = EXCLUDED.value, updated_at = CURRENT_TIMESTAMP"
+        )
+        .bind(block.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+        let details = serde_json::json!({ "to_block": block, "events_removed": removed });
+        sqlx::query(
+            "INSERT INTO admin_audit_log (action, details, created_at)
+             VALUES ('rollback', $1, $2)"
+        )
+        .bind(details.to_string())
+        .bind(chrono::Utc::now().timestamp())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(removed)
+    }
+
+    // Label of an address, if one is known
+    async fn get_address_label(&self, address: &Address) -> Result<Option<AddressLabel>> {
+        let row = sqlx::query("SELECT label, category FROM address_labels WHERE address = $1")
+            .bind(address.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|row| AddressLabel {
+            address: *address,
+            label: row.get("label"),
+            category: row.get("category"),
+        }))
+    }
+
+    // Insert or replace the label of an address
+    async fn save_address_label(&self, label: &AddressLabel) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+        upsert_label(&mut conn, label).await
+    }
+
+    // Insert or replace many labels in one transaction, logged as a single import
+    async fn save_address_labels(&self, labels: &[AddressLabel], source: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for label in labels {
+            upsert_label(&mut tx, label).await?;
+        }
+
+        let details = serde_json::json!({ "source": source, "labels": labels.len() });
+        sqlx::query(
+            "INSERT INTO admin_audit_log (action, details, created_at)
+             VALUES ('label_import', $1, $2)"
+        )
+        .bind(details.to_string())
+        .bind(chrono::Utc::now().timestamp())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn load_address_labels(&self) -> Result<Vec<AddressLabel>> {
+        let rows = sqlx::query("SELECT address, label, category FROM address_labels ORDER BY address")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| Ok(AddressLabel {
+                address: Address::from_str(row.get::<&str, _>("address"))?,
+                label: row.get("label"),
+                category: row.get("category"),
+            }))
+            .collect()
+    }
+
+    // Notification preferences of a user, if any were saved
+    async fn get_notification_preferences(&self, address: &Address) -> Result<Option<NotificationPreferences>> {
+        let row = sqlx::query(
+            "SELECT address, webhook_url, discord_user_id, email, events
+             FROM notification_preferences WHERE address = $1"
+        )
+        .bind(address.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| notification_preferences_from_row(&row)).transpose()
+    }
+
+    // Insert or replace the notification preferences of a user
+    async fn save_notification_preferences(&self, preferences: &NotificationPreferences) -> Result<()> {
+        let events: Vec<&str> = preferences.events.iter().map(|e| e.as_str()).collect();
+        sqlx::query(
+            "INSERT INTO notification_preferences (address, webhook_url, discord_user_id, email, events, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT (address) DO UPDATE SET
+                webhook_url = EXCLUDED.webhook_url,
+                discord_user_id = EXCLUDED.discord_user_id,
+                email = EXCLUDED.email,
+                events = EXCLUDED.events,
+                updated_at = EXCLUDED.updated_at"
+        )
+        .bind(preferences.address.to_string())
+        .bind(&preferences.webhook_url)
+        .bind(&preferences.discord_user_id)
+        .bind(&preferences.email)
+        .bind(events.join(","))
+        .bind(chrono::Utc::now().timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // Drop the notification preferences of a user
+    async fn delete_notification_preferences(&self, address: &Address) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM notification_preferences WHERE address = $1")
+            .bind(address.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Everyone subscribed to something, for the notification dispatcher
+    async fn load_notification_preferences(&self) -> Result<Vec<NotificationPreferences>> {
+        let rows = sqlx::query(
+            "SELECT address, webhook_url, discord_user_id, email, events
+             FROM notification_preferences WHERE events <> ''"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(notification_preferences_from_row).collect()
+    }
+
+    // Profile of a user, if they set one
+    async fn get_profile(&self, address: &Address) -> Result<Option<Profile>> {
+        let row = sqlx::query("SELECT address, display_name, avatar_url FROM profiles WHERE address = $1")
+            .bind(address.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|row| profile_from_row(&row)).transpose()
+    }
+
+    // Insert or replace the profile of a user
+    async fn save_profile(&self, profile: &Profile) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO profiles (address, display_name, avatar_url, updated_at)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (address) DO UPDATE SET
+                display_name = EXCLUDED.display_name,
+                avatar_url = EXCLUDED.avatar_url,
+                updated_at = EXCLUDED.updated_at"
+        )
+        .bind(profile.address.to_string())
+        .bind(&profile.display_name)
+        .bind(&profile.avatar_url)
+        .bind(chrono::Utc::now().timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // Drop the profile of a user
+    async fn delete_profile(&self, address: &Address) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM profiles WHERE address = $1")
+            .bind(address.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Profiles of a page of leaderboard addresses, in one query
+    async fn get_profiles(&self, addresses: &[Address]) -> Result<Vec<Profile>> {
+        let rows = sqlx::query("SELECT address, display_name, avatar_url FROM profiles WHERE address = ANY($1)")
+            .bind(addresses.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(profile_from_row).collect()
+    }
+
+    async fn load_profiles(&self) -> Result<Vec<Profile>> {
+        let rows = sqlx::query("SELECT address, display_name, avatar_url FROM profiles ORDER BY address")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(profile_from_row).collect()
+    }
+
+    // Create the job's row on first use, then take the lease in one conditional update
+    async fn try_lock_job(&self, name: &str, owner: &str, now: u64, due: u64, lease_until: u64) -> Result<bool> {
+        sqlx::query("INSERT INTO scheduled_jobs (name) VALUES ($1) ON CONFLICT (name) DO NOTHING")
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+
+        let result = sqlx::query(
+            "UPDATE scheduled_jobs SET locked_by = $2, locked_until = $3, last_started_at = $4
+             WHERE name = $1
+               AND (locked_until IS NULL OR locked_until < $4)
+               AND (last_started_at IS NULL OR last_started_at < $5)"
+        )
+        .bind(name)
+        .bind(owner)
+        .bind(lease_until as i64)
+        .bind(now as i64)
+        .bind(due as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Record a finished run and release the lease
+    async fn finish_job(&self, name: &str, owner: &str, run: &JobRun) -> Result<()> {
+        sqlx::query(
+            "UPDATE scheduled_jobs SET locked_by = NULL, locked_until = NULL, last_finished_at = $3,
+                last_status = $4, last_message = $5, last_duration_ms = $6, run_count = run_count + 1
+             WHERE name = $1 AND locked_by = $2"
+        )
+        .bind(name)
+        .bind(owner)
+        .bind(run.finished_at as i64)
+        .bind(if run.ok { "ok" } else { "failed" })
+        .bind(&run.message)
+        .bind(run.duration_ms as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn load_job_states(&self) -> Result<Vec<JobState>> {
+        let rows = sqlx::query("SELECT * FROM scheduled_jobs ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(job_state_from_row).collect())
+    }
+
+    // Record the first time we see a staker (no-op if already known)
+    async fn record_staker(&self, user: &Address, timestamp: u64, block_number: u64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO stakers (user_address, first_seen_timestamp, first_seen_block)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (user_address) DO NOTHING"
+        )
+        .bind(user.to_string())
+        .bind(timestamp as i64)
+        .bind(block_number as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // Record a TVL snapshot
+    async fn save_tvl_snapshot(&self, timestamp: u64, block_number: u64, active: U256, unstaking: U256) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO tvl_history (timestamp, block_number, active_amount, unstaking_amount)
+             VALUES ($1, $2, $3, $4)"
+        )
+        .bind(timestamp as i64)
+        .bind(block_number as i64)
+        .bind(BigDecimal::from_str(&active.to_string()).unwrap_or_else(|_| BigDecimal::from(0)))
+        .bind(BigDecimal::from_str(&unstaking.to_string()).unwrap_or_else(|_| BigDecimal::from(0)))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // Get timestamp of the latest TVL snapshot
+    async fn get_last_tvl_timestamp(&self) -> Result<Option<u64>> {
+        let row = sqlx::query("SELECT MAX(timestamp) AS timestamp FROM tvl_history")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.get::<Option<i64>, _>("timestamp").map(|t| t as u64))
+    }
+
+    async fn load_tvl_snapshots(&self) -> Result<Vec<TvlSnapshot>> {
+        let rows = sqlx::query("SELECT timestamp, block_number, active_amount, unstaking_amount FROM tvl_history ORDER BY timestamp, id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| Ok(TvlSnapshot {
+                timestamp: row.get::<i64, _>("timestamp") as u64,
+                block_number: row.get::<i64, _>("block_number") as u64,
+                active_amount: numeric_to_u256(&row.get::<BigDecimal, _>("active_amount")),
+                unstaking_amount: numeric_to_u256(&row.get::<BigDecimal, _>("unstaking_amount")),
+            }))
+            .collect()
+    }
+
+    async fn save_rank_snapshot(&self, timestamp: u64, block_number: u64, ranks: &[(Address, i32, f64)]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for (owner, rank, total_points) in ranks {
+            sqlx::query(
+                "INSERT INTO rank_history (timestamp, block_number, address, rank, total_points)
+                 VALUES ($1, $2, $3, $4, $5)"
+            )
+            .bind(timestamp as i64)
+            .bind(block_number as i64)
+            .bind(owner.to_string())
+            .bind(*rank)
+            .bind(*total_points)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_last_rank_timestamp(&self) -> Result<Option<u64>> {
+        let row = sqlx::query("SELECT MAX(timestamp) AS timestamp FROM rank_history")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.get::<Option<i64>, _>("timestamp").map(|t| t as u64))
+    }
+
+    async fn load_rank_history(&self, user_address: &str, since: u64) -> Result<Vec<RankPoint>> {
+        let rows = sqlx::query(
+            "SELECT timestamp, block_number, rank, total_points
+             FROM rank_history
+             WHERE address = $1 AND timestamp >= $2
+             ORDER BY timestamp ASC"
+        )
+        .bind(user_address)
+        .bind(since as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| RankPoint {
+                timestamp: DateTime::from_timestamp(row.get::<i64, _>("timestamp"), 0).unwrap_or_default(),
+                block_number: row.get("block_number"),
+                rank: row.get("rank"),
+                total_points: row.get("total_points"),
+            })
+            .collect())
+    }
+
+    async fn list_rank_snapshots(&self) -> Result<Vec<RankSnapshot>> {
+        let rows = sqlx::query(
+            "SELECT timestamp, MAX(block_number) AS block_number, COUNT(*) AS owners
+             FROM rank_history
+             GROUP BY timestamp
+             ORDER BY timestamp DESC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| RankSnapshot {
+                timestamp: row.get::<i64, _>("timestamp") as u64,
+                block_number: row.get::<i64, _>("block_number") as u64,
+                owners: row.get::<i64, _>("owners") as u64,
+            })
+            .collect())
+    }
+
+    async fn load_rank_snapshot(&self, timestamp: u64) -> Result<Option<(RankSnapshot, Vec<(Address, i32, f64)>)>> {
+        let rows = sqlx::query("SELECT address, block_number, rank, total_points FROM rank_history WHERE timestamp = $1")
+            .bind(timestamp as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let Some(first) = rows.first() else { return Ok(None) };
+        let snapshot = RankSnapshot {
+            timestamp,
+            block_number: first.get::<i64, _>("block_number") as u64,
+            owners: rows.len() as u64,
+        };
+        let ranks = rows
+            .iter()
+            .map(|row| Ok((Address::from_str(row.get::<&str, _>("address"))?, row.get("rank"), row.get("total_points"))))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some((snapshot, ranks)))
+    }
+
+    // Archive a finished competition period with its winners
+    async fn save_competition_standings(&self, standings: &CompetitionStandings) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = sqlx::query(
+            "INSERT INTO competition_periods (board, period, period_start, period_end, archived_at)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (board, period) DO NOTHING"
+        )
+        .bind(standings.board.as_str())
+        .bind(standings.period as i64)
+        .bind(standings.start as i64)
+        .bind(standings.end as i64)
+        .bind(standings.archived_at.unwrap_or_else(|| chrono::Utc::now().timestamp() as u64) as i64)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if inserted == 0 {
+            return Ok(());
+        }
+
+        for entry in &standings.entries {
+            sqlx::query(
+                "INSERT INTO competition_winners (board, period, rank, address, sage_points, formation_points, total_points)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)"
+            )
+            .bind(standings.board.as_str())
+            .bind(standings.period as i64)
+            .bind(entry.rank)
+            .bind(&entry.address)
+            .bind(entry.sage_points)
+            .bind(entry.formation_points)
+            .bind(entry.total_points)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn load_competition_standings(&self, board: CompetitionBoard, period: u64) -> Result<Option<CompetitionStandings>> {
+        let Some(row) = sqlx::query(
+            "SELECT period_start, period_end, archived_at FROM competition_periods
+             WHERE board = $1 AND period = $2"
+        )
+        .bind(board.as_str())
+        .bind(period as i64)
+        .fetch_optional(&self.pool)
+        .await? else {
+            return Ok(None);
+        };
+
+        let entries = sqlx::query(
+            "SELECT rank, address, sage_points, formation_points, total_points
+             FROM competition_winners
+             WHERE board = $1 AND period = $2
+             ORDER BY rank ASC"
+        )
+        .bind(board.as_str())
+        .bind(period as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| CompetitionEntry {
+            rank: row.get("rank"),
+            address: row.get("address"),
+            sage_points: row.get("sage_points"),
+            formation_points: row.get("formation_points"),
+            total_points: row.get("total_points"),
+        })
+        .collect();
+
+        Ok(Some(CompetitionStandings {
+            board,
+            period,
+            start: row.get::<i64, _>("period_start") as u64,
+            end: row.get::<i64, _>("period_end") as u64,
+            is_final: true,
+            archived_at: Some(row.get::<i64, _>("archived_at") as u64),
+            entries,
+        }))
+    }
+
+    async fn get_last_archived_period(&self, board: CompetitionBoard) -> Result<Option<u64>> {
+        let row = sqlx::query("SELECT MAX(period) AS period FROM competition_periods WHERE board = $1")
+            .bind(board.as_str())
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.get::<Option<i64>, _>("period").map(|p| p as u64))
+    }
+
+    async fn list_competition_periods(&self) -> Result<Vec<(CompetitionBoard, u64)>> {
+        let rows = sqlx::query("SELECT board, period FROM competition_periods ORDER BY period, board")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                let board: String = row.get("board");
+                let board = CompetitionBoard::parse(&board).ok_or_else(|| eyre::eyre!("Unknown competition board {}", board))?;
+                Ok((board, row.get::<i64, _>("period") as u64))
+            })
+            .collect()
+    }
+
+    // Record a points root published on-chain
+    async fn save_points_root(&self, root: &str, block_number: u64, leaf_count: usize, tx_hash: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO points_roots (root, block_number, leaf_count, transaction_hash)
+             VALUES ($1, $2, $3, $4)"
+        )
+        .bind(root)
+        .bind(block_number as i64)
+        .bind(leaf_count as i32)
+        .bind(tx_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn load_points_roots(&self) -> Result<Vec<PointsRoot>> {
+        let rows = sqlx::query("SELECT root, block_number, leaf_count, transaction_hash FROM points_roots ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter()
+            .map(|row| PointsRoot {
+                root: row.get("root"),
+                block_number: row.get::<i64, _>("block_number") as u64,
+                leaf_count: row.get::<i32, _>("leaf_count") as usize,
+                tx_hash: row.get("transaction_hash"),
+            })
+            .collect())
+    }
+
+    // API Methods
+    
+    /// Get historical event data for a specific user
+    async fn get_user_events(&self, user_address: &str) -> Result<Vec<UserEvent>> {
+        let rows = sqlx::query(
+            "SELECT e.event_type, e.amount, e.nonce, e.timestamp, e.block_number,
+                    COALESCE(p.status::text, '') as status,
+                    COALESCE(e.unlocks_at, p.unlocks_at) as unlocks_at
+             FROM events e
+             LEFT JOIN positions p ON p.user_address = e.user_address AND p.nonce = e.nonce
+             WHERE e.user_address = $1
+             ORDER BY e.block_number DESC, e.log_index DESC, e.timestamp DESC"
+        )
+        .bind(user_address)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            let amount: Option<BigDecimal> = row.get("amount");
+            let amount_str = if let Some(amt) = &amount {
+                format!("{:.6}", amt.to_string().parse::<f64>().unwrap_or(0.0) / 1e18)
+            } else {
+                "0.000000".to_string()
+            };
+            
+            events.push(UserEvent {
+                event_type: row.get("event_type"),
+                amount: amount_str,
+                amount_wei: amount.as_ref().map(numeric_to_u256).unwrap_or_default().to_string(),
+                nonce: row.get::<Option<BigDecimal>, _>("nonce").as_ref().map(numeric_to_u256).unwrap_or_default(),
+                timestamp: DateTime::from_timestamp(row.get("timestamp"), 0).unwrap_or_default(),
+                block_number: row.get("block_number"),
+                status: row.get("status"),
+                unlocks_at: row.get::<Option<i64>, _>("unlocks_at")
+                    .and_then(|t| DateTime::from_timestamp(t, 0)),
+                attributed_from: None,
+            });
+        }
+
+        Ok(events)
+    }
+
+    /// Get all staking positions for a specific user
+    async fn get_user_positions(&self, user_address: &str) -> Result<Vec<UserPosition>> {
+        let rows = sqlx::query(
+            "SELECT nonce, amount, status::text as status, deposit_timestamp,
+                    withdrawal_initiated_timestamp, unlocks_at, block_number, lock_duration
+             FROM positions
+             WHERE user_address = $1
+             ORDER BY nonce ASC"
+        )
+        .bind(user_address)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut positions = Vec::new();
+        for row in rows {
+            let amount: BigDecimal = row.get("amount");
+
+            positions.push(UserPosition {
+                nonce: numeric_to_u256(&row.get::<BigDecimal, _>("nonce")),
+                amount: format!("{:.6}", amount.to_string().parse::<f64>().unwrap_or(0.0) / 1e18),
+                amount_wei: numeric_to_u256(&amount).to_string(),
+                status: row.get("status"),
+                deposit_timestamp: DateTime::from_timestamp(row.get("deposit_timestamp"), 0).unwrap_or_default(),
+                withdrawal_initiated_timestamp: row.get::<Option<i64>, _>("withdrawal_initiated_timestamp")
+                    .and_then(|t| DateTime::from_timestamp(t, 0)),
+                unlocks_at: row.get::<Option<i64>, _>("unlocks_at")
+                    .and_then(|t| DateTime::from_timestamp(t, 0)),
+                block_number: row.get("block_number"),
+                lock_duration: row.get::<i64, _>("lock_duration") as u64,
+                lock_multiplier: 1.0, // Filled in from the rules by `Database`
+                attributed_from: None,
+            });
+        }
+
+        Ok(positions)
+    }
+
+    /// Get unstaking positions whose cooldown has elapsed, optionally for one user
+    async fn get_withdrawable_positions(&self, user_address: Option<&str>, limit: i64) -> Result<Vec<WithdrawablePosition>> {
+        let current_time = chrono::Utc::now().timestamp();
+
+        let rows = sqlx::query(
+            "SELECT user_address, nonce, amount, unlocks_at
+             FROM positions
+             WHERE status = 'unstaking'
+               AND unlocks_at IS NOT NULL
+               AND unlocks_at <= $1
+               AND ($2::text IS NULL OR user_address = $2)
+             ORDER BY unlocks_at ASC
+             LIMIT $3"
+        )
+        .bind(current_time)
+        .bind(user_address)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut positions = Vec::new();
+        for row in rows {
+            let amount: BigDecimal = row.get("amount");
+            let unlocks_at: i64 = row.get("unlocks_at");
+
+            positions.push(WithdrawablePosition {
+                address: row.get("user_address"),
+                nonce: numeric_to_u256(&row.get::<BigDecimal, _>("nonce")),
+                amount: format!("{:.6}", amount.to_string().parse::<f64>().unwrap_or(0.0) / 1e18),
+                amount_wei: numeric_to_u256(&amount).to_string(),
+                unlocks_at: DateTime::from_timestamp(unlocks_at, 0).unwrap_or_default(),
+                claimable_for_seconds: current_time - unlocks_at,
+            });
+        }
+
+        Ok(positions)
+    }
+
+    /// Get TVL history bucketed by hour or day (last snapshot in each bucket)
+    async fn get_tvl_history(&self, interval: &str, limit: i64) -> Result<Vec<TvlPoint>> {
+        let rows = sqlx::query(
+            "SELECT * FROM (
+                SELECT DISTINCT ON (date_trunc($1, to_timestamp(timestamp)))
+                    timestamp, block_number,
+                    CAST(active_amount AS FLOAT) / 1e18 AS active_amount,
+                    CAST(unstaking_amount AS FLOAT) / 1e18 AS unstaking_amount
+                FROM tvl_history
+                ORDER BY date_trunc($1, to_timestamp(timestamp)) DESC, timestamp DESC
+                LIMIT $2
+            ) buckets
+            ORDER BY timestamp ASC"
+        )
+        .bind(interval)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut points = Vec::new();
+        for row in rows {
+            let active_amount: f64 = row.get("active_amount");
+            let unstaking_amount: f64 = row.get("unstaking_amount");
+
+            points.push(TvlPoint {
+                timestamp: DateTime::from_timestamp(row.get("timestamp"), 0).unwrap_or_default(),
+                block_number: row.get("block_number"),
+                active_amount,
+                unstaking_amount,
+                total_staked: active_amount + unstaking_amount,
+            });
+        }
+
+        Ok(points)
+    }
+
+    /// Get unique staker counts with daily new/churned stakers for the last `days` days
+    async fn get_staker_metrics(&self, days: i64) -> Result<StakerMetrics> {
+        // New stakers per day from first-seen timestamps
+        let new_rows = sqlx::query(
+            "SELECT (to_timestamp(first_seen_timestamp) AT TIME ZONE 'UTC')::date AS day, COUNT(*) AS count
+             FROM stakers
+             GROUP BY day"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Churned stakers (every position withdrawn) per day of their final withdrawal
+        let churn_rows = sqlx::query(
+            "WITH churned AS (
+                SELECT user_address
+                FROM positions
+                GROUP BY user_address
+                HAVING bool_and(status = 'withdrawn')
+            )
+            SELECT (to_timestamp(MAX(e.timestamp)) AT TIME ZONE 'UTC')::date AS day, e.user_address
+            FROM events e
+            JOIN churned c ON c.user_address = e.user_address
+            WHERE e.event_type = 'Withdraw'
+            GROUP BY e.user_address"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let new_stakers = new_rows
+            .into_iter()
+            .map(|row| (row.get("day"), row.get("count")))
+            .collect();
+        let churned = churn_rows
+            .into_iter()
+            .map(|row| row.get("day"))
+            .collect();
+
+        Ok(build_staker_metrics(new_stakers, churned, days))
+    }
+}
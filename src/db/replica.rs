@@ -0,0 +1,262 @@
+use alloy::primitives::{Address, U256};
+use async_trait::async_trait;
+use eyre::Result;
+use std::sync::Arc;
+
+use super::{
+    AddressLabel, CompetitionStandings, EventData, PointsRoot, PointsStore, RankPoint, RankSnapshot, StakerMetrics, StoredEvent, TvlPoint,
+    TvlSnapshot, UserEvent, UserPosition, WithdrawablePosition,
+};
+use crate::notifications::NotificationPreferences;
+use crate::profiles::Profile;
+use crate::scheduler::{JobRun, JobState};
+use crate::points::{AddressMigration, CompetitionBoard, Delegation, PauseWindow, RatePeriod};
+use crate::Position;
+
+/// Store for API traffic: reads go to a separate reader (a read replica, or its own
+/// pool on the primary), writes to the primary. Replica reads may trail the primary by
+/// the replication lag. The indexer never uses this, it has to see its own writes.
+pub struct ReplicatedStore {
+    primary: Arc<dyn PointsStore>,
+    reader: Arc<dyn PointsStore>,
+}
+
+impl ReplicatedStore {
+    pub fn new(primary: Arc<dyn PointsStore>, reader: Arc<dyn PointsStore>) -> Self {
+        Self { primary, reader }
+    }
+}
+
+#[async_trait]
+impl PointsStore for ReplicatedStore {
+    async fn fetch_positions(&self, user_address: Option<&str>) -> Result<Vec<Position>> {
+        self.reader.fetch_positions(user_address).await
+    }
+
+    async fn save_position(&self, position: &Position) -> Result<()> {
+        self.primary.save_position(position).await
+    }
+
+    async fn save_event(&self, event: EventData) -> Result<()> {
+        self.primary.save_event(event).await
+    }
+
+    async fn load_events(&self) -> Result<Vec<StoredEvent>> {
+        self.reader.load_events().await
+    }
+
+    async fn load_tx_events(&self, tx_hash: &str) -> Result<Vec<StoredEvent>> {
+        self.reader.load_tx_events(tx_hash).await
+    }
+
+    async fn load_position_events(&self, user_address: &str, nonce: U256) -> Result<Vec<StoredEvent>> {
+        self.reader.load_position_events(user_address, nonce).await
+    }
+
+    async fn get_last_processed_block(&self) -> Result<Option<u64>> {
+        self.reader.get_last_processed_block().await
+    }
+
+    async fn update_last_processed_block(&self, block: u64) -> Result<()> {
+        self.primary.update_last_processed_block(block).await
+    }
+
+    async fn get_bootstrap_block(&self) -> Result<Option<u64>> {
+        self.reader.get_bootstrap_block().await
+    }
+
+    async fn set_bootstrap_block(&self, block: u64) -> Result<()> {
+        self.primary.set_bootstrap_block(block).await
+    }
+
+    async fn load_rate_periods(&self) -> Result<Vec<RatePeriod>> {
+        self.reader.load_rate_periods().await
+    }
+
+    async fn save_rate_period(&self, period: &RatePeriod) -> Result<()> {
+        self.primary.save_rate_period(period).await
+    }
+
+    async fn load_pause_windows(&self) -> Result<Vec<PauseWindow>> {
+        self.reader.load_pause_windows().await
+    }
+
+    async fn add_pause_window(&self, start: u64, end: u64, reason: &str) -> Result<i32> {
+        self.primary.add_pause_window(start, end, reason).await
+    }
+
+    async fn remove_pause_window(&self, id: i32) -> Result<bool> {
+        self.primary.remove_pause_window(id).await
+    }
+
+    async fn load_address_migrations(&self) -> Result<Vec<AddressMigration>> {
+        self.reader.load_address_migrations().await
+    }
+
+    async fn migrate_address(&self, migration: &AddressMigration) -> Result<i32> {
+        self.primary.migrate_address(migration).await
+    }
+
+    async fn load_delegations(&self) -> Result<Vec<Delegation>> {
+        self.reader.load_delegations().await
+    }
+
+    async fn save_delegation(&self, delegation: &Delegation) -> Result<()> {
+        self.primary.save_delegation(delegation).await
+    }
+
+    async fn replace_positions(&self, positions: &[Position]) -> Result<()> {
+        self.primary.replace_positions(positions).await
+    }
+
+    async fn rollback_to_block(&self, block: u64, positions: &[Position]) -> Result<u64> {
+        self.primary.rollback_to_block(block, positions).await
+    }
+
+    async fn get_address_label(&self, address: &Address) -> Result<Option<AddressLabel>> {
+        self.reader.get_address_label(address).await
+    }
+
+    async fn save_address_label(&self, label: &AddressLabel) -> Result<()> {
+        self.primary.save_address_label(label).await
+    }
+
+    async fn save_address_labels(&self, labels: &[AddressLabel], source: &str) -> Result<()> {
+        self.primary.save_address_labels(labels, source).await
+    }
+
+    async fn load_address_labels(&self) -> Result<Vec<AddressLabel>> {
+        self.reader.load_address_labels().await
+    }
+
+    // Users read back what they just saved, so preferences stay on the primary
+    async fn get_notification_preferences(&self, address: &Address) -> Result<Option<NotificationPreferences>> {
+        self.primary.get_notification_preferences(address).await
+    }
+
+    async fn save_notification_preferences(&self, preferences: &NotificationPreferences) -> Result<()> {
+        self.primary.save_notification_preferences(preferences).await
+    }
+
+    async fn delete_notification_preferences(&self, address: &Address) -> Result<bool> {
+        self.primary.delete_notification_preferences(address).await
+    }
+
+    async fn load_notification_preferences(&self) -> Result<Vec<NotificationPreferences>> {
+        self.primary.load_notification_preferences().await
+    }
+
+    // Like preferences, a saved profile should show up right away
+    async fn get_profile(&self, address: &Address) -> Result<Option<Profile>> {
+        self.primary.get_profile(address).await
+    }
+
+    async fn save_profile(&self, profile: &Profile) -> Result<()> {
+        self.primary.save_profile(profile).await
+    }
+
+    async fn delete_profile(&self, address: &Address) -> Result<bool> {
+        self.primary.delete_profile(address).await
+    }
+
+    async fn get_profiles(&self, addresses: &[Address]) -> Result<Vec<Profile>> {
+        self.primary.get_profiles(addresses).await
+    }
+
+    async fn load_profiles(&self) -> Result<Vec<Profile>> {
+        self.primary.load_profiles().await
+    }
+
+    // Leases have to be taken and checked on the primary
+    async fn try_lock_job(&self, name: &str, owner: &str, now: u64, due: u64, lease_until: u64) -> Result<bool> {
+        self.primary.try_lock_job(name, owner, now, due, lease_until).await
+    }
+
+    async fn finish_job(&self, name: &str, owner: &str, run: &JobRun) -> Result<()> {
+        self.primary.finish_job(name, owner, run).await
+    }
+
+    async fn load_job_states(&self) -> Result<Vec<JobState>> {
+        self.primary.load_job_states().await
+    }
+
+    async fn record_staker(&self, user: &Address, timestamp: u64, block_number: u64) -> Result<()> {
+        self.primary.record_staker(user, timestamp, block_number).await
+    }
+
+    async fn save_tvl_snapshot(&self, timestamp: u64, block_number: u64, active: U256, unstaking: U256) -> Result<()> {
+        self.primary.save_tvl_snapshot(timestamp, block_number, active, unstaking).await
+    }
+
+    async fn get_last_tvl_timestamp(&self) -> Result<Option<u64>> {
+        self.reader.get_last_tvl_timestamp().await
+    }
+
+    async fn load_tvl_snapshots(&self) -> Result<Vec<TvlSnapshot>> {
+        self.reader.load_tvl_snapshots().await
+    }
+
+    async fn save_points_root(&self, root: &str, block_number: u64, leaf_count: usize, tx_hash: &str) -> Result<()> {
+        self.primary.save_points_root(root, block_number, leaf_count, tx_hash).await
+    }
+
+    async fn load_points_roots(&self) -> Result<Vec<PointsRoot>> {
+        self.reader.load_points_roots().await
+    }
+
+    async fn save_rank_snapshot(&self, timestamp: u64, block_number: u64, ranks: &[(Address, i32, f64)]) -> Result<()> {
+        self.primary.save_rank_snapshot(timestamp, block_number, ranks).await
+    }
+
+    async fn get_last_rank_timestamp(&self) -> Result<Option<u64>> {
+        self.reader.get_last_rank_timestamp().await
+    }
+
+    async fn load_rank_history(&self, user_address: &str, since: u64) -> Result<Vec<RankPoint>> {
+        self.reader.load_rank_history(user_address, since).await
+    }
+
+    async fn list_rank_snapshots(&self) -> Result<Vec<RankSnapshot>> {
+        self.reader.list_rank_snapshots().await
+    }
+
+    async fn load_rank_snapshot(&self, timestamp: u64) -> Result<Option<(RankSnapshot, Vec<(Address, i32, f64)>)>> {
+        self.reader.load_rank_snapshot(timestamp).await
+    }
+
+    async fn save_competition_standings(&self, standings: &CompetitionStandings) -> Result<()> {
+        self.primary.save_competition_standings(standings).await
+    }
+
+    async fn load_competition_standings(&self, board: CompetitionBoard, period: u64) -> Result<Option<CompetitionStandings>> {
+        self.reader.load_competition_standings(board, period).await
+    }
+
+    async fn get_last_archived_period(&self, board: CompetitionBoard) -> Result<Option<u64>> {
+        self.reader.get_last_archived_period(board).await
+    }
+
+    async fn list_competition_periods(&self) -> Result<Vec<(CompetitionBoard, u64)>> {
+        self.reader.list_competition_periods().await
+    }
+
+    async fn get_user_events(&self, user_address: &str) -> Result<Vec<UserEvent>> {
+        self.reader.get_user_events(user_address).await
+    }
+
+    async fn get_user_positions(&self, user_address: &str) -> Result<Vec<UserPosition>> {
+        self.reader.get_user_positions(user_address).await
+    }
+
+    async fn get_withdrawable_positions(&self, user_address: Option<&str>, limit: i64) -> Result<Vec<WithdrawablePosition>> {
+        self.reader.get_withdrawable_positions(user_address, limit).await
+    }
+
+    async fn get_tvl_history(&self, interval: &str, limit: i64) -> Result<Vec<TvlPoint>> {
+        self.reader.get_tvl_history(interval, limit).await
+    }
+
+    async fn get_staker_metrics(&self, days: i64) -> Result<StakerMetrics> {
+        self.reader.get_staker_metrics(days).await
+    }
+}
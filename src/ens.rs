@@ -0,0 +1,84 @@
+use alloy::primitives::{address, keccak256, Address, B256};
+use alloy::providers::{ProviderBuilder, RootProvider};
+use alloy::sol;
+use alloy::transports::http::{Client, Http};
+use eyre::Result;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::cache::SwrCache;
+
+// ENS registry, deployed at the same address on mainnet and testnets
+const ENS_REGISTRY: Address = address!("00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+
+sol!(
+    #[sol(rpc)]
+    contract EnsRegistry {
+        function resolver(bytes32 node) external view returns (address);
+    }
+);
+
+sol!(
+    #[sol(rpc)]
+    contract EnsAddrResolver {
+        function addr(bytes32 node) external view returns (address);
+    }
+);
+
+/// Whether `name` looks like a normalized ASCII ENS name, e.g. `vitalik.eth`
+pub fn is_ens_name(name: &str) -> bool {
+    name.len() <= 255
+        && name.contains('.')
+        && name.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+}
+
+// EIP-137 namehash
+fn namehash(name: &str) -> B256 {
+    name.rsplit('.').fold(B256::ZERO, |node, label| {
+        keccak256([node.as_slice(), keccak256(label.as_bytes()).as_slice()].concat())
+    })
+}
+
+/// Resolves ENS names through a mainnet provider. Answers, including names without
+/// an address, are cached and refreshed in the background once stale.
+#[derive(Clone)]
+pub struct EnsResolver {
+    provider: RootProvider<Http<Client>>,
+    cache: Arc<SwrCache<Option<Address>>>,
+}
+
+impl EnsResolver {
+    /// Resolver for `ENS_RPC_URL`, None when unset
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(url) = std::env::var("ENS_RPC_URL").ok() else {
+            return Ok(None);
+        };
+        let ttl = std::env::var("ENS_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(3600);
+
+        Ok(Some(Self {
+            provider: ProviderBuilder::new().on_http(url.parse()?),
+            cache: SwrCache::new(Duration::from_secs(ttl)),
+        }))
+    }
+
+    /// Address `name` points to, None if it has no resolver or address record
+    pub async fn resolve(&self, name: &str) -> Result<Option<Address>> {
+        let provider = self.provider.clone();
+        let node = namehash(name);
+        let cached = self.cache.get(name.to_string(), move || async move {
+            let resolver = EnsRegistry::new(ENS_REGISTRY, &provider).resolver(node).call().await?._0;
+            if resolver.is_zero() {
+                return Ok(None);
+            }
+            let address = EnsAddrResolver::new(resolver, &provider).addr(node).call().await?._0;
+            Ok((!address.is_zero()).then_some(address))
+        }).await?;
+
+        Ok(*cached.value)
+    }
+}
@@ -0,0 +1,193 @@
+use arrow::array::{ArrayRef, Float64Array, StringArray, UInt64Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use chrono::{DateTime, NaiveDate};
+use eyre::{eyre, Result};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::db::Database;
+use crate::points::PointsRules;
+use crate::{format_token_amount_as_float, PositionStatus};
+
+const DAY: u64 = 86400;
+
+fn utc_date(timestamp: u64) -> NaiveDate {
+    DateTime::from_timestamp(timestamp as i64, 0).unwrap_or_default().date_naive()
+}
+
+fn status_name(status: &PositionStatus) -> &'static str {
+    match status {
+        PositionStatus::Active => "active",
+        PositionStatus::Unstaking => "unstaking",
+        PositionStatus::Withdrawn => "withdrawn",
+    }
+}
+
+// Column values for one table, in schema order
+enum Column {
+    Text(Vec<String>),
+    OptionalText(Vec<Option<String>>),
+    Int(Vec<u64>),
+    OptionalInt(Vec<Option<u64>>),
+    Float(Vec<f64>),
+}
+
+impl Column {
+    fn data_type(&self) -> (DataType, bool) {
+        match self {
+            Self::Text(_) => (DataType::Utf8, false),
+            Self::OptionalText(_) => (DataType::Utf8, true),
+            Self::Int(_) => (DataType::UInt64, false),
+            Self::OptionalInt(_) => (DataType::UInt64, true),
+            Self::Float(_) => (DataType::Float64, false),
+        }
+    }
+
+    fn into_array(self) -> ArrayRef {
+        match self {
+            Self::Text(values) => Arc::new(StringArray::from(values)),
+            Self::OptionalText(values) => Arc::new(StringArray::from(values)),
+            Self::Int(values) => Arc::new(UInt64Array::from(values)),
+            Self::OptionalInt(values) => Arc::new(UInt64Array::from(values)),
+            Self::Float(values) => Arc::new(Float64Array::from(values)),
+        }
+    }
+}
+
+// Write one partition as `<dir>/<table>/date=<date>/part-0.parquet`
+fn write_partition(dir: &Path, table: &str, date: NaiveDate, columns: Vec<(&str, Column)>) -> Result<()> {
+    let schema = Arc::new(Schema::new(
+        columns.iter()
+            .map(|(name, column)| {
+                let (data_type, nullable) = column.data_type();
+                Field::new(*name, data_type, nullable)
+            })
+            .collect::<Vec<_>>(),
+    ));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        columns.into_iter().map(|(_, column)| column.into_array()).collect(),
+    )?;
+
+    let partition = dir.join(table).join(format!("date={}", date));
+    std::fs::create_dir_all(&partition)?;
+    let file = std::fs::File::create(partition.join("part-0.parquet"))?;
+
+    let props = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut writer = ArrowWriter::try_new(file, schema, Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Write events (by block date), positions (by deposit date) and daily points per
+/// owner (by day) as date-partitioned Parquet files under `dir`. Returns row counts.
+pub async fn export_parquet(db: &Database, rules: &PointsRules, dir: &Path, since: Option<NaiveDate>) -> Result<(usize, usize, usize)> {
+    let keep = |date: NaiveDate| since.is_none_or(|since| date >= since);
+
+    // Events
+    let mut events_by_day: BTreeMap<NaiveDate, Vec<_>> = BTreeMap::new();
+    for event in db.load_events().await? {
+        let date = utc_date(event.timestamp);
+        if keep(date) {
+            events_by_day.entry(date).or_default().push(event);
+        }
+    }
+    let mut event_rows = 0;
+    for (date, events) in events_by_day {
+        event_rows += events.len();
+        write_partition(dir, "events", date, vec![
+            ("event_type", Column::Text(events.iter().map(|e| e.event_type.clone()).collect())),
+            ("user_address", Column::Text(events.iter().map(|e| e.user.to_string()).collect())),
+            ("nonce", Column::Text(events.iter().map(|e| e.nonce.to_string()).collect())),
+            ("amount_wei", Column::OptionalText(events.iter().map(|e| e.amount.map(|a| a.to_string())).collect())),
+            ("amount", Column::Float(events.iter().map(|e| format_token_amount_as_float(e.amount.unwrap_or_default())).collect())),
+            ("unlocks_at", Column::OptionalInt(events.iter().map(|e| e.unlocks_at).collect())),
+            ("lock_duration", Column::OptionalInt(events.iter().map(|e| e.lock_duration).collect())),
+            ("block_number", Column::Int(events.iter().map(|e| e.block_number).collect())),
+            ("log_index", Column::OptionalInt(events.iter().map(|e| e.log_index).collect())),
+            ("transaction_hash", Column::Text(events.iter().map(|e| e.tx_hash.clone()).collect())),
+            ("timestamp", Column::Int(events.iter().map(|e| e.timestamp).collect())),
+        ])?;
+    }
+
+    // Positions
+    let positions = db.fetch_positions(None).await?;
+    let mut positions_by_day: BTreeMap<NaiveDate, Vec<_>> = BTreeMap::new();
+    for position in &positions {
+        let date = utc_date(position.deposit_timestamp);
+        if keep(date) {
+            positions_by_day.entry(date).or_default().push(position);
+        }
+    }
+    let mut position_rows = 0;
+    for (date, day_positions) in positions_by_day {
+        position_rows += day_positions.len();
+        write_partition(dir, "positions", date, vec![
+            ("user_address", Column::Text(day_positions.iter().map(|p| p.user.to_string()).collect())),
+            ("points_owner", Column::Text(day_positions.iter().map(|p| rules.owner(p.user).to_string()).collect())),
+            ("nonce", Column::Text(day_positions.iter().map(|p| p.nonce.to_string()).collect())),
+            ("amount_wei", Column::Text(day_positions.iter().map(|p| p.amount.to_string()).collect())),
+            ("amount", Column::Float(day_positions.iter().map(|p| format_token_amount_as_float(p.amount)).collect())),
+            ("status", Column::Text(day_positions.iter().map(|p| status_name(&p.status).to_string()).collect())),
+            ("deposit_timestamp", Column::Int(day_positions.iter().map(|p| p.deposit_timestamp).collect())),
+            ("withdrawal_initiated_timestamp", Column::OptionalInt(day_positions.iter().map(|p| p.withdrawal_initiated_timestamp).collect())),
+            ("unlocks_at", Column::OptionalInt(day_positions.iter().map(|p| p.unlocks_at).collect())),
+            ("lock_duration", Column::Int(day_positions.iter().map(|p| p.lock_duration).collect())),
+            ("block_number", Column::Int(day_positions.iter().map(|p| p.block_number).collect())),
+        ])?;
+    }
+
+    // Points earned per owner per UTC day, up to now
+    let now = chrono::Utc::now().timestamp() as u64;
+    let first_day = positions.iter().map(|p| p.deposit_timestamp / DAY * DAY).min();
+    let mut points_rows = 0;
+    if let Some(first_day) = first_day {
+        let mut day_start = first_day;
+        while day_start < now {
+            let date = utc_date(day_start);
+            if keep(date) {
+                // Daily caps apply per owner, so aggregate with the engine rather than per position
+                let day_points: Vec<(String, (f64, f64))> = rules.owner_totals(&positions, day_start, (day_start + DAY).min(now))
+                    .into_iter()
+                    .filter(|(_, totals)| totals.points.total() > 0.0)
+                    .map(|(owner, totals)| (owner.to_string(), (totals.points.sage_points, totals.points.formation_points)))
+                    .collect();
+
+                if !day_points.is_empty() {
+                    let mut rows = day_points;
+                    rows.sort_by(|a, b| a.0.cmp(&b.0));
+                    points_rows += rows.len();
+                    write_partition(dir, "daily_points", date, vec![
+                        ("address", Column::Text(rows.iter().map(|r| r.0.clone()).collect())),
+                        ("sage_points", Column::Float(rows.iter().map(|r| r.1.0).collect())),
+                        ("formation_points", Column::Float(rows.iter().map(|r| r.1.1).collect())),
+                        ("total_points", Column::Float(rows.iter().map(|r| r.1.0 + r.1.1).collect())),
+                    ])?;
+                }
+            }
+            day_start += DAY;
+        }
+    }
+
+    Ok((event_rows, position_rows, points_rows))
+}
+
+/// `export-parquet <dir> [--since YYYY-MM-DD]`
+pub async fn run_export_command(db: &Database, rules: &PointsRules, args: &[String]) -> Result<()> {
+    let usage = "Usage: export-parquet <dir> [--since YYYY-MM-DD]";
+    let dir = args.first().ok_or_else(|| eyre!(usage))?;
+    let since = match args.iter().position(|a| a == "--since").and_then(|i| args.get(i + 1)) {
+        Some(date) => Some(NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| eyre!(usage))?),
+        None => None,
+    };
+
+    let (events, positions, points) = export_parquet(db, rules, Path::new(dir), since).await?;
+    println!("📦 Exported {} events, {} positions and {} daily points rows to {}", events, positions, points, dir);
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use alloy::primitives::{Address, Bytes, LogData, B256};
+use alloy::rpc::types::Log;
+use async_trait::async_trait;
+use eyre::{eyre, Result};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::import::IntValue;
+use crate::source::EventSource;
+
+// Blocks per historical sync batch; the cursor is saved after each one
+const HYPERSYNC_BLOCK_RANGE: u64 = 1_000_000;
+
+#[derive(Debug, Deserialize)]
+struct HeightResponse {
+    height: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct QueryResponse {
+    #[serde(default)]
+    data: Vec<QueryData>,
+    next_block: u64, // First block not covered by this response
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct QueryData {
+    #[serde(default)]
+    blocks: Vec<HyperBlock>,
+    #[serde(default)]
+    logs: Vec<HyperLog>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HyperBlock {
+    number: u64,
+    timestamp: IntValue, // Hex string
+}
+
+#[derive(Debug, Deserialize)]
+struct HyperLog {
+    block_number: u64,
+    block_hash: Option<String>,
+    log_index: u64,
+    transaction_index: Option<u64>,
+    transaction_hash: Option<String>,
+    address: String,
+    data: String,
+    topic0: Option<String>,
+    topic1: Option<String>,
+    topic2: Option<String>,
+    topic3: Option<String>,
+}
+
+impl HyperLog {
+    // Same shape as an `eth_getLogs` result, with the block timestamp filled in
+    fn into_log(self, timestamps: &HashMap<u64, u64>) -> Result<Log> {
+        let hash = |value: Option<String>| value.map(|v| B256::from_str(&v)).transpose();
+        let topics = [self.topic0, self.topic1, self.topic2, self.topic3]
+            .into_iter()
+            .flatten()
+            .map(|t| B256::from_str(&t))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Log {
+            inner: alloy::primitives::Log {
+                address: Address::from_str(&self.address)?,
+                data: LogData::new_unchecked(topics, Bytes::from_str(&self.data)?),
+            },
+            block_hash: hash(self.block_hash)?,
+            block_number: Some(self.block_number),
+            block_timestamp: timestamps.get(&self.block_number).copied(),
+            transaction_hash: hash(self.transaction_hash)?,
+            transaction_index: self.transaction_index,
+            log_index: Some(self.log_index),
+            removed: false,
+        })
+    }
+}
+
+/// Contract logs from a HyperSync (Envio) endpoint, which serves millions of blocks per
+/// request instead of the few hundred an RPC allows. Used for backfills only, see
+/// `BackfillSource`; it doesn't report block hashes, so it can't detect reorgs.
+pub struct HyperSyncSource {
+    client: reqwest::Client,
+    url: String,
+    api_token: Option<String>,
+    contract_address: Address,
+}
+
+impl HyperSyncSource {
+    /// Source for `HYPERSYNC_URL` (e.g. `https://base.hypersync.xyz`), None when unset
+    pub fn from_env(contract_address: Address) -> Option<Self> {
+        let url = std::env::var("HYPERSYNC_URL").ok().filter(|u| !u.is_empty())?;
+
+        Some(Self {
+            client: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            api_token: std::env::var("HYPERSYNC_API_TOKEN").ok().filter(|t| !t.is_empty()),
+            contract_address,
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn request(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.api_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+#[async_trait]
+impl EventSource for HyperSyncSource {
+    async fn head(&self) -> Result<u64> {
+        let response: HeightResponse = self.request(self.client.get(format!("{}/height", self.url)))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(response.height)
+    }
+
+    // A response may stop short of `to` (size and time limits), continue from `next_block`
+    async fn logs(&self, from: u64, to: u64) -> Result<Vec<Log>> {
+        let mut logs = Vec::new();
+        let mut next = from;
+
+        while next <= to {
+            let query = json!({
+                "from_block": next,
+                "to_block": to + 1, // Exclusive
+                "logs": [{ "address": [self.contract_address.to_string()] }],
+                "field_selection": {
+                    "block": ["number", "timestamp"],
+                    "log": [
+                        "block_number", "block_hash", "log_index", "transaction_index", "transaction_hash",
+                        "address", "data", "topic0", "topic1", "topic2", "topic3",
+                    ],
+                },
+            });
+            let response: QueryResponse = self.request(self.client.post(format!("{}/query", self.url)).json(&query))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            if response.next_block <= next {
+                return Err(eyre!("HyperSync made no progress at block {}", next));
+            }
+
+            let timestamps: HashMap<u64, u64> = response.data.iter()
+                .flat_map(|d| &d.blocks)
+                .filter_map(|b| b.timestamp.parse().map(|t| (b.number, t.saturating_to::<u64>())))
+                .collect();
+            for log in response.data.into_iter().flat_map(|d| d.logs) {
+                logs.push(log.into_log(&timestamps)?);
+            }
+            next = response.next_block;
+        }
+
+        Ok(logs)
+    }
+
+    fn max_block_range(&self) -> u64 {
+        HYPERSYNC_BLOCK_RANGE
+    }
+}
@@ -0,0 +1,215 @@
+use alloy::primitives::{Address, B256, U256};
+use eyre::{eyre, Result};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::backtest::replay_events;
+use crate::db::{Database, EventData, StoredEvent};
+
+/// Integer given as a JSON number or as a decimal / 0x-hex string
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum IntValue {
+    Number(u64),
+    Text(String),
+}
+
+impl IntValue {
+    pub fn parse(&self) -> Option<U256> {
+        match self {
+            Self::Number(n) => Some(U256::from(*n)),
+            Self::Text(text) => match text.strip_prefix("0x") {
+                Some(hex) => U256::from_str_radix(hex, 16).ok(),
+                None => U256::from_str_radix(text, 10).ok(),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for IntValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(n) => write!(f, "{}", n),
+            Self::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+/// Event row as exported by an external source (subgraph, the old Python tracker)
+#[derive(Debug, Deserialize)]
+pub struct ImportRow {
+    pub event_type: String,
+    pub user: String,
+    pub nonce: IntValue,
+    pub amount: Option<IntValue>, // wei, as a string when above 2^64
+    pub unlocks_at: Option<u64>,
+    #[serde(default)]
+    pub lock_duration: Option<u64>, // Seconds, V2 deposits only
+    pub block_number: u64,
+    #[serde(default)]
+    pub log_index: Option<u64>,
+    pub tx_hash: String,
+    pub timestamp: u64,
+}
+
+// Events are unique per transaction, type and position
+type EventKey = (String, String, Address, U256);
+
+fn event_key(event: &StoredEvent) -> EventKey {
+    (event.tx_hash.clone(), event.event_type.clone(), event.user, event.nonce)
+}
+
+impl ImportRow {
+    /// Validate the row and convert it to the stored representation
+    pub fn validate(&self) -> Result<StoredEvent> {
+        let needs_amount = match self.event_type.as_str() {
+            "Deposit" | "Withdraw" | "RestakeFromWithdrawalInitiated" => true,
+            "InitiateWithdraw" => false,
+            other => return Err(eyre!("unknown event type '{}'", other)),
+        };
+
+        let user = Address::from_str(&self.user).map_err(|_| eyre!("invalid address '{}'", self.user))?;
+        let nonce = self.nonce.parse().ok_or_else(|| eyre!("invalid nonce '{}'", self.nonce))?;
+        let tx_hash = B256::from_str(&self.tx_hash).map_err(|_| eyre!("invalid transaction hash '{}'", self.tx_hash))?;
+
+        let amount = match &self.amount {
+            Some(amount) => Some(amount.parse().ok_or_else(|| eyre!("invalid amount '{}'", amount))?),
+            None if needs_amount => return Err(eyre!("{} requires an amount", self.event_type)),
+            None => None,
+        };
+        if self.event_type == "InitiateWithdraw" && self.unlocks_at.is_none() {
+            return Err(eyre!("InitiateWithdraw requires unlocks_at"));
+        }
+        if self.block_number == 0 || self.timestamp == 0 {
+            return Err(eyre!("block_number and timestamp must be set"));
+        }
+
+        Ok(StoredEvent {
+            event_type: self.event_type.clone(),
+            user,
+            nonce,
+            amount,
+            unlocks_at: self.unlocks_at,
+            lock_duration: self.lock_duration,
+            block_number: self.block_number,
+            log_index: self.log_index,
+            tx_hash: tx_hash.to_string(), // Same lowercase form the indexer stores
+            timestamp: self.timestamp,
+        })
+    }
+}
+
+// Minimal CSV reader for exported event rows: a header line naming the `ImportRow`
+// fields in any order, comma separated, no quoting
+fn parse_csv(contents: &str) -> Result<Vec<ImportRow>> {
+    let mut lines = contents.lines().filter(|l| !l.trim().is_empty());
+    let header: Vec<&str> = lines.next()
+        .ok_or_else(|| eyre!("CSV file is empty"))?
+        .split(',')
+        .map(str::trim)
+        .collect();
+
+    lines
+        .enumerate()
+        .map(|(i, line)| {
+            let values: Vec<&str> = line.split(',').map(str::trim).collect();
+            if values.len() != header.len() {
+                return Err(eyre!("line {}: expected {} columns, found {}", i + 2, header.len(), values.len()));
+            }
+
+            // Go through JSON so both formats share one set of field names and types
+            let mut object = serde_json::Map::new();
+            for (name, value) in header.iter().zip(values) {
+                let value = match (*name, value) {
+                    (_, "") => serde_json::Value::Null,
+                    ("unlocks_at" | "lock_duration" | "block_number" | "log_index" | "timestamp", number) => number.parse::<u64>()
+                        .map(serde_json::Value::from)
+                        .map_err(|_| eyre!("line {}: invalid {} '{}'", i + 2, name, number))?,
+                    (_, text) => serde_json::Value::from(text),
+                };
+                object.insert(name.to_string(), value);
+            }
+            serde_json::from_value(serde_json::Value::Object(object))
+                .map_err(|e| eyre!("line {}: {}", i + 2, e))
+        })
+        .collect()
+}
+
+/// `import-events <file.csv|file.json> [--dry-run]`
+pub async fn run_import_command(db: &Database, args: &[String]) -> Result<()> {
+    let path = args.first().ok_or_else(|| eyre!("Usage: import-events <file.csv|file.json> [--dry-run]"))?;
+    let dry_run = args.iter().any(|a| a == "--dry-run");
+
+    let contents = std::fs::read_to_string(path)?;
+    let rows: Vec<ImportRow> = if path.ends_with(".csv") {
+        parse_csv(&contents)?
+    } else {
+        serde_json::from_str(&contents)?
+    };
+
+    // Reject the whole file if any row is invalid, so a partial import never happens
+    let mut events = Vec::with_capacity(rows.len());
+    let mut errors = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        match row.validate() {
+            Ok(event) => events.push(event),
+            Err(e) => errors.push(format!("  row {}: {}", i + 1, e)),
+        }
+    }
+    if !errors.is_empty() {
+        for error in errors.iter().take(20) {
+            eprintln!("{}", error);
+        }
+        return Err(eyre!("{} of {} rows are invalid, nothing was imported", errors.len(), rows.len()));
+    }
+
+    // Bootstrapped positions already cover everything up to the bootstrap block
+    if let Some(bootstrap) = db.get_bootstrap_block().await? {
+        let early = events.iter().filter(|e| e.block_number <= bootstrap).count();
+        if early > 0 {
+            return Err(eyre!("{} rows are at or before bootstrap block {}, nothing was imported", early, bootstrap));
+        }
+    }
+
+    // Skip events that are already stored or repeated in the file
+    let mut seen: HashSet<EventKey> = db.load_events().await?.iter().map(event_key).collect();
+    events.sort_by_key(|e| e.block_number);
+    events.retain(|e| seen.insert(event_key(e)));
+
+    println!("📥 {} rows read, {} new events, {} duplicates skipped", rows.len(), events.len(), rows.len() - events.len());
+    if dry_run || events.is_empty() {
+        return Ok(());
+    }
+
+    for event in &events {
+        if event.event_type == "Deposit" {
+            db.record_staker(&event.user, event.timestamp, event.block_number).await?;
+        }
+        db.save_event(EventData {
+            event_type: event.event_type.clone(),
+            user: event.user,
+            nonce: Some(event.nonce),
+            amount: event.amount,
+            unlocks_at: event.unlocks_at,
+            lock_duration: event.lock_duration,
+            block_number: event.block_number,
+            log_index: event.log_index,
+            tx_hash: event.tx_hash.clone(),
+            timestamp: event.timestamp,
+        }).await?;
+    }
+
+    // Rebuild positions from the merged history
+    let tracker = replay_events(&db.load_events().await?).await;
+    let positions: Vec<_> = tracker.active_positions.values()
+        .chain(tracker.unstaking_positions.values())
+        .chain(tracker.withdrawn_positions.values())
+        .cloned()
+        .collect();
+    db.replace_positions(&positions).await?;
+
+    println!("✅ Imported {} events, rebuilt {} positions", events.len(), positions.len());
+    println!("Restart the service to load the rebuilt positions.");
+    Ok(())
+}
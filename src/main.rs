@@ -1,20 +1,56 @@
 use alloy::{
-    primitives::{Address, U256},
-    providers::{Provider, ProviderBuilder},
-    rpc::types::{Filter, Log},
+    primitives::{Address, B256, U256},
+    providers::{ProviderBuilder, RootProvider},
+    rpc::types::Log,
     sol,
     sol_types::SolEvent,
+    transports::http::{Client, Http},
 };
 use eyre::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::str::FromStr;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::time::sleep;
 
 mod db;
 mod api;
-use db::{Database, EventData};
+mod alerts;
+mod reconcile;
+mod publisher;
+mod request_id;
+mod backtest;
+mod backup;
+mod bootstrap;
+mod cache;
+mod chain;
+mod config;
+mod ens;
+mod export;
+mod hypersync;
+mod import;
+mod notifications;
+mod points;
+mod profiles;
+mod programs;
+mod rollback;
+mod scheduler;
+mod settings;
+mod siwe;
+mod snapshot;
+mod source;
+mod trace;
+mod watchdog;
+use alerts::{Flow, Notifier, WhaleAlerts};
+use bootstrap::ViewBootstrap;
+use db::{competition_from, Database, EventData};
+use points::{CompetitionBoard, Delegation, PointsBreakdown, PointsRules, SharedRules};
+use publisher::RootPublisher;
+use scheduler::Scheduler;
+use hypersync::HyperSyncSource;
+use source::{BackfillSource, EventSource, FileEventSource, RpcEventSource};
+use trace::{RecordingChain, ReplayChain, TraceMode};
+use watchdog::IndexerStatus;
+use std::sync::{Arc, RwLock};
 
 // Define the contract events using the sol! macro
 sol!(
@@ -24,12 +60,31 @@ sol!(
         event InitiateWithdraw(address indexed user, uint256 nonce, uint256 unlocksAt, uint256 timestamp);
         event Withdraw(address indexed user, uint256 amount, uint256 nonce, uint256 timestamp);
         event RestakeFromWithdrawalInitiated(address indexed user, uint256 nonce, uint256 amount, uint256 timestamp);
+
+        // View functions used for reconciliation
+        function stakedBalance(address user) external view returns (uint256);
+        function getPosition(address user, uint256 nonce) external view returns (uint256 amount, uint256 unlocksAt, uint8 status);
+    }
+);
+
+// Lock-tier contract version, whose deposits carry the chosen lock duration in seconds,
+// with points delegation
+sol!(
+    contract SageStakingV2 {
+        event Deposit(address indexed user, uint256 amount, uint256 nonce, uint256 timestamp, uint256 lockDuration);
+        event Delegate(address indexed user, address indexed delegatee);
     }
 );
 
 // Maximum blocks to fetch in one request (to avoid RPC limits)
 const MAX_BLOCK_RANGE: u64 = 500; // Reduced to avoid rate limits
 
+// Minimum seconds between TVL snapshots
+const TVL_SNAPSHOT_INTERVAL: u64 = 900; // 15 minutes
+
+// Minimum seconds between rank snapshots
+const RANK_SNAPSHOT_INTERVAL: u64 = 86400; // Daily
+
 // Position status for tracking
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum PositionStatus {
@@ -42,36 +97,67 @@ pub enum PositionStatus {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
     pub user: Address,
-    pub nonce: u64,
+    pub nonce: U256,
     pub amount: U256, // Amount in wei
     pub deposit_timestamp: u64,
     pub status: PositionStatus,
     pub withdrawal_initiated_timestamp: Option<u64>,
+    pub unlocks_at: Option<u64>, // Cooldown end from InitiateWithdraw
     pub block_number: u64, // Track the block when position was created
-}
-
-// Points breakdown
-#[derive(Debug, Clone, Default)]
-struct PointsBreakdown {
-    sage_points: f64,
-    formation_points: f64,
+    #[serde(default)]
+    pub lock_duration: u64, // Seconds locked at deposit, 0 before lock tiers
 }
 
 // Global state to track all positions
 struct PointsTracker {
     // Separate tracking for different position states for efficiency
-    active_positions: HashMap<(Address, u64), Position>,     // Currently earning points
-    unstaking_positions: HashMap<(Address, u64), Position>,  // Withdrawal initiated, not earning
-    withdrawn_positions: HashMap<(Address, u64), Position>,  // Fully withdrawn
+    active_positions: HashMap<(Address, U256), Position>,     // Currently earning points
+    unstaking_positions: HashMap<(Address, U256), Position>,  // Withdrawal initiated, not earning
+    withdrawn_positions: HashMap<(Address, U256), Position>,  // Fully withdrawn
     total_events_processed: usize,
     current_block: u64,
+    last_tvl_timestamp: u64,  // Timestamp of the last recorded TVL snapshot
+    last_event_timestamp: u64,  // Timestamp of the latest event, for events that carry none
+    last_rank_timestamp: u64,  // Timestamp of the last recorded rank snapshot
+    archived_periods: HashMap<CompetitionBoard, u64>,  // Last archived competition period per board
+    rules: SharedRules,  // Accrual rules, replaced on settings reload
     db: Option<Database>,  // Database connection for persistence
+    whale_alerts: Option<WhaleAlerts>,  // Set once caught up, so historical syncs don't alert
 }
 
+// Tracker shared between the monitoring task (writer) and the API (readers)
+type SharedTracker = Arc<tokio::sync::RwLock<PointsTracker>>;
+
 impl PointsTracker {
-    async fn with_database_instance(db: Database) -> Result<Self> {
+    // Tracker without persistence (used for replays)
+    fn in_memory(rules: PointsRules) -> Self {
+        Self {
+            active_positions: HashMap::new(),
+            unstaking_positions: HashMap::new(),
+            withdrawn_positions: HashMap::new(),
+            total_events_processed: 0,
+            current_block: 0,
+            last_tvl_timestamp: 0,
+            last_event_timestamp: 0,
+            last_rank_timestamp: 0,
+            archived_periods: HashMap::new(),
+            rules: Arc::new(RwLock::new(rules)),
+            db: None,
+            whale_alerts: None,
+        }
+    }
+
+    async fn with_database_instance(db: Database, rules: SharedRules) -> Result<Self> {
         // Load existing positions from database
         let (active, unstaking, withdrawn) = db.load_positions().await?;
+        let last_tvl_timestamp = db.get_last_tvl_timestamp().await?.unwrap_or(0);
+        let last_rank_timestamp = db.get_last_rank_timestamp().await?.unwrap_or(0);
+        let mut archived_periods = HashMap::new();
+        for board in CompetitionBoard::ALL {
+            if let Some(period) = db.get_last_archived_period(board).await? {
+                archived_periods.insert(board, period);
+            }
+        }
         
         let tracker = Self {
             active_positions: active.into_iter().collect(),
@@ -79,24 +165,100 @@ impl PointsTracker {
             withdrawn_positions: withdrawn.into_iter().collect(),
             total_events_processed: 0,
             current_block: 0,
+            last_tvl_timestamp,
+            last_event_timestamp: last_tvl_timestamp,
+            last_rank_timestamp,
+            archived_periods,
+            rules,
             db: Some(db),
+            whale_alerts: None,
         };
         
         Ok(tracker)
     }
 
+    // All positions, whatever their state
+    fn positions(&self) -> impl Iterator<Item = &Position> {
+        self.active_positions.values()
+            .chain(self.unstaking_positions.values())
+            .chain(self.withdrawn_positions.values())
+    }
+
     // Get a position from any of the maps
-    fn get_position(&self, key: &(Address, u64)) -> Option<&Position> {
+    fn get_position(&self, key: &(Address, U256)) -> Option<&Position> {
         self.active_positions.get(key)
             .or_else(|| self.unstaking_positions.get(key))
             .or_else(|| self.withdrawn_positions.get(key))
     }
 
+    // Get keys of all positions belonging to a user
+    fn get_user_position_keys(&self, user: &Address) -> Vec<(Address, U256)> {
+        self.active_positions.keys()
+            .chain(self.unstaking_positions.keys())
+            .chain(self.withdrawn_positions.keys())
+            .filter(|(u, _)| u == user)
+            .copied()
+            .collect()
+    }
+
+    // Overwrite a position with on-chain state, returns true if anything changed
+    async fn repair_position(
+        &mut self,
+        key: (Address, U256),
+        amount: U256,
+        status: PositionStatus,
+        unlocks_at: Option<u64>,
+    ) -> bool {
+        let mut position = match self.active_positions.remove(&key)
+            .or_else(|| self.unstaking_positions.remove(&key))
+            .or_else(|| self.withdrawn_positions.remove(&key))
+        {
+            Some(position) => position,
+            None => return false,
+        };
+
+        let changed = position.amount != amount || position.status != status || position.unlocks_at != unlocks_at;
+        if changed {
+            println!("🔧 Repairing position {} #{}: {:?} {} → {:?} {}",
+                format_address(key.0), key.1,
+                position.status, format_token_amount(position.amount),
+                status, format_token_amount(amount));
+
+            // Points stop at withdrawal initiation; if we never saw it, stop them now
+            if status == PositionStatus::Active {
+                position.withdrawal_initiated_timestamp = None;
+            } else if position.withdrawal_initiated_timestamp.is_none() {
+                position.withdrawal_initiated_timestamp = Some(
+                    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+                );
+            }
+            position.amount = amount;
+            position.status = status;
+            position.unlocks_at = unlocks_at;
+
+            // Save to database
+            if let Some(db) = &self.db {
+                if let Err(e) = db.save_position(&position).await {
+                    eprintln!("⚠️  Failed to save position to database: {}", e);
+                }
+            }
+        }
+
+        match position.status {
+            PositionStatus::Active => self.active_positions.insert(key, position),
+            PositionStatus::Unstaking => self.unstaking_positions.insert(key, position),
+            PositionStatus::Withdrawn => self.withdrawn_positions.insert(key, position),
+        };
+
+        changed
+    }
+
     // Move position between states
-    async fn move_to_unstaking(&mut self, key: (Address, u64), timestamp: u64) {
+    async fn move_to_unstaking(&mut self, key: (Address, U256), timestamp: u64, unlocks_at: u64) {
         if let Some(mut position) = self.active_positions.remove(&key) {
             position.status = PositionStatus::Unstaking;
             position.withdrawal_initiated_timestamp = Some(timestamp);
+            position.unlocks_at = Some(unlocks_at);
             
             // Save to database
             if let Some(db) = &self.db {
@@ -109,7 +271,7 @@ impl PointsTracker {
         }
     }
 
-    async fn move_to_withdrawn(&mut self, key: (Address, u64)) {
+    async fn move_to_withdrawn(&mut self, key: (Address, U256)) {
         if let Some(mut position) = self.unstaking_positions.remove(&key) {
             position.status = PositionStatus::Withdrawn;
             
@@ -124,10 +286,11 @@ impl PointsTracker {
         }
     }
 
-    async fn move_to_active(&mut self, key: (Address, u64), new_deposit_timestamp: u64) {
+    async fn move_to_active(&mut self, key: (Address, U256), new_deposit_timestamp: u64) {
         if let Some(mut position) = self.unstaking_positions.remove(&key) {
             position.status = PositionStatus::Active;
             position.withdrawal_initiated_timestamp = None;
+            position.unlocks_at = None;
             position.deposit_timestamp = new_deposit_timestamp;
             
             // Save to database
@@ -141,7 +304,7 @@ impl PointsTracker {
         }
     }
     
-    async fn add_active_position(&mut self, key: (Address, u64), position: Position) {
+    async fn add_active_position(&mut self, key: (Address, U256), position: Position) {
         // Save to database
         if let Some(db) = &self.db {
             if let Err(e) = db.save_position(&position).await {
@@ -152,34 +315,115 @@ impl PointsTracker {
         self.active_positions.insert(key, position);
     }
 
-    // Calculate points for a position with both SAGE and Formation points
-    fn calculate_position_points(&self, position: &Position) -> PointsBreakdown {
-        let end_timestamp = if let Some(withdrawal_ts) = position.withdrawal_initiated_timestamp {
-            // For unstaking/withdrawn positions, points stopped at withdrawal initiation
-            withdrawal_ts
-        } else if matches!(position.status, PositionStatus::Active) {
-            // Still active, calculate until now
-            SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs()
-        } else {
-            // Shouldn't happen, but use deposit timestamp as fallback
-            position.deposit_timestamp
-        };
+    // Total staked amount in wei (active, unstaking)
+    fn get_total_staked(&self) -> (U256, U256) {
+        let active = self.active_positions.values().fold(U256::ZERO, |acc, p| acc + p.amount);
+        let unstaking = self.unstaking_positions.values().fold(U256::ZERO, |acc, p| acc + p.amount);
+        (active, unstaking)
+    }
 
-        let seconds_staked = end_timestamp.saturating_sub(position.deposit_timestamp);
-        let days_staked = seconds_staked as f64 / 86400.0; // 86400 seconds in a day
-        
-        // Convert amount from wei to tokens (18 decimals)
-        let tokens = format_token_amount_as_float(position.amount);
+    // Record TVL and rank snapshots and archive ended competitions, each when it is due
+    async fn maybe_record_snapshots(&mut self, timestamp: u64) {
+        self.last_event_timestamp = self.last_event_timestamp.max(timestamp);
+        self.maybe_record_tvl(timestamp).await;
+        self.maybe_record_ranks(timestamp).await;
+        self.maybe_archive_competitions(timestamp).await;
+    }
+
+    // Record a TVL snapshot if the interval has elapsed since the last one
+    async fn maybe_record_tvl(&mut self, timestamp: u64) {
+        if timestamp < self.last_tvl_timestamp + TVL_SNAPSHOT_INTERVAL {
+            return;
+        }
+
+        let (active, unstaking) = self.get_total_staked();
+        if let Some(db) = &self.db {
+            if let Err(e) = db.save_tvl_snapshot(timestamp, self.current_block, active, unstaking).await {
+                eprintln!("⚠️  Failed to save TVL snapshot: {}", e);
+                return;
+            }
+        }
         
-        // 0.01 SAGE points per token per day
-        // 0.005 Formation points per token per day
-        PointsBreakdown {
-            sage_points: tokens * days_staked * 0.01,
-            formation_points: tokens * days_staked * 0.005,
+        self.last_tvl_timestamp = timestamp;
+    }
+
+    // Record every points owner's rank as of `timestamp` if the interval has elapsed
+    // since the last rank snapshot
+    async fn maybe_record_ranks(&mut self, timestamp: u64) {
+        if timestamp < self.last_rank_timestamp + RANK_SNAPSHOT_INTERVAL {
+            return;
         }
+        let Some(db) = &self.db else { return };
+
+        let mut ranked: Vec<(Address, f64)> = self.rules.read().unwrap()
+            .owner_totals(self.positions(), 0, timestamp)
+            .into_iter()
+            .map(|(owner, totals)| (owner, totals.points.total()))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let ranks: Vec<(Address, i32, f64)> = ranked.into_iter()
+            .enumerate()
+            .map(|(i, (owner, points))| (owner, i as i32 + 1, points))
+            .collect();
+
+        if let Err(e) = db.save_rank_snapshot(timestamp, self.current_block, &ranks).await {
+            eprintln!("⚠️  Failed to save rank snapshot: {}", e);
+            return;
+        }
+        self.last_rank_timestamp = timestamp;
+    }
+
+    // Archive the winners of every competition period that ended by `timestamp`,
+    // catching up on all of them after a sync or when competitions were just configured
+    async fn maybe_archive_competitions(&mut self, timestamp: u64) {
+        let Some(db) = self.db.clone() else { return };
+        let Some(config) = self.rules.read().unwrap().competitions.clone() else { return };
+
+        for board in CompetitionBoard::ALL {
+            let Some(current) = config.period_at(board, timestamp) else { continue };
+            let mut period = self.archived_periods.get(&board).map_or(1, |p| p + 1);
+            if period >= current {
+                continue;
+            }
+
+            let rules = self.rules.read().unwrap().clone();
+            while period < current {
+                let Some(mut standings) = competition_from(self.positions(), board, period, &rules, timestamp) else { break };
+                standings.is_final = true;
+                standings.archived_at = Some(timestamp);
+                if let Err(e) = db.save_competition_standings(&standings).await {
+                    eprintln!("⚠️  Failed to archive {} competition #{}: {}", board.as_str(), period, e);
+                    return;
+                }
+                if let Some(winner) = standings.entries.first() {
+                    println!("🏆 {} competition #{} won by {} with {:.2} points", board.as_str(), period, winner.address, winner.total_points);
+                }
+                self.archived_periods.insert(board, period);
+                period += 1;
+            }
+        }
+    }
+
+    // Checkpoint every position's accrual up to `now` so points reads only integrate
+    // from here on. Skipped if the rules changed while it was computed.
+    fn checkpoint_accrual(&self, now: u64) {
+        let rules = self.rules.read().unwrap().clone();
+        let checkpoints = rules.checkpoint(self.positions(), now);
+
+        let mut shared = self.rules.write().unwrap();
+        if shared.version() == rules.version() {
+            shared.checkpoints = Arc::new(checkpoints);
+        }
+    }
+
+    // Calculate points for a position with both SAGE and Formation points
+    fn calculate_position_points(&self, position: &Position) -> PointsBreakdown {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        self.rules.read().unwrap().position_points(position, now)
     }
 
     // Calculate total points for a user
@@ -234,39 +478,15 @@ impl PointsTracker {
         (active_amount, unstaking_amount, withdrawn_amount)
     }
 
-    // Get points leaderboard
+    // Get points leaderboard, aggregated by points owner like the API's
     fn get_leaderboard(&self) -> Vec<(Address, PointsBreakdown)> {
-        let mut user_points: HashMap<Address, PointsBreakdown> = HashMap::new();
-        
-        // Calculate points for all positions
-        for position in self.active_positions.values() {
-            let points = self.calculate_position_points(position);
-            let entry = user_points.entry(position.user).or_default();
-            entry.sage_points += points.sage_points;
-            entry.formation_points += points.formation_points;
-        }
-        
-        for position in self.unstaking_positions.values() {
-            let points = self.calculate_position_points(position);
-            let entry = user_points.entry(position.user).or_default();
-            entry.sage_points += points.sage_points;
-            entry.formation_points += points.formation_points;
-        }
-        
-        for position in self.withdrawn_positions.values() {
-            let points = self.calculate_position_points(position);
-            let entry = user_points.entry(position.user).or_default();
-            entry.sage_points += points.sage_points;
-            entry.formation_points += points.formation_points;
-        }
-        
-        let mut leaderboard: Vec<(Address, PointsBreakdown)> = user_points.into_iter().collect();
-        leaderboard.sort_by(|a, b| {
-            // Sort by total points (sage + formation)
-            let total_a = a.1.sage_points + a.1.formation_points;
-            let total_b = b.1.sage_points + b.1.formation_points;
-            total_b.partial_cmp(&total_a).unwrap()
-        });
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let mut leaderboard: Vec<(Address, PointsBreakdown)> = self.rules.read().unwrap()
+            .owner_totals(self.positions(), 0, now)
+            .into_iter()
+            .map(|(owner, totals)| (owner, totals.points))
+            .collect();
+        leaderboard.sort_by(|a, b| b.1.total().total_cmp(&a.1.total()));
         leaderboard
     }
 
@@ -320,6 +540,14 @@ impl PointsTracker {
     }
 }
 
+// DATABASE_URL is required unless running with --no-db
+fn database_url() -> Result<String> {
+    std::env::var("DATABASE_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .ok_or_else(|| eyre::eyre!("DATABASE_URL is not set: PostgreSQL or sqlite:// URL of the points database"))
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logger
@@ -330,184 +558,439 @@ async fn main() -> Result<()> {
     
     println!("🚀 Starting Points Calculator Service...");
     
-    // Get configuration from environment
-    let database_url = std::env::var("DATABASE_URL")
-        .expect("DATABASE_URL must be set");
-    let base_rpc_url = std::env::var("BASE_RPC_URL")
-        .expect("BASE_RPC_URL must be set");
-    let contract_address_str = std::env::var("CONTRACT_ADDRESS")
-        .expect("CONTRACT_ADDRESS must be set");
-    let deployment_block = std::env::var("DEPLOYMENT_BLOCK")
-        .expect("DEPLOYMENT_BLOCK must be set")
-        .parse::<u64>()
-        .expect("DEPLOYMENT_BLOCK must be a valid u64");
-    let api_port = std::env::var("PORT")
-        .unwrap_or_else(|_| "3000".to_string())
-        .parse::<u16>()
-        .unwrap_or(3000);
-
-    // Initialize database connection
-    let db = Database::new(&database_url).await?;
+    // Offline commands only need the database
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("backtest") {
+        let db = if args.iter().any(|a| a == "--no-db") {
+            Database::in_memory()
+        } else {
+            Database::new(&database_url()?).await?
+        };
+        return backtest::run_backtest_command(&db, &args[2..]).await;
+    }
+    if args.get(1).map(String::as_str) == Some("pause") {
+        let db = Database::new(&database_url()?).await?;
+        return settings::run_pause_command(&db, &args[2..]).await;
+    }
+    if args.get(1).map(String::as_str) == Some("migrate-points") {
+        let db = Database::new(&database_url()?).await?;
+        return settings::run_migrate_points_command(&db, &args[2..]).await;
+    }
+    if args.get(1).map(String::as_str) == Some("label") {
+        let db = Database::new(&database_url()?).await?;
+        return settings::run_label_command(&db, &args[2..]).await;
+    }
+    if args.get(1).map(String::as_str) == Some("backup") {
+        let db = Database::new(&database_url()?).await?;
+        let settings_path = std::env::var("SETTINGS_FILE").ok();
+        let rules: SharedRules = Arc::new(RwLock::new(settings::load_rules(&db, settings_path.as_deref()).await?));
+        return backup::run_backup_command(&db, &rules, settings_path.as_deref(), &args[2..]).await;
+    }
+    if args.get(1).map(String::as_str) == Some("export-parquet") {
+        let db = Database::new(&database_url()?).await?;
+        let settings_path = std::env::var("SETTINGS_FILE").ok();
+        let rules = settings::load_rules(&db, settings_path.as_deref()).await?;
+        return export::run_export_command(&db, &rules, &args[2..]).await;
+    }
+    if args.get(1).map(String::as_str) == Some("import-events") {
+        let db = Database::new(&database_url()?).await?;
+        return import::run_import_command(&db, &args[2..]).await;
+    }
+    if args.get(1).map(String::as_str) == Some("rollback") {
+        let db = Database::new(&database_url()?).await?;
+        return rollback::run_rollback_command(&db, &args[2..]).await;
+    }
+    if args.get(1).map(String::as_str) == Some("snapshot") {
+        let db = Database::new(&database_url()?).await?;
+        let settings_path = std::env::var("SETTINGS_FILE").ok();
+        return snapshot::run_snapshot_command(&db, settings_path.as_deref(), &args[2..]).await;
+    }
+
+    // Development mode without a database, state lives only in memory
+    let no_db = args.iter().any(|a| a == "--no-db");
+
+    // Check all required settings, the chain and the databases before starting anything
+    let mut config = config::load(no_db).await?;
+    let programs = std::mem::take(&mut config.programs);
+    // Reloadable settings (rates, boosts, blocklist), re-read on SIGHUP
+    let settings_path = std::env::var("SETTINGS_FILE").ok();
+    // Pre-recorded logs to index instead of the live chain
+    let events_file = std::env::var("EVENTS_FILE").ok();
+    if no_db {
+        println!("🧪 Running without a database, all state is kept in memory and lost on exit");
+    }
+    let db = config.db.clone();
+
+    // Load accrual rules shared by the indexer and the API
+    let rules: SharedRules = Arc::new(RwLock::new(
+        settings::load_rules(&db, settings_path.as_deref()).await?
+    ));
+
+    // One-shot commands
+    if args.get(1).map(String::as_str) == Some("reconcile") {
+        let repair = args.iter().any(|a| a == "--repair");
+        let tracker: SharedTracker = Arc::new(tokio::sync::RwLock::new(PointsTracker::with_database_instance(db, rules).await?));
+        let provider = ProviderBuilder::new().on_http(config.base_rpc_url.parse()?);
+
+        let report = reconcile::run_reconciliation(&provider, config.contract_address, &tracker, repair).await?;
+        report.print();
+        return Ok(());
+    }
+    
+    // Optional on-chain points-root publisher
+    let publisher = match config.publisher.clone() {
+        Some(publisher) => Some(RootPublisher::new(publisher, Some(db.clone()))?),
+        None => None,
+    };
+    
+    // Periodic jobs, locked through the database so only one instance runs each
+    let mut scheduler = Scheduler::new(db.clone(), config.job_schedules.clone());
+
+    // Scheduled backups to S3-compatible storage
+    if let Some(backup) = config.backup.clone() {
+        println!("☁️  Backing up to s3://{}/{}, keeping {}", backup.bucket, backup.prefix, backup.retention);
+        backup::register(&mut scheduler, db.clone(), rules.clone(), settings_path.clone(), backup);
+    }
+
+    settings::spawn_reload_on_sighup(db.clone(), settings_path, rules.clone());
     
-    // Clone database for monitoring task
-    let monitor_db = db.clone();
+    // Where the indexer reads contract logs from
+    let (source, bootstrap): (Box<dyn EventSource>, _) = match &events_file {
+        Some(path) => {
+            let source = FileEventSource::open(path)?;
+            println!("📼 Indexing pre-recorded logs from {} instead of the chain", path);
+            (Box::new(source), None)
+        }
+        None => {
+            let chain = ProviderBuilder::new().on_http(config.base_rpc_url.parse()?);
+            let bootstrap = ViewBootstrap::from_env(chain.clone(), config.contract_address).filter(|_| !replaying(&config));
+            (rpc_source(chain, config.contract_address, config.trace.clone())?, bootstrap)
+        }
+    };
+
+    // Alert when the indexer stops making progress
+    let notifier = Notifier::from_env();
+    let status = IndexerStatus::new();
+    watchdog::spawn(status.clone(), config.watchdog.clone(), notifier.clone());
+
+    // Notify about large deposits and unstakes
+    let whale_alerts = WhaleAlerts::new(config.whale_alerts, notifier.clone());
+    if let Some(alerts) = &whale_alerts {
+        println!("🐋 Whale alerts: {}", alerts.describe());
+    }
+
+    let mut extra_programs = Vec::new();
+    for program in programs {
+        extra_programs.push(start_program(program, &config, &notifier, &mut scheduler).await?);
+    }
+
+    // Indexer state, also read by the API
+    let tracker: SharedTracker = Arc::new(tokio::sync::RwLock::new(
+        PointsTracker::with_database_instance(db.clone(), rules.clone()).await?
+    ));
+    let monitor_tracker = tracker.clone();
+    let monitor_status = status.clone();
+
+    // Compare our state against the contract
+    if let Some(interval) = config.reconcile.interval_secs {
+        let provider = ProviderBuilder::new().on_http(config.base_rpc_url.parse()?);
+        reconcile::register(&mut scheduler, "reconcile", interval, provider, config.contract_address, tracker.clone(), config.reconcile.auto_repair);
+    }
+    let scheduler = scheduler.start();
+
+    // Tell users about the events they subscribed to
+    notifications::spawn(db.clone(), rules.clone(), tracker.clone(), notifications::UserNotifier::from_env()?);
     
     // Spawn monitoring task in the background
+    let deployment_block = config.deployment_block;
     tokio::spawn(async move {
-        if let Err(e) = run_monitoring(monitor_db, base_rpc_url, contract_address_str, deployment_block).await {
+        if let Err(e) = run_monitoring(
+            source,
+            monitor_tracker,
+            monitor_status,
+            deployment_block,
+            publisher,
+            whale_alerts,
+            bootstrap,
+        ).await {
             eprintln!("❌ Monitoring task error: {}", e);
         }
     });
     
     // Run API server on main task
-    api::run_api_server(db, api_port).await?;
+    api::run_api_server(config.api_db, rules, tracker, status, extra_programs, scheduler, config.api).await?;
     
     Ok(())
 }
 
-// Extract monitoring logic into a separate function
-async fn run_monitoring(
-    db: Database,
-    base_rpc_url: String,
-    contract_address_str: String, 
-    deployment_block: u64
-) -> Result<()> {
-    // Initialize points tracker with database
-    let mut tracker = PointsTracker::with_database_instance(db).await?;
+// Whether chain reads come from a recorded trace, which skips the view bootstrap
+fn replaying(config: &config::Config) -> bool {
+    config.trace.as_ref().is_some_and(TraceMode::is_replay)
+}
 
-    // Parse the contract address
-    let contract_address = Address::from_str(&contract_address_str)?;
+// Start indexing an additional program with its own contract, settings and storage.
+// It shares the RPC, alert channels, scheduler, reconciliation, whale alert and trace
+// settings of the main program; the root publisher and backups only cover the main program
+async fn start_program(
+    program: config::Program,
+    config: &config::Config,
+    notifier: &Notifier,
+    scheduler: &mut Scheduler,
+) -> Result<api::ProgramState> {
+    let config::Program { config: program, db, api_db } = program;
+    println!("🧩 Starting program '{}' for contract {} from block {}", program.name, program.contract_address, program.deployment_block);
 
-    // Create HTTP provider
-    let provider = ProviderBuilder::new().on_http(base_rpc_url.parse()?);
+    let rules: SharedRules = Arc::new(RwLock::new(
+        settings::load_rules(&db, program.settings_file.as_deref()).await?
+    ));
+    settings::spawn_reload_on_sighup(db.clone(), program.settings_file.clone(), rules.clone());
 
-    // Get the current block number
-    let current_block = provider.get_block_number().await?;
+    let status = IndexerStatus::new();
+    watchdog::spawn(status.clone(), config.watchdog.clone(), notifier.clone());
 
-    // Load the last processed block from database or use deployment block
-    let mut last_block = if let Some(db) = &tracker.db {
-        let db_block = db.get_last_processed_block().await?;
-        
-        // Use the database block if it's valid, otherwise start from deployment
-        db_block.filter(|&b| b >= deployment_block).unwrap_or(deployment_block)
-    } else {
-        deployment_block
-    };
+    let tracker: SharedTracker = Arc::new(tokio::sync::RwLock::new(
+        PointsTracker::with_database_instance(db.clone(), rules.clone()).await?
+    ));
+
+    let chain = ProviderBuilder::new().on_http(config.base_rpc_url.parse()?);
+    if let Some(interval) = config.reconcile.interval_secs {
+        let name = format!("reconcile:{}", program.name);
+        reconcile::register(scheduler, name, interval, chain.clone(), program.contract_address, tracker.clone(), config.reconcile.auto_repair);
+    }
+    let bootstrap = ViewBootstrap::from_env(chain.clone(), program.contract_address).filter(|_| !replaying(config));
+    let trace = config.trace.as_ref().map(|trace| trace.for_program(&program.name));
+    let source = rpc_source(chain, program.contract_address, trace)?;
+    let monitor_tracker = tracker.clone();
+    let monitor_status = status.clone();
+    let whale_alerts = WhaleAlerts::new(config.whale_alerts, notifier.clone());
+    let name = program.name.clone();
+    tokio::spawn(async move {
+        if let Err(e) = run_monitoring(
+            source,
+            monitor_tracker,
+            monitor_status,
+            program.deployment_block,
+            None,
+            whale_alerts,
+            bootstrap,
+        ).await {
+            eprintln!("❌ Monitoring task error in program '{}': {}", name, e);
+        }
+    });
+
+    Ok(api::ProgramState { name: program.name, db: api_db, rules, tracker, status })
+}
+
+// Live logs from the RPC, with backfills from HyperSync when it is configured. When
+// recording or replaying a trace, only the RPC is used, so the trace covers every read
+fn rpc_source(chain: RootProvider<Http<Client>>, contract_address: Address, trace: Option<TraceMode>) -> Result<Box<dyn EventSource>> {
+    match trace {
+        Some(TraceMode::Replay(path)) => {
+            println!("📼 Replaying chain responses for {} from {}, without network access", contract_address, path);
+            return Ok(Box::new(RpcEventSource::new(ReplayChain::open(&path)?, contract_address)));
+        }
+        Some(TraceMode::Record(path)) => {
+            println!("🎙️  Recording chain responses for {} to {}", contract_address, path);
+            return Ok(Box::new(RpcEventSource::new(RecordingChain::create(chain, &path)?, contract_address)));
+        }
+        None => {}
+    }
+
+    let live = Box::new(RpcEventSource::new(chain, contract_address));
+    Ok(match HyperSyncSource::from_env(contract_address) {
+        Some(hypersync) => {
+            println!("🚀 Backfilling {} from HyperSync at {}", contract_address, hypersync.url());
+            Box::new(BackfillSource::new(Box::new(hypersync), live))
+        }
+        None => live,
+    })
+}
+
+// Fetch and apply all logs between `last_block` and `current_block` in batches,
+// returns the last block that was processed
+async fn sync_historical(
+    source: &dyn EventSource,
+    tracker: &mut PointsTracker,
+    mut last_block: u64,
+    current_block: u64,
+) -> Result<u64> {
+    let blocks_to_sync = current_block - last_block;
+    println!("⏳ Syncing {} blocks ({} → {})...", blocks_to_sync, last_block, current_block);
     
-    // Fetch historical events first
-    if last_block < current_block {
-        let blocks_to_sync = current_block - last_block;
-        println!("⏳ Syncing {} blocks ({} → {})...", blocks_to_sync, last_block, current_block);
+    let mut from_block = last_block;
+    let mut events_count = 0;
+    let mut blocks_processed = 0;
+    
+    while from_block < current_block {
+        // Calculate the range for this batch
+        let to_block = from_block.saturating_add(source.max_block_range()).min(current_block);
         
-        let mut from_block = last_block;
-        let mut events_count = 0;
-        let mut blocks_processed = 0;
+        // Show progress every 10 batches (5000 blocks)
+        if blocks_processed % 5000 == 0 {
+            println!("📊 Progress: Processed {} blocks, found {} events so far...", blocks_processed, events_count);
+        }
         
-        while from_block < current_block {
-            // Calculate the range for this batch
-            let to_block = (from_block + MAX_BLOCK_RANGE).min(current_block);
-            
-            // Show progress every 10 batches (5000 blocks)
-            if blocks_processed % 5000 == 0 {
-                println!("📊 Progress: Processed {} blocks, found {} events so far...", blocks_processed, events_count);
-            }
-            
-            // Create a filter for events in this range
-            let filter = Filter::new()
-                .address(contract_address)
-                .from_block(from_block)
-                .to_block(to_block);
-
-            // Get logs with retry on rate limit
-            let mut retry_count = 0;
-            loop {
-                match provider.get_logs(&filter).await {
-                    Ok(logs) => {
-                        if !logs.is_empty() {
-                            println!("   ✨ Found {} events in this range", logs.len());
-                        }
-                        events_count += logs.len();
-                        blocks_processed += to_block - from_block + 1;
-                        
-                        // Update tracker's current block
-                        tracker.current_block = to_block;
-                        
-                        for log in logs {
-                            handle_log(log, &mut tracker).await?;
-                        }
-                        
-                        // Update and save progress to database
-                        last_block = to_block;
-                        
-                        if let Some(db) = &tracker.db {
-                            if let Err(e) = db.update_last_processed_block(last_block).await {
-                                eprintln!("⚠️  Failed to update last block in database: {}", e);
-                            }
-                        }
-                        
-                        break; // Success, exit retry loop
+        // Get logs, retrying until the provider serves the range
+        let mut retry_count: u32 = 0;
+        loop {
+            match source.logs(from_block, to_block).await {
+                Ok(logs) => {
+                    if !logs.is_empty() {
+                        println!("   ✨ Found {} events in this range", logs.len());
                     }
-                    Err(e) => {
-                        if e.to_string().contains("rate limit") && retry_count < 3 {
-                            retry_count += 1;
-                            println!("⏳ Rate limited, waiting 2s and retrying... (attempt {}/3)", retry_count);
-                            sleep(Duration::from_secs(2)).await;
-                            continue; // Retry the same block range
-                        } else {
-                            eprintln!("❌ Error fetching logs for blocks {}-{}: {}", from_block, to_block, e);
-                            break; // Give up and move to next range
+                    events_count += logs.len();
+                    blocks_processed += to_block - from_block + 1;
+                    
+                    // Update tracker's current block
+                    tracker.current_block = to_block;
+                    
+                    for log in source::in_chain_order(logs) {
+                        handle_log(log, tracker).await?;
+                    }
+                    
+                    // Update and save progress to database
+                    last_block = to_block;
+                    
+                    if let Some(db) = &tracker.db {
+                        if let Err(e) = db.update_last_processed_block(last_block).await {
+                            eprintln!("⚠️  Failed to update last block in database: {}", e);
                         }
                     }
+                    
+                    break; // Success, exit retry loop
+                }
+                Err(e) => {
+                    // Never skip the range, its events would be lost for good. The
+                    // watchdog flags the indexer as stalled while this keeps failing
+                    retry_count += 1;
+                    let delay = 2u64.pow(retry_count.min(5));
+                    eprintln!("❌ Error fetching logs for blocks {}-{} (attempt {}), retrying in {}s: {}", from_block, to_block, retry_count, delay, e);
+                    sleep(Duration::from_secs(delay)).await;
                 }
-            }
-            
-            from_block = to_block + 1;
-            
-            // Small delay to avoid rate limiting
-            if from_block < current_block {
-                sleep(Duration::from_millis(100)).await;
             }
         }
         
-        println!("✅ Sync complete: {} blocks processed, {} events found", blocks_processed, events_count);
+        from_block = to_block + 1;
         
-        // Display points summary after historical sync
-        tracker.display_points_summary();
+        // Small delay to avoid rate limiting
+        if from_block < current_block {
+            sleep(Duration::from_millis(100)).await;
+        }
     }
+    
+    println!("✅ Sync complete: {} blocks processed, {} events found", blocks_processed, events_count);
+    Ok(last_block)
+}
+
+// Hash of a block, if the source can tell
+async fn block_hash(source: &dyn EventSource, number: u64) -> Option<B256> {
+    source.block_hash(number).await.ok().flatten()
+}
+
+// Extract monitoring logic into a separate function
+async fn run_monitoring(
+    source: Box<dyn EventSource>,
+    tracker: SharedTracker,
+    status: Arc<IndexerStatus>,
+    deployment_block: u64,
+    mut publisher: Option<RootPublisher>,
+    whale_alerts: Option<WhaleAlerts>,
+    bootstrap: Option<ViewBootstrap>,
+) -> Result<()> {
+    let db = tracker.read().await.db.clone();
 
+    // Get the current block number
+    let current_block = source.head().await?;
+
+    // Load the last processed block from database, if it's valid
+    let stored_block = match &db {
+        Some(db) => db.get_last_processed_block().await?.filter(|&b| b >= deployment_block),
+        None => None,
+    };
+    let mut last_block = stored_block.unwrap_or(deployment_block);
+
+    // On a cold start, read current positions from the contract instead of replaying
+    // every event since deployment; replay anyway if the contract can't be read
+    if let (None, Some(bootstrap)) = (stored_block, &bootstrap) {
+        let mut tracker = tracker.write().await;
+        match bootstrap.run(&mut tracker, current_block).await {
+            Ok(block) => {
+                last_block = block;
+                tracker.checkpoint_accrual(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
+            }
+            Err(e) => eprintln!("⚠️  View bootstrap failed, replaying events from block {}: {}", deployment_block, e),
+        }
+    }
+    
+    // Fetch historical events first, API reads fall back to the database meanwhile
+    {
+        let mut tracker = tracker.write().await;
+        if last_block < current_block {
+            last_block = sync_historical(source.as_ref(), &mut tracker, last_block, current_block).await?;
+            tracker.checkpoint_accrual(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
+            
+            // Display points summary after historical sync
+            tracker.display_points_summary();
+        }
+        tracker.whale_alerts = whale_alerts;
+    }
+    status.record(last_block, current_block);
+
+    let mut last_block_hash = block_hash(source.as_ref(), last_block).await;
     let mut last_points_update = SystemTime::now();
+    let mut last_root_publish = UNIX_EPOCH;
     
     // Continuous monitoring loop
     loop {
-        // Recalculate points every 60 seconds (since points accumulate over time)
+        // Recalculate points every 60 seconds (since points accumulate over time),
+        // checkpointing accrual first so this and later reads start from here
         if SystemTime::now().duration_since(last_points_update).unwrap().as_secs() >= 60 {
             println!("\n⏰ Periodic points update");
+            let tracker = tracker.read().await;
+            tracker.checkpoint_accrual(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
             tracker.display_points_summary();
             last_points_update = SystemTime::now();
         }
+
+        // Publish the points Merkle root on-chain
+        if let Some(publisher) = publisher.as_mut() {
+            if SystemTime::now().duration_since(last_root_publish).unwrap().as_secs() >= publisher.interval_secs() {
+                let (leaderboard, block) = {
+                    let tracker = tracker.read().await;
+                    (tracker.get_leaderboard(), tracker.current_block)
+                };
+                if let Err(e) = publisher.publish(&leaderboard, block).await {
+                    eprintln!("❌ Failed to publish points root: {}", e);
+                }
+                last_root_publish = SystemTime::now();
+            }
+        }
         
         // Get the current block
-        match provider.get_block_number().await {
+        match source.head().await {
             Ok(current_block) => {
                 // Update tracker's current block
-                tracker.current_block = current_block;
+                tracker.write().await.current_block = current_block;
                 
                 // If there are new blocks, fetch logs
                 if current_block > last_block {
+                    // Warn when the last processed block was replaced by a reorg
+                    let current_hash = block_hash(source.as_ref(), last_block).await;
+                    if let (Some(known), Some(current)) = (last_block_hash, current_hash) {
+                        if known != current {
+                            eprintln!("⚠️  Reorg detected at block {}: {} → {}", last_block, known, current);
+                        }
+                    }
+
                     // Silent check - only log if events are found
                     
-                    // Create a filter for events in the new blocks
-                    let filter = Filter::new()
-                        .address(contract_address)
-                        .from_block(last_block + 1)
-                        .to_block(current_block);
-
                     // Get logs
-                    match provider.get_logs(&filter).await {
+                    match source.logs(last_block + 1, current_block).await {
                         Ok(logs) => {
                             if !logs.is_empty() {
                                 println!("🔔 Found {} new events!", logs.len());
-                                for log in logs {
+                                let mut tracker = tracker.write().await;
+                                for log in source::in_chain_order(logs) {
                                     handle_log(log, &mut tracker).await?;
                                 }
                                 
@@ -518,9 +1001,10 @@ async fn run_monitoring(
                             
                             // Always update the last processed block
                             last_block = current_block;
+                            last_block_hash = block_hash(source.as_ref(), last_block).await;
                             
                             // Save to database
-                            if let Some(db) = &tracker.db {
+                            if let Some(db) = &db {
                                 if let Err(e) = db.update_last_processed_block(last_block).await {
                                     eprintln!("⚠️  Failed to update last block in database: {}", e);
                                 }
@@ -531,6 +1015,12 @@ async fn run_monitoring(
                         }
                     }
                 }
+                
+                status.record(last_block, current_block);
+
+                // Record snapshots even when no events arrive
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+                tracker.write().await.maybe_record_snapshots(now).await;
             }
             Err(e) => {
                 eprintln!("❌ Error getting current block: {}", e);
@@ -550,42 +1040,67 @@ async fn handle_log(log: Log, tracker: &mut PointsTracker) -> Result<()> {
     
     // Get the first topic (event signature)
     if let Some(_topic0) = log.topics().first() {
+        // Deposits from the lock-tier contract carry a lock duration, older ones have none
+        let deposit = match SageStakingV2::Deposit::decode_log(&log.inner, true) {
+            Ok(event) => Ok((
+                SageStaking::Deposit { user: event.user, amount: event.amount, nonce: event.nonce, timestamp: event.timestamp },
+                event.lockDuration.saturating_to::<u64>(),
+            )),
+            Err(_) => SageStaking::Deposit::decode_log(&log.inner, true).map(|event| (event.data, 0)),
+        };
+
         // Try to decode each event type
-        if let Ok(event) = SageStaking::Deposit::decode_log(&log.inner, true) {
+        if let Ok((event, lock_duration)) = deposit {
             println!("\n📥 DEPOSIT EVENT [Block: {}]", block_num);
             println!("   User: {}", format_address(event.user));
             println!("   Amount: {} tokens", format_token_amount(event.amount));
             println!("   Nonce: {}", event.nonce);
+            if lock_duration > 0 {
+                println!("   Lock: {} days", lock_duration / 86400);
+            }
             println!("   Timestamp: {}", format_timestamp(event.timestamp));
             println!("   Tx Hash: {}", log.transaction_hash.unwrap_or_default());
             
             // Track the position as active
             let position = Position {
                 user: event.user,
-                nonce: event.nonce.to::<u64>(),
+                nonce: event.nonce,
                 amount: event.amount,
                 deposit_timestamp: event.timestamp.to::<u64>(),
                 status: PositionStatus::Active,
                 withdrawal_initiated_timestamp: None,
+                unlocks_at: None,
                 block_number: block_num,
+                lock_duration,
             };
             
             // Add to active positions
-            tracker.add_active_position((event.user, event.nonce.to::<u64>()), position).await;
+            tracker.add_active_position((event.user, event.nonce), position).await;
             
             // Save event to database
             if let Some(db) = &tracker.db {
                 if let Err(e) = db.save_event(EventData {
                     event_type: "Deposit".to_string(),
                     user: event.user,
-                    nonce: Some(event.nonce.to::<u64>()),
+                    nonce: Some(event.nonce),
                     amount: Some(event.amount),
+                    unlocks_at: None,
+                    lock_duration: Some(lock_duration),
                     block_number: block_num,
+                    log_index: log.log_index,
                     tx_hash: log.transaction_hash.unwrap_or_default().to_string(),
                     timestamp: event.timestamp.to::<u64>(),
                 }).await {
                     eprintln!("⚠️  Failed to save deposit event: {}", e);
                 }
+                if let Err(e) = db.record_staker(&event.user, event.timestamp.to::<u64>(), block_num).await {
+                    eprintln!("⚠️  Failed to record staker: {}", e);
+                }
+            }
+            tracker.maybe_record_snapshots(event.timestamp.to::<u64>()).await;
+            if let Some(alerts) = &tracker.whale_alerts {
+                let tx_hash = log.transaction_hash.unwrap_or_default().to_string();
+                alerts.check(tracker.db.clone(), Flow::Deposit, event.user, event.amount, tx_hash);
             }
             
             let user_points = tracker.calculate_user_points(&event.user);
@@ -604,31 +1119,40 @@ async fn handle_log(log: Log, tracker: &mut PointsTracker) -> Result<()> {
             println!("   Tx Hash: {}", log.transaction_hash.unwrap_or_default());
             
             // Move position from active to unstaking
-            let key = (event.user, event.nonce.to::<u64>());
+            let key = (event.user, event.nonce);
             if let Some(position) = tracker.get_position(&key) {
                 let position_points = tracker.calculate_position_points(position);
                 println!("   📊 Position Points Earned: SAGE={:.4}, FORM={:.4}", 
                     position_points.sage_points, position_points.formation_points);
                 println!("   ⚠️  Points accumulation STOPPED for this position");
+
+                if let Some(alerts) = &tracker.whale_alerts {
+                    let tx_hash = log.transaction_hash.unwrap_or_default().to_string();
+                    alerts.check(tracker.db.clone(), Flow::Unstake, event.user, position.amount, tx_hash);
+                }
             }
             
             // Move to unstaking state
-            tracker.move_to_unstaking(key, event.timestamp.to::<u64>()).await;
+            tracker.move_to_unstaking(key, event.timestamp.to::<u64>(), event.unlocksAt.to::<u64>()).await;
             
             // Save event to database
             if let Some(db) = &tracker.db {
                 if let Err(e) = db.save_event(EventData {
                     event_type: "InitiateWithdraw".to_string(),
                     user: event.user,
-                    nonce: Some(event.nonce.to::<u64>()),
+                    nonce: Some(event.nonce),
                     amount: None,  // No amount in this event
+                    unlocks_at: Some(event.unlocksAt.to::<u64>()),
+                    lock_duration: None,
                     block_number: block_num,
+                    log_index: log.log_index,
                     tx_hash: log.transaction_hash.unwrap_or_default().to_string(),
                     timestamp: event.timestamp.to::<u64>(),
                 }).await {
                     eprintln!("⚠️  Failed to save initiate withdraw event: {}", e);
                 }
             }
+            tracker.maybe_record_snapshots(event.timestamp.to::<u64>()).await;
             
             let user_points = tracker.calculate_user_points(&event.user);
             let (active, unstaking, withdrawn) = tracker.get_user_deposits_summary(&event.user);
@@ -646,7 +1170,7 @@ async fn handle_log(log: Log, tracker: &mut PointsTracker) -> Result<()> {
             println!("   Tx Hash: {}", log.transaction_hash.unwrap_or_default());
             
             // Move position from unstaking to withdrawn
-            let key = (event.user, event.nonce.to::<u64>());
+            let key = (event.user, event.nonce);
             if let Some(position) = tracker.get_position(&key) {
                 let position_points = tracker.calculate_position_points(position);
                 println!("   📊 Final Position Points: SAGE={:.4}, FORM={:.4}", 
@@ -661,15 +1185,19 @@ async fn handle_log(log: Log, tracker: &mut PointsTracker) -> Result<()> {
                 if let Err(e) = db.save_event(EventData {
                     event_type: "Withdraw".to_string(),
                     user: event.user,
-                    nonce: Some(event.nonce.to::<u64>()),
+                    nonce: Some(event.nonce),
                     amount: Some(event.amount),
+                    unlocks_at: None,
+                    lock_duration: None,
                     block_number: block_num,
+                    log_index: log.log_index,
                     tx_hash: log.transaction_hash.unwrap_or_default().to_string(),
                     timestamp: event.timestamp.to::<u64>(),
                 }).await {
                     eprintln!("⚠️  Failed to save withdraw event: {}", e);
                 }
             }
+            tracker.maybe_record_snapshots(event.timestamp.to::<u64>()).await;
             
             let user_points = tracker.calculate_user_points(&event.user);
             let (active, unstaking, withdrawn) = tracker.get_user_deposits_summary(&event.user);
@@ -687,7 +1215,7 @@ async fn handle_log(log: Log, tracker: &mut PointsTracker) -> Result<()> {
             println!("   Tx Hash: {}", log.transaction_hash.unwrap_or_default());
             
             // Move position from unstaking back to active
-            let key = (event.user, event.nonce.to::<u64>());
+            let key = (event.user, event.nonce);
             tracker.move_to_active(key, event.timestamp.to::<u64>()).await;
             println!("   ✅ Points accumulation RESUMED for this position");
             
@@ -696,15 +1224,19 @@ async fn handle_log(log: Log, tracker: &mut PointsTracker) -> Result<()> {
                 if let Err(e) = db.save_event(EventData {
                     event_type: "RestakeFromWithdrawalInitiated".to_string(),
                     user: event.user,
-                    nonce: Some(event.nonce.to::<u64>()),
+                    nonce: Some(event.nonce),
                     amount: Some(event.amount),
+                    unlocks_at: None,
+                    lock_duration: None,
                     block_number: block_num,
+                    log_index: log.log_index,
                     tx_hash: log.transaction_hash.unwrap_or_default().to_string(),
                     timestamp: event.timestamp.to::<u64>(),
                 }).await {
                     eprintln!("⚠️  Failed to save restake event: {}", e);
                 }
             }
+            tracker.maybe_record_snapshots(event.timestamp.to::<u64>()).await;
             
             let user_points = tracker.calculate_user_points(&event.user);
             let (active, unstaking, withdrawn) = tracker.get_user_deposits_summary(&event.user);
@@ -712,6 +1244,42 @@ async fn handle_log(log: Log, tracker: &mut PointsTracker) -> Result<()> {
                 user_points.sage_points, user_points.formation_points);
             println!("   💰 User Deposits: Active={:.2}, Unstaking={:.2}, Withdrawn={:.2}", 
                 active, unstaking, withdrawn);
+            
+        } else if let Ok(event) = SageStakingV2::Delegate::decode_log(&log.inner, true) {
+            // Delegate has no timestamp field; use the block's when the provider includes it
+            let timestamp = log.block_timestamp.unwrap_or(tracker.last_event_timestamp);
+            println!("\n🤝 DELEGATE EVENT [Block: {}]", block_num);
+            println!("   User: {}", format_address(event.user));
+            if event.delegatee.is_zero() {
+                println!("   Delegatee: none (revoked)");
+            } else {
+                println!("   Delegatee: {}", format_address(event.delegatee));
+            }
+            println!("   Timestamp: {}", format_timestamp(U256::from(timestamp)));
+            println!("   Tx Hash: {}", log.transaction_hash.unwrap_or_default());
+
+            let delegation = Delegation {
+                delegator: event.user,
+                delegatee: event.delegatee,
+                block_number: block_num,
+                tx_hash: log.transaction_hash.unwrap_or_default().to_string(),
+                timestamp,
+            };
+
+            // Save to database, so settings reloads keep it
+            if let Some(db) = &tracker.db {
+                if let Err(e) = db.save_delegation(&delegation).await {
+                    eprintln!("⚠️  Failed to save delegation: {}", e);
+                }
+            }
+
+            // Apply right away
+            let mut rules = tracker.rules.write().unwrap();
+            let known = rules.delegations.iter()
+                .any(|d| d.tx_hash == delegation.tx_hash && d.delegator == delegation.delegator);
+            if !known {
+                rules.delegations.push(delegation);
+            }
         }
         
         println!("{}", "=".repeat(100));
@@ -782,4 +1350,108 @@ fn format_token_amount_as_float(amount: U256) -> f64 {
     } else {
         0.0
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chain::testing::{deposit, initiate_withdraw, tokens, ScriptedChain, CONTRACT};
+    use points::{DEFAULT_FORMATION_RATE, DEFAULT_SAGE_RATE};
+
+    const ALICE: Address = Address::repeat_byte(0xa1);
+    const BOB: Address = Address::repeat_byte(0xb0);
+    const DAY: u64 = 86400;
+    const START: u64 = 1_700_000_000;
+
+    fn source(chain: &ScriptedChain) -> RpcEventSource<ScriptedChain> {
+        RpcEventSource::new(chain.clone(), CONTRACT)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sync_applies_scripted_logs() {
+        let chain = ScriptedChain::new(1_200, vec![
+            deposit(ALICE, 1, tokens(100), START, 10, 0),
+            deposit(BOB, 1, tokens(50), START, 10, 1),
+            initiate_withdraw(ALICE, 1, START + 8 * DAY, START + DAY, 900, 0),
+        ]);
+        let mut tracker = PointsTracker::in_memory(PointsRules::default());
+
+        let last_block = sync_historical(&source(&chain), &mut tracker, 0, 1_200).await.unwrap();
+
+        assert_eq!(last_block, 1_200);
+        assert_eq!(tracker.active_positions.len(), 1);
+        let unstaking = &tracker.unstaking_positions[&(ALICE, U256::from(1))];
+        assert_eq!(unstaking.withdrawal_initiated_timestamp, Some(START + DAY));
+        assert_eq!(unstaking.unlocks_at, Some(START + 8 * DAY));
+        // Alice earned one day on 100 tokens
+        let points = tracker.calculate_user_points(&ALICE);
+        assert!((points.sage_points - 100.0 * DEFAULT_SAGE_RATE).abs() < 1e-9);
+        // Batches cover every block once
+        assert_eq!(chain.log_requests(), vec![(0, 500), (501, 1_001), (1_002, 1_200)]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sync_retries_failed_ranges() {
+        let chain = ScriptedChain::new(100, vec![deposit(ALICE, 1, tokens(10), START, 40, 0)]);
+        chain.fail_logs("connection reset by peer");
+        let mut tracker = PointsTracker::in_memory(PointsRules::default());
+
+        let last_block = sync_historical(&source(&chain), &mut tracker, 0, 100).await.unwrap();
+
+        // The failed range is fetched again instead of skipped
+        assert_eq!(last_block, 100);
+        assert_eq!(chain.log_requests(), vec![(0, 100), (0, 100)]);
+        assert!(tracker.active_positions.contains_key(&(ALICE, U256::from(1))));
+    }
+
+    #[tokio::test]
+    async fn recorded_logs_fixture_yields_positions_and_points() {
+        let source = FileEventSource::open(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/logs.json")).unwrap();
+        let mut tracker = PointsTracker::in_memory(PointsRules::default());
+
+        let head = source.head().await.unwrap();
+        sync_historical(&source, &mut tracker, 0, head).await.unwrap();
+
+        let position = |user, nonce: u64| tracker.get_position(&(user, U256::from(nonce))).unwrap();
+        assert_eq!(position(ALICE, 1).status, PositionStatus::Withdrawn);
+        assert_eq!(position(ALICE, 2).status, PositionStatus::Active);
+        // Bob's restake comes after his InitiateWithdraw in the block, though listed first
+        assert_eq!(position(BOB, 1).status, PositionStatus::Active);
+        assert_eq!(position(BOB, 1).deposit_timestamp, START + 3 * DAY);
+
+        let totals = tracker.rules.read().unwrap().owner_totals(tracker.positions(), 0, START + 10 * DAY);
+        // Alice: 100 tokens for 2 days, then 10 tokens for 9 days
+        assert!((totals[&ALICE].points.sage_points - (200.0 + 90.0) * DEFAULT_SAGE_RATE).abs() < 1e-9);
+        assert!((totals[&ALICE].points.formation_points - (200.0 + 90.0) * DEFAULT_FORMATION_RATE).abs() < 1e-9);
+        // Bob: 40 tokens for the 7 days since the restake
+        assert!((totals[&BOB].points.sage_points - 280.0 * DEFAULT_SAGE_RATE).abs() < 1e-9);
+        assert_eq!(totals[&BOB].active_amount, tokens(40));
+    }
+
+    // Reorgs are only detected and warned about, repairing them takes `rollback`
+    #[tokio::test(start_paused = true)]
+    async fn monitoring_keeps_indexed_logs_of_reorged_blocks() {
+        let chain = ScriptedChain::new(10, vec![deposit(ALICE, 1, tokens(10), START, 10, 0)]);
+        chain.set_hash(10, B256::repeat_byte(1));
+        let tracker: SharedTracker = Arc::new(tokio::sync::RwLock::new(PointsTracker::in_memory(PointsRules::default())));
+        let status = IndexerStatus::new();
+        let monitoring = tokio::spawn(run_monitoring(Box::new(source(&chain)), tracker.clone(), status.clone(), 0, None, None, None));
+
+        sleep(Duration::from_secs(10)).await;
+        assert_eq!(status.health().last_processed_block, 10);
+        assert!(tracker.read().await.active_positions.contains_key(&(ALICE, U256::from(1))));
+
+        // Block 10 is replaced without Alice's deposit, and Bob deposits after it
+        chain.reorg(10, B256::repeat_byte(2), vec![deposit(BOB, 1, tokens(30), START, 12, 0)]);
+        chain.set_head(20);
+        sleep(Duration::from_secs(10)).await;
+        monitoring.abort();
+
+        // Indexing goes on from the cursor, the deposit from the replaced block stays
+        assert_eq!(status.health().last_processed_block, 20);
+        assert_eq!(chain.log_requests().last(), Some(&(11, 20)));
+        let tracker = tracker.read().await;
+        assert!(tracker.active_positions.contains_key(&(BOB, U256::from(1))));
+        assert!(tracker.active_positions.contains_key(&(ALICE, U256::from(1))));
+    }
+}
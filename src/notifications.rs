@@ -0,0 +1,395 @@
+use alloy::primitives::Address;
+use eyre::{eyre, Result};
+use futures::StreamExt;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+
+use crate::db::Database;
+use crate::points::SharedRules;
+use crate::{PositionStatus, SharedTracker};
+
+// Seconds between dispatcher runs
+const DISPATCH_INTERVAL_SECS: u64 = 60;
+
+// Bounds on a single delivery, so one slow channel can't hold up a dispatch run
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(15);
+const CONCURRENT_DELIVERIES: usize = 16;
+
+/// Something a user can be notified about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    CooldownComplete, // An unstaking position can be withdrawn
+    RankChange,       // The daily rank snapshot moved the user
+    NewSeason,        // A configured season started
+}
+
+impl NotificationEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::CooldownComplete => "cooldown_complete",
+            Self::RankChange => "rank_change",
+            Self::NewSeason => "new_season",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "cooldown_complete" => Some(Self::CooldownComplete),
+            "rank_change" => Some(Self::RankChange),
+            "new_season" => Some(Self::NewSeason),
+            _ => None,
+        }
+    }
+}
+
+/// Where and about what a user wants to be notified
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    #[serde(skip_deserializing)]
+    pub address: Address, // Taken from the session, never from the request body
+    pub webhook_url: Option<String>,
+    pub discord_user_id: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub events: Vec<NotificationEvent>,
+}
+
+impl NotificationPreferences {
+    /// Reject malformed channels, so the dispatcher never sends to them
+    pub fn validate(&self) -> Result<()> {
+        if let Some(url) = &self.webhook_url {
+            let parsed = reqwest::Url::parse(url).ok().filter(|u| u.scheme() == "https" && u.has_host() && url.len() <= 2048);
+            let Some(parsed) = parsed else {
+                return Err(eyre!("webhook_url must be an https URL"));
+            };
+            if !is_public_host(&parsed) {
+                return Err(eyre!("webhook_url must point to a public host"));
+            }
+        }
+        if let Some(id) = &self.discord_user_id {
+            if id.is_empty() || id.len() > 20 || !id.chars().all(|c| c.is_ascii_digit()) {
+                return Err(eyre!("discord_user_id must be a numeric Discord user id"));
+            }
+        }
+        if let Some(email) = &self.email {
+            let valid = email.len() <= 254
+                && email.split_once('@').is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'));
+            if !valid {
+                return Err(eyre!("email is not a valid address"));
+            }
+        }
+        Ok(())
+    }
+
+    fn wants(&self, event: NotificationEvent) -> bool {
+        self.events.contains(&event)
+    }
+}
+
+// Whether a webhook may be sent to this URL host. Names are checked again once resolved.
+fn is_public_host(url: &reqwest::Url) -> bool {
+    let Some(host) = url.host_str() else { return false };
+    // IPv6 hosts come in brackets
+    match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => is_public_ip(ip),
+        Err(_) => {
+            let name = host.trim_end_matches('.').to_ascii_lowercase();
+            name != "localhost" && !name.ends_with(".localhost") && name.contains('.')
+        }
+    }
+}
+
+// False for loopback, private, link-local and other addresses that aren't reachable
+// from the internet, so user webhooks can't reach the service's own network
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b)) // Carrier-grade NAT
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public_ip(IpAddr::V4(v4)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || (first & 0xfe00) == 0xfc00  // Unique local
+                    || (first & 0xffc0) == 0xfe80) // Link-local
+            }
+        },
+    }
+}
+
+// Resolver for webhook requests: fails when a name resolves to any non-public
+// address, so a user's DNS record can't point the dispatcher at internal services
+struct PublicOnlyResolver;
+
+impl Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            if let Some(addr) = addrs.iter().find(|a| !is_public_ip(a.ip())) {
+                return Err(format!("{} resolves to non-public address {}", name.as_str(), addr.ip()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Delivers user notifications over the channels each user registered. Discord DMs
+/// need a bot token and email goes through an external sender.
+#[derive(Clone)]
+pub struct UserNotifier {
+    client: reqwest::Client,         // Discord and the email sender, which the operator configured
+    webhook_client: reqwest::Client, // User-supplied URLs, public addresses only
+    discord_bot_token: Option<String>,
+    email_sender_url: Option<String>, // Receives `{to, subject, text}`
+}
+
+impl UserNotifier {
+    /// Read delivery settings from the environment
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+        // Redirects are not followed: a public webhook could otherwise bounce to an internal one
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+        let webhook_client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(PublicOnlyResolver))
+            .no_proxy()
+            .build()?;
+
+        Ok(Self {
+            client,
+            webhook_client,
+            discord_bot_token: var("DISCORD_BOT_TOKEN"),
+            email_sender_url: var("EMAIL_SENDER_URL"),
+        })
+    }
+
+    /// Send `message` to every channel of `preferences`. Fails if any channel failed.
+    pub async fn deliver(
+        &self,
+        preferences: &NotificationPreferences,
+        event: NotificationEvent,
+        message: &str,
+        details: serde_json::Value,
+    ) -> Result<()> {
+        let mut errors = Vec::new();
+
+        if let Some(url) = &preferences.webhook_url {
+            let body = json!({
+                "event": event.as_str(),
+                "address": preferences.address.to_string(),
+                "text": message,
+                "details": details,
+            });
+            if let Err(e) = self.post_webhook(url, &body).await {
+                errors.push(format!("webhook: {}", e));
+            }
+        }
+
+        if let (Some(user_id), Some(token)) = (&preferences.discord_user_id, &self.discord_bot_token) {
+            if let Err(e) = self.send_discord_dm(token, user_id, message).await {
+                errors.push(format!("discord: {}", e));
+            }
+        }
+
+        if let (Some(email), Some(url)) = (&preferences.email, &self.email_sender_url) {
+            let body = json!({ "to": email, "subject": format!("Points: {}", event.as_str().replace('_', " ")), "text": message });
+            if let Err(e) = self.post(url, &body, None).await {
+                errors.push(format!("email: {}", e));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!(errors.join(", ")))
+        }
+    }
+
+    // Open (or reuse) the bot's DM channel with the user and post into it
+    async fn send_discord_dm(&self, token: &str, user_id: &str, message: &str) -> Result<()> {
+        let auth = format!("Bot {}", token);
+        let channel: serde_json::Value = self.client
+            .post("https://discord.com/api/v10/users/@me/channels")
+            .header("Authorization", &auth)
+            .json(&json!({ "recipient_id": user_id }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let channel_id = channel["id"].as_str().ok_or_else(|| eyre!("no DM channel id in Discord response"))?;
+
+        let url = format!("https://discord.com/api/v10/channels/{}/messages", channel_id);
+        self.post(&url, &json!({ "content": message }), Some(&auth)).await?;
+        Ok(())
+    }
+
+    // Preferences saved before hosts were checked may still name an internal address
+    async fn post_webhook(&self, url: &str, body: &serde_json::Value) -> Result<()> {
+        if !is_public_host(&reqwest::Url::parse(url)?) {
+            return Err(eyre!("{} is not a public host", url));
+        }
+        self.webhook_client.post(url).json(body).send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    async fn post(&self, url: &str, body: &serde_json::Value, authorization: Option<&str>) -> reqwest::Result<()> {
+        let mut request = self.client.post(url).json(body);
+        if let Some(authorization) = authorization {
+            request = request.header("Authorization", authorization);
+        }
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Check for notifiable events every minute and notify subscribed users. Only
+/// events after the dispatcher started are sent, so restarts don't replay old ones.
+pub fn spawn(db: Database, rules: SharedRules, tracker: SharedTracker, notifier: UserNotifier) {
+    tokio::spawn(async move {
+        let mut last_check = chrono::Utc::now().timestamp() as u64;
+        loop {
+            sleep(Duration::from_secs(DISPATCH_INTERVAL_SECS)).await;
+
+            let now = chrono::Utc::now().timestamp() as u64;
+            if let Err(e) = dispatch(&db, &rules, &tracker, &notifier, last_check, now).await {
+                eprintln!("⚠️  Failed to dispatch user notifications: {}", e);
+                continue;
+            }
+            last_check = now;
+        }
+    });
+}
+
+// Notify about everything that happened in `(since, now]`
+async fn dispatch(
+    db: &Database,
+    rules: &SharedRules,
+    tracker: &SharedTracker,
+    notifier: &UserNotifier,
+    since: u64,
+    now: u64,
+) -> Result<()> {
+    let subscribers = db.load_notification_preferences().await?;
+    if subscribers.is_empty() {
+        return Ok(());
+    }
+
+    let mut outgoing: Vec<(&NotificationPreferences, NotificationEvent, String, serde_json::Value)> = Vec::new();
+
+    // Cooldowns that ended, for the address holding the position
+    let cooled_down: Vec<(Address, String, u64)> = {
+        let tracker = tracker.read().await;
+        tracker.positions()
+            .filter(|p| matches!(p.status, PositionStatus::Unstaking))
+            .filter_map(|p| p.unlocks_at.filter(|&t| t > since && t <= now).map(|t| (p.user, p.nonce.to_string(), t)))
+            .collect()
+    };
+    for (user, nonce, unlocks_at) in cooled_down {
+        if let Some(preferences) = subscribers.iter().find(|s| s.address == user && s.wants(NotificationEvent::CooldownComplete)) {
+            let message = format!("⏳ Cooldown complete: position #{} can now be withdrawn", nonce);
+            outgoing.push((preferences, NotificationEvent::CooldownComplete, message, json!({ "nonce": nonce, "unlocks_at": unlocks_at })));
+        }
+    }
+
+    // Rank moves at snapshots taken since the last run
+    let ranked = db.get_last_rank_timestamp().await?.is_some_and(|t| t > since);
+    for preferences in subscribers.iter().filter(|s| ranked && s.wants(NotificationEvent::RankChange)) {
+        let history = db.load_rank_history(&preferences.address.to_string(), now.saturating_sub(8 * 86400)).await?;
+        let [.., previous, latest] = history.as_slice() else { continue };
+        if latest.timestamp.timestamp() as u64 > since && latest.rank != previous.rank {
+            let message = format!("📈 Your rank moved from #{} to #{}", previous.rank, latest.rank);
+            outgoing.push((preferences, NotificationEvent::RankChange, message, json!({ "previous_rank": previous.rank, "rank": latest.rank })));
+        }
+    }
+
+    // Seasons that started
+    let started: Vec<String> = rules.read().unwrap().seasons.iter()
+        .filter(|s| s.start > since && s.start <= now)
+        .map(|s| s.name.clone())
+        .collect();
+    for season in started {
+        for preferences in subscribers.iter().filter(|s| s.wants(NotificationEvent::NewSeason)) {
+            let message = format!("🌱 {} has started", season);
+            outgoing.push((preferences, NotificationEvent::NewSeason, message, json!({ "season": season })));
+        }
+    }
+
+    let deliveries: Vec<_> = outgoing.into_iter()
+        .map(|(preferences, event, message, details)| async move {
+            let result = match timeout(DELIVERY_TIMEOUT, notifier.deliver(preferences, event, &message, details)).await {
+                Ok(result) => result,
+                Err(_) => Err(eyre!("timed out after {}s", DELIVERY_TIMEOUT.as_secs())),
+            };
+            (preferences.address, result)
+        })
+        .collect();
+    let results: Vec<(Address, Result<()>)> = futures::stream::iter(deliveries)
+        .buffer_unordered(CONCURRENT_DELIVERIES)
+        .collect()
+        .await;
+
+    let mut failed: HashSet<Address> = HashSet::new();
+    for (address, result) in results {
+        if let Err(e) = result {
+            if failed.insert(address) {
+                eprintln!("⚠️  Failed to notify {}: {}", address, e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn webhook(url: &str) -> NotificationPreferences {
+        NotificationPreferences { address: Address::ZERO, webhook_url: Some(url.into()), discord_user_id: None, email: None, events: vec![] }
+    }
+
+    #[test]
+    fn webhooks_must_point_to_public_hosts() {
+        assert!(webhook("https://hooks.example.com/points").validate().is_ok());
+        assert!(webhook("https://8.8.8.8/hook").validate().is_ok());
+        for url in [
+            "http://hooks.example.com/points",
+            "https://localhost/hook",
+            "https://127.0.0.1:8080/hook",
+            "https://10.0.0.5/hook",
+            "https://169.254.169.254/latest/meta-data",
+            "https://[::1]/hook",
+            "https://[fe80::1]/hook",
+            "https://[::ffff:192.168.1.1]/hook",
+            "https://internal/hook",
+        ] {
+            assert!(webhook(url).validate().is_err(), "{} was accepted", url);
+        }
+    }
+}
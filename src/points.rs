@@ -0,0 +1,925 @@
+use alloy::primitives::{keccak256, Address, U256};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use crate::{format_token_amount_as_float, Position, PositionStatus};
+
+// Default emission rates (points per token per day)
+pub const DEFAULT_SAGE_RATE: f64 = 0.01;
+pub const DEFAULT_FORMATION_RATE: f64 = 0.005;
+
+const DAY: u64 = 86400;
+
+/// Rules shared between the indexer and the API, replaced on reload
+pub type SharedRules = Arc<RwLock<PointsRules>>;
+
+// Points breakdown
+#[derive(Debug, Clone, Default)]
+pub struct PointsBreakdown {
+    pub sage_points: f64,
+    pub formation_points: f64,
+}
+
+impl PointsBreakdown {
+    pub fn total(&self) -> f64 {
+        self.sage_points + self.formation_points
+    }
+
+    // Scale both point types down proportionally to at most `cap` in total
+    fn capped(self, cap: f64) -> Self {
+        let total = self.total();
+        if total <= cap {
+            return self;
+        }
+        self.scaled(cap / total)
+    }
+
+    fn scaled(self, factor: f64) -> Self {
+        Self {
+            sage_points: self.sage_points * factor,
+            formation_points: self.formation_points * factor,
+        }
+    }
+}
+
+impl std::ops::AddAssign for PointsBreakdown {
+    fn add_assign(&mut self, other: Self) {
+        self.sage_points += other.sage_points;
+        self.formation_points += other.formation_points;
+    }
+}
+
+/// Points and active stake credited to one points owner
+#[derive(Debug, Clone, Default)]
+pub struct OwnerTotals {
+    pub points: PointsBreakdown,
+    pub active_amount: U256, // Wei in active positions
+}
+
+/// Points emitted over an interval, by point type and by what produced them
+#[derive(Debug, Clone, Default)]
+pub struct Emissions {
+    pub points: PointsBreakdown, // What owners were credited, after daily caps
+    pub base: f64,               // At the plain rate
+    pub boosts: f64,             // Extra from boost windows
+    pub lock_tiers: f64,         // Extra from lock-tier multipliers
+    pub daily_caps: f64,         // Removed by daily caps, zero or negative
+}
+
+/// Stretch of an accrual interval with constant rates and multiplier
+#[derive(Debug, Clone, Serialize)]
+pub struct AccrualSegment {
+    pub start: u64,
+    pub end: u64,
+    pub sage_rate: f64,
+    pub formation_rate: f64,
+    pub multiplier: f64, // Zero while paused
+    pub sage_points: f64,
+    pub formation_points: f64,
+}
+
+/// Emission rates in effect from a given timestamp onwards
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RatePeriod {
+    pub effective_from: u64,
+    pub sage_rate: f64,      // SAGE points per token per day
+    pub formation_rate: f64, // Formation points per token per day
+}
+
+/// Time window during which accrual is multiplied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoostWindow {
+    pub start: u64,
+    pub end: u64,
+    pub multiplier: f64,
+}
+
+/// Accrual multiplier for positions locked for at least `min_duration` seconds at deposit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockTier {
+    pub min_duration: u64,
+    pub multiplier: f64,
+}
+
+/// Limit on the points one owner earns per UTC day, for the days inside the window
+/// (typically one season)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyCap {
+    pub start: u64,
+    pub end: u64,
+    pub max_points: f64, // SAGE + Formation, scaled down proportionally
+}
+
+/// Named stretch of the program, e.g. "Season 1"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Season {
+    pub name: String,
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Points balance a token allocation is shared out by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AllocationBasis {
+    #[default]
+    Total,
+    Sage,
+    Formation,
+}
+
+/// Pro-rata token allocation over points, for previews until the formula is final
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllocationConfig {
+    pub total_tokens: f64,
+    #[serde(default)]
+    pub basis: AllocationBasis,
+    #[serde(default)]
+    pub min_points: f64,            // Owners below this are left out of the pool
+    pub max_share: Option<f64>,     // Fraction of `total_tokens` one owner can receive, excess is not redistributed
+}
+
+impl AllocationConfig {
+    /// The points of `breakdown` that count towards the allocation
+    pub fn points(&self, breakdown: &PointsBreakdown) -> f64 {
+        match self.basis {
+            AllocationBasis::Total => breakdown.total(),
+            AllocationBasis::Sage => breakdown.sage_points,
+            AllocationBasis::Formation => breakdown.formation_points,
+        }
+    }
+}
+
+/// Leaderboard that restarts every period, for recurring community competitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompetitionBoard {
+    Weekly,
+    Monthly,
+}
+
+impl CompetitionBoard {
+    pub const ALL: [Self; 2] = [Self::Weekly, Self::Monthly];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "weekly" => Some(Self::Weekly),
+            "monthly" => Some(Self::Monthly),
+            _ => None,
+        }
+    }
+}
+
+fn default_competition_winners() -> usize {
+    10
+}
+
+/// Weekly and monthly competitions, rotating on their own. Week 1 starts at `start`;
+/// months are UTC calendar months, month 1 being the one `start` falls in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompetitionConfig {
+    pub start: u64,
+    #[serde(default = "default_competition_winners")]
+    pub winners: usize, // Places shown and archived per period
+}
+
+// Months since year 0 of the UTC month `timestamp` falls in
+fn month_index(timestamp: u64) -> i64 {
+    let date = DateTime::from_timestamp(timestamp as i64, 0).unwrap_or_default();
+    date.year() as i64 * 12 + date.month0() as i64
+}
+
+// Timestamp of the first second of a month from `month_index`
+fn month_start(index: i64) -> u64 {
+    Utc.with_ymd_and_hms((index / 12) as i32, (index % 12) as u32 + 1, 1, 0, 0, 0)
+        .single()
+        .map_or(0, |t| t.timestamp().max(0) as u64)
+}
+
+impl CompetitionConfig {
+    /// Bounds `[start, end)` of the 1-based `period` of `board`
+    pub fn period_bounds(&self, board: CompetitionBoard, period: u64) -> Option<(u64, u64)> {
+        let offset = period.checked_sub(1)?;
+        match board {
+            CompetitionBoard::Weekly => {
+                let start = self.start.checked_add(offset.checked_mul(7 * DAY)?)?;
+                Some((start, start.checked_add(7 * DAY)?))
+            }
+            CompetitionBoard::Monthly => {
+                let index = month_index(self.start).checked_add(i64::try_from(offset).ok()?)?;
+                if index >= 12 * 10_000 {
+                    return None;
+                }
+                Some((month_start(index).max(self.start), month_start(index + 1)))
+            }
+        }
+    }
+
+    /// Period of `board` running at `timestamp`, None before the first one starts
+    pub fn period_at(&self, board: CompetitionBoard, timestamp: u64) -> Option<u64> {
+        if timestamp < self.start {
+            return None;
+        }
+        Some(match board {
+            CompetitionBoard::Weekly => (timestamp - self.start) / (7 * DAY) + 1,
+            CompetitionBoard::Monthly => (month_index(timestamp) - month_index(self.start)) as u64 + 1,
+        })
+    }
+}
+
+/// How a scaled points value is turned into an integer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rounding {
+    Floor,
+    Ceil,
+    #[default]
+    Round, // Half away from zero
+}
+
+fn default_display_divisor() -> f64 {
+    1.0
+}
+
+/// Divisor and rounding of one point type's display values
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DisplayScale {
+    #[serde(default = "default_display_divisor")]
+    pub divisor: f64,
+    #[serde(default)]
+    pub rounding: Rounding,
+}
+
+impl Default for DisplayScale {
+    fn default() -> Self {
+        Self { divisor: default_display_divisor(), rounding: Rounding::default() }
+    }
+}
+
+impl DisplayScale {
+    pub fn apply(&self, points: f64) -> i64 {
+        let scaled = points / self.divisor;
+        let rounded = match self.rounding {
+            Rounding::Floor => scaled.floor(),
+            Rounding::Ceil => scaled.ceil(),
+            Rounding::Round => scaled.round(),
+        };
+        rounded as i64
+    }
+}
+
+/// Integer points for display, served next to the exact values. Only applied when
+/// responses are serialized; stored and computed points are never rounded.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DisplayPoints {
+    #[serde(default)]
+    pub sage: DisplayScale,
+    #[serde(default)]
+    pub formation: DisplayScale,
+    #[serde(default)]
+    pub total: DisplayScale, // Totals and trailing windows, scaled on their own
+}
+
+impl DisplayPoints {
+    // Scale of a response field holding points, None for anything else. Every f64
+    // points field of a response type has to be listed here.
+    fn scale_of(&self, key: &str) -> Option<&DisplayScale> {
+        match key {
+            "sage_points" | "position_sage_points" => Some(&self.sage),
+            "formation_points" | "position_formation_points" => Some(&self.formation),
+            "total_points" | "running_total" | "points_24h" | "points_7d" | "points_30d" => Some(&self.total),
+            "from_points" | "to_points" | "points_delta" => Some(&self.total), // Snapshot diffs
+            "points" | "pool_points" => Some(&self.total),                     // Allocation previews
+            _ => None,
+        }
+    }
+
+    /// Add a `<field>_display` integer next to every points field of a response payload
+    pub fn annotate(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(fields) => {
+                let display: Vec<(String, i64)> = fields
+                    .iter()
+                    .filter_map(|(key, value)| Some((format!("{}_display", key), self.scale_of(key)?.apply(value.as_f64()?))))
+                    .collect();
+                for value in fields.values_mut() {
+                    self.annotate(value);
+                }
+                for (key, points) in display {
+                    fields.insert(key, points.into());
+                }
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|item| self.annotate(item)),
+            _ => {}
+        }
+    }
+}
+
+/// Program parameters in effect at a point in time, for frontends
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramInfo {
+    pub season: Option<Season>,
+    pub sage_rate: f64,      // Per token per day
+    pub formation_rate: f64, // Per token per day
+    pub multiplier: f64,     // Combined boost multiplier right now, 0 while paused
+    pub paused: bool,
+    pub active_boosts: Vec<BoostWindow>,
+    pub upcoming_boosts: Vec<BoostWindow>,
+    pub lock_tiers: Vec<LockTier>,
+    pub daily_cap: Option<DailyCap>,
+    pub min_deposit: f64,
+    pub program_end: Option<u64>,
+    pub seconds_until_end: Option<u64>, // 0 once the program has ended
+    pub rules_version: String,
+}
+
+/// Time window during which no position accrues points
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PauseWindow {
+    pub id: i32,
+    pub start: u64,
+    pub end: u64,
+    pub reason: String,
+}
+
+/// Points (and optionally position history) moved from one address to another
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressMigration {
+    pub id: i32,
+    pub from: Address,
+    pub to: Address,
+    pub include_positions: bool, // Also show the old address's positions and events under the new one
+    pub reason: String,
+    pub migrated_at: u64,
+}
+
+/// On-chain `Delegate` event: from `timestamp` until the delegator's next one,
+/// `delegatee` can earn the points of the delegator's positions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delegation {
+    pub delegator: Address,
+    pub delegatee: Address, // Zero address revokes the delegation
+    pub block_number: u64,
+    pub tx_hash: String,
+    pub timestamp: u64,
+}
+
+/// Which party earns the points of delegated positions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DelegatedPoints {
+    #[default]
+    Owner,
+    Delegatee,
+}
+
+/// Points each position slice had accrued by `at`, so lifetime totals only integrate
+/// from there. With daily caps, each earner's capped total is kept as well, up to the
+/// start of `at`'s UTC day so capped days are never split. Only valid for the rules
+/// they were built with; rules loaded on reload start without any.
+#[derive(Debug, Default)]
+pub struct AccrualCheckpoints {
+    pub at: u64,
+    entries: HashMap<(Address, U256, u64), AccrualCheckpoint>, // By user, nonce, slice start
+    owners: HashMap<Address, OwnerCheckpoint>, // By earner, only with daily caps
+}
+
+// Position slice by user, nonce and slice start, with its accrual window
+type SliceWindow = ((Address, U256, u64), Option<(f64, u64, u64)>);
+
+#[derive(Debug)]
+struct AccrualCheckpoint {
+    window: (f64, u64, u64), // Accrual window up to `at`, a slice that changed since won't match
+    points: PointsBreakdown,
+}
+
+#[derive(Debug)]
+struct OwnerCheckpoint {
+    windows: Vec<SliceWindow>, // Every slice the total covers, sorted
+    points: PointsBreakdown,
+}
+
+/// Everything the accrual engine needs to turn positions into points
+#[derive(Debug, Clone)]
+pub struct PointsRules {
+    pub rate_periods: Vec<RatePeriod>, // Sorted by effective_from
+    pub boosts: Vec<BoostWindow>,      // Overlapping boosts add their bonus
+    pub lock_tiers: Vec<LockTier>,     // The longest tier a position qualifies for applies
+    pub pauses: Vec<PauseWindow>,      // Accrual is zero inside these windows
+    pub daily_caps: Vec<DailyCap>,     // Non-overlapping, applied per owner
+    pub blocklist: HashSet<Address>,   // Addresses that never earn points
+    pub migrations: Vec<AddressMigration>, // Admin address migrations, in order
+    pub delegations: Vec<Delegation>,  // In chain order
+    pub delegated_points_to: DelegatedPoints,
+    pub program_end: Option<u64>,      // Nothing accrues from this timestamp on
+    pub seasons: Vec<Season>,          // Informational, sorted by start
+    pub allocation: Option<AllocationConfig>,
+    pub competitions: Option<CompetitionConfig>,
+    pub display: Option<DisplayPoints>, // Integer display values in API responses
+    pub min_deposit: f64,              // Tokens; smaller positions are tracked but never earn
+    pub checkpoints: Arc<AccrualCheckpoints>,
+}
+
+impl Default for PointsRules {
+    fn default() -> Self {
+        Self {
+            rate_periods: vec![RatePeriod {
+                effective_from: 0,
+                sage_rate: DEFAULT_SAGE_RATE,
+                formation_rate: DEFAULT_FORMATION_RATE,
+            }],
+            boosts: Vec::new(),
+            lock_tiers: Vec::new(),
+            pauses: Vec::new(),
+            daily_caps: Vec::new(),
+            blocklist: HashSet::new(),
+            migrations: Vec::new(),
+            delegations: Vec::new(),
+            delegated_points_to: DelegatedPoints::Owner,
+            program_end: None,
+            seasons: Vec::new(),
+            allocation: None,
+            competitions: None,
+            display: None,
+            min_deposit: 0.0,
+            checkpoints: Arc::default(),
+        }
+    }
+}
+
+impl PointsRules {
+    // Rate period in effect at a timestamp
+    fn rate_at(&self, timestamp: u64) -> Option<&RatePeriod> {
+        self.rate_periods.iter().rev().find(|p| p.effective_from <= timestamp)
+    }
+
+    // Combined boost multiplier at a timestamp (zero while paused)
+    fn multiplier_at(&self, timestamp: u64) -> f64 {
+        if self.pauses.iter().any(|p| p.start <= timestamp && timestamp < p.end) {
+            return 0.0;
+        }
+
+        1.0 + self.boosts.iter()
+            .filter(|b| b.start <= timestamp && timestamp < b.end)
+            .map(|b| b.multiplier - 1.0)
+            .sum::<f64>()
+    }
+
+    // Follow migrations from `user`, optionally only those that moved position history
+    fn resolve(&self, user: Address, history_only: bool) -> Address {
+        let mut current = user;
+        // Chains are bounded by the number of migrations, cycles are rejected on insert
+        for _ in 0..self.migrations.len() {
+            match self.migrations.iter().find(|m| m.from == current && (!history_only || m.include_positions)) {
+                Some(migration) => current = migration.to,
+                None => break,
+            }
+        }
+        current
+    }
+
+    /// Multiplier of the lock tier a position locked for `lock_duration` seconds falls in
+    pub fn lock_multiplier(&self, lock_duration: u64) -> f64 {
+        self.lock_tiers.iter()
+            .filter(|t| t.min_duration <= lock_duration)
+            .max_by_key(|t| t.min_duration)
+            .map_or(1.0, |t| t.multiplier)
+    }
+
+    /// Parameters in effect at `now`
+    pub fn program_info(&self, now: u64) -> ProgramInfo {
+        let rate = self.rate_at(now);
+        let multiplier = if self.program_end.is_some_and(|end| now >= end) { 0.0 } else { self.multiplier_at(now) };
+
+        ProgramInfo {
+            season: self.seasons.iter().find(|s| s.start <= now && now < s.end).cloned(),
+            sage_rate: rate.map_or(0.0, |r| r.sage_rate),
+            formation_rate: rate.map_or(0.0, |r| r.formation_rate),
+            multiplier,
+            paused: self.pauses.iter().any(|p| p.start <= now && now < p.end),
+            active_boosts: self.boosts.iter().filter(|b| b.start <= now && now < b.end).cloned().collect(),
+            upcoming_boosts: self.boosts.iter().filter(|b| b.start > now).cloned().collect(),
+            lock_tiers: self.lock_tiers.clone(),
+            daily_cap: self.daily_caps.iter().find(|c| c.start <= now && now < c.end).cloned(),
+            min_deposit: self.min_deposit,
+            program_end: self.program_end,
+            seconds_until_end: self.program_end.map(|end| end.saturating_sub(now)),
+            rules_version: self.version(),
+        }
+    }
+
+    /// Short hash of every parameter that affects accrual, changes whenever they do
+    pub fn version(&self) -> String {
+        let pauses: Vec<(u64, u64)> = self.pauses.iter().map(|p| (p.start, p.end)).collect();
+        let mut blocklist: Vec<&Address> = self.blocklist.iter().collect();
+        blocklist.sort();
+        let migrations: Vec<(Address, Address)> = self.migrations.iter().map(|m| (m.from, m.to)).collect();
+        // Delegations only move points when delegatees earn them
+        let delegations = (self.delegated_points_to == DelegatedPoints::Delegatee).then_some(self.delegations.len());
+
+        let encoded = serde_json::to_vec(&(
+            &self.rate_periods,
+            &self.boosts,
+            &self.lock_tiers,
+            &pauses,
+            &self.daily_caps,
+            &blocklist,
+            &migrations,
+            delegations,
+            self.program_end,
+            self.min_deposit,
+        ))
+        .unwrap_or_default();
+        keccak256(encoded).to_string()[..18].to_string() // 0x + 8 bytes
+    }
+
+    /// Whether a position is below the minimum deposit, so it earns nothing and
+    /// stays off the leaderboard
+    pub fn is_dust(&self, position: &Position) -> bool {
+        self.min_deposit > 0.0 && format_token_amount_as_float(position.amount) < self.min_deposit
+    }
+
+    /// Address that receives the points earned by `user`'s positions
+    pub fn owner(&self, user: Address) -> Address {
+        self.resolve(user, false)
+    }
+
+    /// Addresses whose points (or, with `history_only`, positions and events) belong to `owner`
+    pub fn addresses_owned_by(&self, owner: Address, history_only: bool) -> Vec<Address> {
+        // Each address can only be migrated away once, so there are no duplicates
+        std::iter::once(owner)
+            .chain(self.migrations.iter().map(|m| m.from))
+            .filter(|&address| self.resolve(address, history_only) == owner)
+            .collect()
+    }
+
+    /// Addresses whose positions can earn points for `owner`: its own and migrated ones,
+    /// plus stakers that delegated to it when delegatees earn the points
+    pub fn point_sources(&self, owner: Address) -> Vec<Address> {
+        let mut sources = self.addresses_owned_by(owner, false);
+        if self.delegated_points_to == DelegatedPoints::Delegatee {
+            for delegation in &self.delegations {
+                if !delegation.delegatee.is_zero()
+                    && self.owner(delegation.delegatee) == owner
+                    && !sources.contains(&delegation.delegator)
+                {
+                    sources.push(delegation.delegator);
+                }
+            }
+        }
+        sources
+    }
+
+    /// A position split into the stretches each points owner earns: the staker's owner,
+    /// or while delegated (and delegatees earn the points) the delegatee's owner
+    pub fn earner_slices<'a>(&self, position: &'a Position) -> Vec<(Address, Cow<'a, Position>)> {
+        let owner = self.owner(position.user);
+        let delegations: Vec<&Delegation> = match self.delegated_points_to {
+            DelegatedPoints::Owner => Vec::new(),
+            DelegatedPoints::Delegatee => self.delegations.iter().filter(|d| d.delegator == position.user).collect(),
+        };
+        if delegations.is_empty() {
+            return vec![(owner, Cow::Borrowed(position))];
+        }
+
+        // Copy of the position that only accrues between `start` and `end`
+        let clipped = |start: u64, end: Option<u64>| {
+            let mut slice = position.clone();
+            slice.deposit_timestamp = slice.deposit_timestamp.max(start);
+            if let Some(end) = end {
+                slice.withdrawal_initiated_timestamp = Some(slice.withdrawal_initiated_timestamp.map_or(end, |t| t.min(end)));
+            }
+            slice
+        };
+
+        let mut slices = Vec::new();
+        let (mut start, mut earner) = (0, owner);
+        for delegation in delegations {
+            slices.push((earner, clipped(start, Some(delegation.timestamp))));
+            start = delegation.timestamp;
+            earner = if delegation.delegatee.is_zero() { owner } else { self.owner(delegation.delegatee) };
+        }
+        slices.push((earner, clipped(start, None)));
+
+        slices
+            .into_iter()
+            .filter(|(_, slice)| slice.withdrawal_initiated_timestamp.is_none_or(|end| slice.deposit_timestamp < end))
+            .map(|(earner, slice)| (earner, Cow::Owned(slice)))
+            .collect()
+    }
+
+    /// Points accrued by `tokens` staked between `start` and `end`
+    pub fn accrue(&self, tokens: f64, start: u64, end: u64) -> PointsBreakdown {
+        let mut total = PointsBreakdown::default();
+        for segment in self.accrual_segments(tokens, start, end) {
+            total.sage_points += segment.sage_points;
+            total.formation_points += segment.formation_points;
+        }
+        total
+    }
+
+    /// Accrual of `tokens` staked between `start` and `end`, split wherever the rate,
+    /// boost or pause state changes
+    pub fn accrual_segments(&self, tokens: f64, start: u64, end: u64) -> Vec<AccrualSegment> {
+        let mut segments = Vec::new();
+        if end <= start {
+            return segments;
+        }
+
+        // Split the interval wherever the rate, boost or pause state changes
+        let mut breakpoints = vec![start, end];
+        breakpoints.extend(self.rate_periods.iter().map(|p| p.effective_from));
+        breakpoints.extend(self.boosts.iter().flat_map(|b| [b.start, b.end]));
+        breakpoints.extend(self.pauses.iter().flat_map(|p| [p.start, p.end]));
+        breakpoints.retain(|&t| t >= start && t <= end);
+        breakpoints.sort_unstable();
+        breakpoints.dedup();
+
+        for segment in breakpoints.windows(2) {
+            let (from, to) = (segment[0], segment[1]);
+            let Some(rate) = self.rate_at(from) else { continue };
+
+            let multiplier = self.multiplier_at(from);
+            let days = (to - from) as f64 / 86400.0; // 86400 seconds in a day
+            let weighted = tokens * days * multiplier;
+            segments.push(AccrualSegment {
+                start: from,
+                end: to,
+                sage_rate: rate.sage_rate,
+                formation_rate: rate.formation_rate,
+                multiplier,
+                sage_points: weighted * rate.sage_rate,
+                formation_points: weighted * rate.formation_rate,
+            });
+        }
+
+        segments
+    }
+
+    /// Points earned by a position up to `now`
+    pub fn position_points(&self, position: &Position, now: u64) -> PointsBreakdown {
+        self.position_points_since(position, 0, now)
+    }
+
+    /// Points earned by a position between `since` and `now`
+    pub fn position_points_since(&self, position: &Position, since: u64, now: u64) -> PointsBreakdown {
+        if since == 0 {
+            if let Some(points) = self.checkpointed_points(position, now) {
+                return points;
+            }
+        }
+
+        match self.accrual_window(position, since, now) {
+            Some((tokens, start, end)) => self.accrue(tokens, start, end),
+            None => PointsBreakdown::default(),
+        }
+    }
+
+    /// How a position's points up to `now` were accrued, with its lock tier
+    /// included in each segment's multiplier
+    pub fn position_segments(&self, position: &Position, now: u64) -> Vec<AccrualSegment> {
+        let Some((tokens, start, end)) = self.accrual_window(position, 0, now) else {
+            return Vec::new();
+        };
+
+        let lock_multiplier = self.lock_multiplier(position.lock_duration);
+        let mut segments = self.accrual_segments(tokens, start, end);
+        for segment in &mut segments {
+            segment.multiplier *= lock_multiplier;
+        }
+        segments
+    }
+
+    /// Points earned between `since` and `now` and active stake per points owner,
+    /// blocked owners excluded. Every leaderboard (API, console, published roots,
+    /// backtests) is built from this, so they cannot disagree. Delegated points go to
+    /// the earner of each stretch, the stake always stays with the staker's owner.
+    pub fn owner_totals<'a>(
+        &self,
+        positions: impl IntoIterator<Item = &'a Position>,
+        since: u64,
+        now: u64,
+    ) -> HashMap<Address, OwnerTotals> {
+        let mut slices = Vec::new();
+        let mut active_amounts: HashMap<Address, U256> = HashMap::new();
+        for position in positions {
+            let owner = self.owner(position.user);
+            if self.blocklist.contains(&owner) || self.is_dust(position) {
+                continue;
+            }
+            let active_amount = active_amounts.entry(owner).or_default();
+            if matches!(position.status, PositionStatus::Active) {
+                *active_amount += position.amount;
+            }
+            slices.extend(self.earner_slices(position).into_iter().filter(|(earner, _)| !self.blocklist.contains(earner)));
+        }
+
+        let mut by_earner: HashMap<Address, Vec<&Position>> = HashMap::new();
+        for (earner, slice) in &slices {
+            by_earner.entry(*earner).or_default().push(slice.as_ref());
+        }
+
+        let mut totals: HashMap<Address, OwnerTotals> = by_earner
+            .into_iter()
+            .map(|(earner, positions)| {
+                let points = match since {
+                    0 => self.checkpointed_owner_points(earner, &positions, now),
+                    _ => None,
+                };
+                let points = points.unwrap_or_else(|| self.owner_points(&positions, since, now));
+                (earner, OwnerTotals { points, active_amount: U256::ZERO })
+            })
+            .collect();
+        for (owner, active_amount) in active_amounts {
+            totals.entry(owner).or_default().active_amount = active_amount;
+        }
+        totals
+    }
+
+    /// Checkpoint of every position slice's points up to `at`, extending the current
+    /// checkpoint where it still applies
+    pub fn checkpoint<'a>(&self, positions: impl IntoIterator<Item = &'a Position>, at: u64) -> AccrualCheckpoints {
+        let mut entries = HashMap::new();
+        let mut by_earner: HashMap<Address, Vec<Cow<Position>>> = HashMap::new();
+        for position in positions {
+            for (earner, slice) in self.earner_slices(position) {
+                if let Some(window) = self.accrual_window(&slice, 0, at) {
+                    let points = self.position_points_since(&slice, 0, at);
+                    entries.insert((slice.user, slice.nonce, slice.deposit_timestamp), AccrualCheckpoint { window, points });
+                }
+                by_earner.entry(earner).or_default().push(slice);
+            }
+        }
+
+        // Capped totals are summed one UTC day at a time, so without a checkpoint their
+        // cost grows with every day of history
+        let mut owners = HashMap::new();
+        if !self.daily_caps.is_empty() {
+            let day = at / DAY * DAY;
+            for (earner, slices) in by_earner {
+                let positions: Vec<&Position> = slices.iter().map(|slice| slice.as_ref()).collect();
+                let points = self.checkpointed_owner_points(earner, &positions, day)
+                    .unwrap_or_else(|| self.owner_points(&positions, 0, day));
+                owners.insert(earner, OwnerCheckpoint { windows: self.slice_windows(&positions, day), points });
+            }
+        }
+
+        AccrualCheckpoints { at, entries, owners }
+    }
+
+    // Accrual windows up to `at` of one earner's slices, sorted
+    fn slice_windows(&self, positions: &[&Position], at: u64) -> Vec<SliceWindow> {
+        let mut windows: Vec<SliceWindow> = positions
+            .iter()
+            .map(|p| ((p.user, p.nonce, p.deposit_timestamp), self.accrual_window(p, 0, at)))
+            .collect();
+        windows.sort_by_key(|window| window.0);
+        windows
+    }
+
+    // Capped lifetime points of one earner's slices up to `now`, integrating only from
+    // its checkpoint, None unless the checkpoint covers exactly these slices unchanged
+    fn checkpointed_owner_points(&self, earner: Address, positions: &[&Position], now: u64) -> Option<PointsBreakdown> {
+        let checkpoint = self.checkpoints.owners.get(&earner)?;
+        let day = self.checkpoints.at / DAY * DAY;
+        if now < day || self.slice_windows(positions, day) != checkpoint.windows {
+            return None;
+        }
+
+        let mut points = checkpoint.points.clone();
+        points += self.owner_points(positions, day, now);
+        Some(points)
+    }
+
+    // Lifetime points of a position up to `now`, integrating only from its checkpoint,
+    // None without a matching one
+    fn checkpointed_points(&self, position: &Position, now: u64) -> Option<PointsBreakdown> {
+        let checkpoints = &self.checkpoints;
+        if now < checkpoints.at {
+            return None;
+        }
+        let checkpoint = checkpoints.entries.get(&(position.user, position.nonce, position.deposit_timestamp))?;
+        if self.accrual_window(position, 0, checkpoints.at) != Some(checkpoint.window) {
+            return None;
+        }
+
+        let mut points = checkpoint.points.clone();
+        if let Some((tokens, start, end)) = self.accrual_window(position, checkpoints.at, now) {
+            points += self.accrue(tokens, start, end);
+        }
+        Some(points)
+    }
+
+    /// Points emitted between `since` and `now` across `positions`, split by source.
+    /// The sources add up to the credited total, so emissions can be checked against
+    /// the model rate by rate.
+    pub fn emissions(&self, positions: &[&Position], since: u64, now: u64) -> Emissions {
+        let mut emissions = Emissions::default();
+        for totals in self.owner_totals(positions.iter().copied(), since, now).into_values() {
+            emissions.points += totals.points;
+        }
+
+        let mut uncapped = 0.0;
+        for position in positions {
+            for (earner, slice) in self.earner_slices(position) {
+                if self.blocklist.contains(&earner) {
+                    continue;
+                }
+                let Some((_, start, end)) = self.accrual_window(&slice, since, now) else { continue };
+
+                // Segments of the unweighted stake carry the boost in their multiplier
+                let tokens = format_token_amount_as_float(slice.amount);
+                let lock_multiplier = self.lock_multiplier(slice.lock_duration);
+                for segment in self.accrual_segments(tokens, start, end) {
+                    let boosted = segment.sage_points + segment.formation_points;
+                    let base = if segment.multiplier > 0.0 { boosted / segment.multiplier } else { 0.0 };
+                    emissions.base += base;
+                    emissions.boosts += boosted - base;
+                    emissions.lock_tiers += boosted * (lock_multiplier - 1.0);
+                    uncapped += boosted * lock_multiplier;
+                }
+            }
+        }
+        emissions.daily_caps = emissions.points.total() - uncapped;
+
+        emissions
+    }
+
+    /// Points earned between `since` and `now` by positions that all belong to one
+    /// owner, with daily caps applied to their combined accrual
+    pub fn owner_points(&self, positions: &[&Position], since: u64, now: u64) -> PointsBreakdown {
+        let sum = |from: u64, to: u64| {
+            let mut points = PointsBreakdown::default();
+            if from < to {
+                for position in positions {
+                    points += self.position_points_since(position, from, to);
+                }
+            }
+            points
+        };
+
+        // Uncapped stretches are summed as a whole, capped ones one UTC day at a time
+        let mut total = PointsBreakdown::default();
+        let mut cursor = since;
+        for cap in self.daily_caps.iter().filter(|c| c.end > since && c.start < now) {
+            let (start, end) = (cap.start.max(since), cap.end.min(now));
+            total += sum(cursor, start);
+
+            let mut day_start = start;
+            while day_start < end {
+                let day_end = (day_start / DAY * DAY + DAY).min(end);
+                // The cap is on the whole day, a day cut short by `since` keeps its share of it
+                let whole_start = (day_start / DAY * DAY).max(cap.start);
+                let part = sum(day_start, day_end);
+                total += if whole_start < day_start {
+                    let whole = sum(whole_start, day_end).total();
+                    part.scaled(if whole > cap.max_points { cap.max_points / whole } else { 1.0 })
+                } else {
+                    part.capped(cap.max_points)
+                };
+                day_start = day_end;
+            }
+            cursor = end;
+        }
+        total += sum(cursor, now);
+
+        total
+    }
+
+    // Staked tokens (weighted by lock tier) and the interval a position accrues over,
+    // None if it never earns
+    fn accrual_window(&self, position: &Position, since: u64, now: u64) -> Option<(f64, u64, u64)> {
+        if self.blocklist.contains(&position.user) || self.blocklist.contains(&self.owner(position.user)) || self.is_dust(position) {
+            return None;
+        }
+
+        let end_timestamp = if let Some(withdrawal_ts) = position.withdrawal_initiated_timestamp {
+            // For unstaking/withdrawn positions, points stopped at withdrawal initiation
+            withdrawal_ts
+        } else if matches!(position.status, PositionStatus::Active) {
+            // Still active, calculate until now
+            now
+        } else {
+            // Shouldn't happen, but use deposit timestamp as fallback
+            position.deposit_timestamp
+        };
+
+        // Convert amount from wei to tokens (18 decimals), weighted by lock tier
+        let tokens = format_token_amount_as_float(position.amount) * self.lock_multiplier(position.lock_duration);
+
+        // The program end freezes every position's points
+        let end_timestamp = end_timestamp.min(now).min(self.program_end.unwrap_or(u64::MAX));
+
+        Some((tokens, position.deposit_timestamp.max(since), end_timestamp))
+    }
+}
@@ -0,0 +1,138 @@
+use alloy::primitives::Address;
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::db::{Database, LeaderboardEntry};
+
+const MAX_DISPLAY_NAME_CHARS: usize = 32;
+
+/// Public display name and avatar an address chose for itself, shown on the leaderboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(skip_deserializing)]
+    pub address: Address, // Taken from the session, never from the request body
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+impl Profile {
+    /// Trim fields, treating blank ones as unset
+    pub fn normalize(&mut self) {
+        for field in [&mut self.display_name, &mut self.avatar_url] {
+            *field = field.take().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        }
+    }
+
+    /// Reject names and URLs that can't be shown safely on a public board
+    pub fn validate(&self) -> Result<()> {
+        if self.display_name.is_none() && self.avatar_url.is_none() {
+            return Err(eyre!("Set a display_name or an avatar_url"));
+        }
+        if let Some(name) = &self.display_name {
+            if name.chars().count() > MAX_DISPLAY_NAME_CHARS {
+                return Err(eyre!("display_name must be at most {} characters", MAX_DISPLAY_NAME_CHARS));
+            }
+            if !name.chars().all(|c| c.is_alphanumeric() || " ._-".contains(c)) {
+                return Err(eyre!("display_name may only contain letters, digits, spaces, '.', '_' and '-'"));
+            }
+            // Names that read as an address could impersonate another staker
+            if name.to_ascii_lowercase().starts_with("0x") {
+                return Err(eyre!("display_name must not start with 0x"));
+            }
+        }
+        if let Some(url) = &self.avatar_url {
+            let valid = url.len() <= 2048 && reqwest::Url::parse(url).is_ok_and(|u| u.scheme() == "https" && u.host_str().is_some());
+            if !valid {
+                return Err(eyre!("avatar_url must be an https URL"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ModerationVerdict {
+    allowed: bool,
+    reason: Option<String>,
+}
+
+/// Checks profiles before they are saved: a blocked-word list (`PROFILE_BLOCKED_WORDS`)
+/// and, when `PROFILE_MODERATION_URL` is set, an off-chain moderation service
+#[derive(Clone)]
+pub struct ProfileModeration {
+    client: reqwest::Client,
+    blocked_words: Vec<String>, // Lowercase
+    hook_url: Option<String>,   // Receives `{address, display_name, avatar_url}`, answers `{allowed, reason}`
+}
+
+impl ProfileModeration {
+    pub fn from_env() -> Self {
+        let blocked_words = std::env::var("PROFILE_BLOCKED_WORDS")
+            .map(|v| v.split(',').map(|w| w.trim().to_lowercase()).filter(|w| !w.is_empty()).collect())
+            .unwrap_or_default();
+
+        Self {
+            client: reqwest::Client::new(),
+            blocked_words,
+            hook_url: std::env::var("PROFILE_MODERATION_URL").ok().filter(|v| !v.is_empty()),
+        }
+    }
+
+    /// Why the profile is rejected, None when it may be saved. Fails when the
+    /// moderation service can't be reached, so unchecked profiles are never published.
+    pub async fn review(&self, profile: &Profile) -> Result<Option<String>> {
+        if let Some(name) = &profile.display_name {
+            // Ignore separators, so "b.a d" still matches "bad"
+            let folded: String = name.to_lowercase().chars().filter(|c| c.is_alphanumeric()).collect();
+            if self.blocked_words.iter().any(|word| folded.contains(word.as_str())) {
+                return Ok(Some("display_name contains a blocked word".to_string()));
+            }
+        }
+
+        let Some(url) = &self.hook_url else { return Ok(None) };
+        let verdict: ModerationVerdict = self.client
+            .post(url)
+            .timeout(Duration::from_secs(5))
+            .json(&json!({
+                "address": profile.address.to_string(),
+                "display_name": profile.display_name,
+                "avatar_url": profile.avatar_url,
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok((!verdict.allowed).then(|| verdict.reason.unwrap_or_else(|| "Rejected by moderation".to_string())))
+    }
+}
+
+/// Profiles live in the main program's database and are shared by every program
+#[derive(Clone)]
+pub struct Profiles {
+    pub db: Database,
+    pub moderation: ProfileModeration,
+}
+
+impl Profiles {
+    /// Fill in the display name and avatar of every entry that has a profile
+    pub async fn attach(&self, entries: &mut [LeaderboardEntry]) -> Result<()> {
+        let addresses: Vec<Address> = entries.iter().filter_map(|e| e.address.parse().ok()).collect();
+        let profiles: HashMap<Address, Profile> = self.db.get_profiles(&addresses).await?
+            .into_iter()
+            .map(|p| (p.address, p))
+            .collect();
+
+        for entry in entries {
+            if let Some(profile) = entry.address.parse().ok().and_then(|a: Address| profiles.get(&a)) {
+                entry.display_name = profile.display_name.clone();
+                entry.avatar_url = profile.avatar_url.clone();
+            }
+        }
+        Ok(())
+    }
+}
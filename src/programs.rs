@@ -0,0 +1,71 @@
+use alloy::primitives::Address;
+use eyre::{eyre, Result};
+use serde::Deserialize;
+use std::collections::HashSet;
+
+// First path segments of the API routes, which program names would shadow
+const RESERVED_NAMES: &[&str] = &[
+    "points", "events", "positions", "summary", "withdrawable", "tx", "tvl", "metrics",
+    "leaderboard", "snapshots", "verify", "program", "allocation", "rank", "health", "api", "auth", "notifications", "profile", "admin",
+];
+
+/// A points program indexed alongside the main one, listed in `PROGRAMS_FILE`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProgramConfig {
+    pub name: String, // Route prefix, `/v1/{name}/...`
+    pub contract_address: Address,
+    pub deployment_block: u64,
+    #[serde(default)]
+    pub settings_file: Option<String>,
+    #[serde(default)]
+    pub database_url: Option<String>, // Defaults to DATABASE_URL
+    #[serde(default)]
+    pub database_read_url: Option<String>, // Replica for API reads, defaults to DATABASE_READ_URL when sharing DATABASE_URL
+    #[serde(default)]
+    pub schema: Option<String>,       // PostgreSQL schema, defaults to the name when sharing DATABASE_URL
+}
+
+impl ProgramConfig {
+    /// Schema holding this program's tables; programs sharing the main database get
+    /// their own so they never see each other's rows
+    pub fn schema(&self) -> Option<&str> {
+        match (&self.schema, &self.database_url) {
+            (Some(schema), _) => Some(schema),
+            (None, None) => Some(&self.name),
+            (None, Some(_)) => None,
+        }
+    }
+}
+
+// Names end up in URLs and schema names, so keep them to lowercase identifiers
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 32
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Read and validate the additional programs
+pub fn load(path: &str) -> Result<Vec<ProgramConfig>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| eyre!("Failed to read programs file {}: {}", path, e))?;
+    let programs: Vec<ProgramConfig> = serde_json::from_str(&contents)
+        .map_err(|e| eyre!("Invalid programs file {}: {}", path, e))?;
+
+    let mut names = HashSet::new();
+    for program in &programs {
+        if !is_valid_name(&program.name) || RESERVED_NAMES.contains(&program.name.as_str()) {
+            return Err(eyre!("Invalid program name '{}': use lowercase letters, digits and '_', not an API route name", program.name));
+        }
+        if !names.insert(&program.name) {
+            return Err(eyre!("Duplicate program name '{}'", program.name));
+        }
+        if let Some(schema) = &program.schema {
+            if !is_valid_name(schema) {
+                return Err(eyre!("Invalid schema '{}' for program '{}'", schema, program.name));
+            }
+        }
+    }
+
+    Ok(programs)
+}
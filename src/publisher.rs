@@ -0,0 +1,183 @@
+use alloy::{
+    network::EthereumWallet,
+    primitives::{keccak256, Address, B256, U256},
+    providers::{Provider, ProviderBuilder},
+    signers::local::PrivateKeySigner,
+    sol,
+    sol_types::SolValue,
+};
+use eyre::{eyre, Result};
+use std::str::FromStr;
+
+use crate::db::Database;
+use crate::points::PointsBreakdown;
+
+sol!(
+    #[sol(rpc)]
+    contract PointsOracle {
+        function updateRoot(bytes32 root, uint256 blockNumber) external;
+    }
+);
+
+/// Settings for the on-chain points-root publisher, checked by `config::load`
+#[derive(Debug, Clone)]
+pub struct PublisherConfig {
+    pub oracle_address: Address,
+    pub rpc_url: String,
+    pub private_key: Option<String>,
+    pub interval_secs: u64,
+    pub dry_run: bool,
+    pub gas_limit: Option<u64>,
+    pub max_fee_per_gas: Option<u128>,          // wei
+    pub max_priority_fee_per_gas: Option<u128>, // wei
+}
+
+// Convert points to an 18-decimal fixed-point integer (6 decimals of precision kept)
+fn points_to_units(points: f64) -> U256 {
+    U256::from((points.max(0.0) * 1e6).round() as u128) * U256::from(1_000_000_000_000u64)
+}
+
+// Leaf hash compatible with OpenZeppelin's StandardMerkleTree:
+// keccak256(bytes.concat(keccak256(abi.encode(account, sagePoints, formationPoints))))
+fn leaf_hash(user: Address, points: &PointsBreakdown) -> B256 {
+    let encoded = (user, points_to_units(points.sage_points), points_to_units(points.formation_points)).abi_encode();
+    keccak256(keccak256(encoded))
+}
+
+// Hash a pair of nodes in sorted order
+fn hash_pair(a: B256, b: B256) -> B256 {
+    if a < b {
+        keccak256([a.as_slice(), b.as_slice()].concat())
+    } else {
+        keccak256([b.as_slice(), a.as_slice()].concat())
+    }
+}
+
+/// Compute the Merkle root over every user's points (sorted leaves, sorted-pair hashing)
+pub fn compute_points_root(leaderboard: &[(Address, PointsBreakdown)]) -> B256 {
+    let mut layer: Vec<B256> = leaderboard
+        .iter()
+        .map(|(user, points)| leaf_hash(*user, points))
+        .collect();
+    layer.sort();
+
+    if layer.is_empty() {
+        return B256::ZERO;
+    }
+
+    while layer.len() > 1 {
+        layer = layer
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(*a, *b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    layer[0]
+}
+
+/// Periodically submits the points Merkle root to the PointsOracle contract
+pub struct RootPublisher {
+    config: PublisherConfig,
+    signer: Option<PrivateKeySigner>,
+    next_nonce: Option<u64>,
+    last_root: Option<B256>,
+    db: Option<Database>,
+}
+
+impl RootPublisher {
+    pub fn new(config: PublisherConfig, db: Option<Database>) -> Result<Self> {
+        let signer = match &config.private_key {
+            Some(key) => Some(PrivateKeySigner::from_str(key)?),
+            None => None,
+        };
+
+        Ok(Self {
+            config,
+            signer,
+            next_nonce: None,
+            last_root: None,
+            db,
+        })
+    }
+
+    pub fn interval_secs(&self) -> u64 {
+        self.config.interval_secs
+    }
+
+    /// Compute the root for the given leaderboard and submit it if it changed
+    pub async fn publish(&mut self, leaderboard: &[(Address, PointsBreakdown)], block_number: u64) -> Result<()> {
+        let root = compute_points_root(leaderboard);
+
+        if self.last_root == Some(root) {
+            println!("🌳 Points root unchanged ({}), skipping publish", root);
+            return Ok(());
+        }
+
+        println!("🌳 Points root {} for {} users at block {}", root, leaderboard.len(), block_number);
+
+        let signer = match (&self.signer, self.config.dry_run) {
+            (Some(signer), false) => signer.clone(),
+            _ => {
+                println!("   🧪 Dry run - not submitting to {}", self.config.oracle_address);
+                self.last_root = Some(root);
+                return Ok(());
+            }
+        };
+
+        let signer_address = signer.address();
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .wallet(EthereumWallet::from(signer))
+            .on_http(self.config.rpc_url.parse()?);
+
+        // Track nonces locally so a stuck tx doesn't get silently replaced
+        let nonce = match self.next_nonce {
+            Some(nonce) => nonce,
+            None => provider.get_transaction_count(signer_address).pending().await?,
+        };
+
+        let oracle = PointsOracle::new(self.config.oracle_address, &provider);
+        let mut call = oracle.updateRoot(root, U256::from(block_number)).nonce(nonce);
+        if let Some(gas_limit) = self.config.gas_limit {
+            call = call.gas(gas_limit);
+        }
+        if let Some(max_fee) = self.config.max_fee_per_gas {
+            call = call.max_fee_per_gas(max_fee);
+        }
+        if let Some(priority_fee) = self.config.max_priority_fee_per_gas {
+            call = call.max_priority_fee_per_gas(priority_fee);
+        }
+
+        let pending = match call.send().await {
+            Ok(pending) => pending,
+            Err(e) => {
+                // Re-read the nonce from the chain on the next attempt
+                self.next_nonce = None;
+                return Err(e.into());
+            }
+        };
+        self.next_nonce = Some(nonce + 1);
+
+        let tx_hash = *pending.tx_hash();
+        println!("   📤 Submitted root in tx {} (nonce {})", tx_hash, nonce);
+
+        let receipt = pending.get_receipt().await?;
+        if !receipt.status() {
+            return Err(eyre!("Root publish transaction {} reverted", tx_hash));
+        }
+        println!("   ✅ Root confirmed in block {}", receipt.block_number.unwrap_or_default());
+
+        if let Some(db) = &self.db {
+            if let Err(e) = db.save_points_root(&root.to_string(), block_number, leaderboard.len(), &tx_hash.to_string()).await {
+                eprintln!("⚠️  Failed to save published root: {}", e);
+            }
+        }
+
+        self.last_root = Some(root);
+        Ok(())
+    }
+}
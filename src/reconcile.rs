@@ -0,0 +1,238 @@
+use alloy::{
+    primitives::{address, Address, U256},
+    providers::{Provider, RootProvider},
+    sol,
+    sol_types::SolCall,
+    transports::{http::{Client, Http}, Transport},
+};
+use eyre::Result;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::scheduler::{Schedule, Scheduler};
+use crate::{format_address, format_token_amount, PositionStatus, SageStaking, SharedTracker};
+
+// Multicall3 is deployed at the same address on Base and most EVM chains
+pub(crate) const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+// Maximum calls bundled into a single multicall request
+pub(crate) const MULTICALL_BATCH_SIZE: usize = 100;
+
+sol!(
+    #[sol(rpc)]
+    contract IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct CallResult {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls) external payable returns (CallResult[] memory returnData);
+    }
+);
+
+/// A user whose indexed staked balance differs from the contract
+#[derive(Debug)]
+pub struct BalanceDiscrepancy {
+    pub user: Address,
+    pub indexed: U256,
+    pub on_chain: U256,
+}
+
+/// Result of a reconciliation run
+#[derive(Debug, Default)]
+pub struct ReconciliationReport {
+    pub users_checked: usize,
+    pub failed_calls: usize,
+    pub discrepancies: Vec<BalanceDiscrepancy>,
+    pub positions_repaired: usize,
+}
+
+impl ReconciliationReport {
+    pub fn print(&self) {
+        println!("\n🔍 RECONCILIATION REPORT");
+        println!("   Users checked: {}", self.users_checked);
+        println!("   Failed calls: {}", self.failed_calls);
+        println!("   Discrepancies: {}", self.discrepancies.len());
+
+        for discrepancy in &self.discrepancies {
+            println!("   ❗ {} indexed={} on-chain={}",
+                format_address(discrepancy.user),
+                format_token_amount(discrepancy.indexed),
+                format_token_amount(discrepancy.on_chain));
+        }
+
+        if self.positions_repaired > 0 {
+            println!("   🔧 Positions repaired: {}", self.positions_repaired);
+        }
+    }
+
+    /// One-line summary for the job status
+    pub fn summary(&self) -> String {
+        format!(
+            "{} users checked, {} failed calls, {} discrepancies, {} positions repaired",
+            self.users_checked, self.failed_calls, self.discrepancies.len(), self.positions_repaired
+        )
+    }
+}
+
+/// Reconciliation settings, from `RECONCILE_INTERVAL_SECS` and `RECONCILE_AUTO_REPAIR`
+/// (read by `config::load`)
+#[derive(Debug, Clone, Copy)]
+pub struct ReconcileConfig {
+    pub interval_secs: Option<u64>, // None disables reconciliation
+    pub auto_repair: bool,
+}
+
+/// Schedule reconciliation of `tracker` against the contract as job `name`. The
+/// tracker is this instance's own, so every instance reconciles it.
+pub fn register(
+    scheduler: &mut Scheduler,
+    name: impl Into<String>,
+    interval_secs: u64,
+    provider: RootProvider<Http<Client>>,
+    contract_address: Address,
+    tracker: SharedTracker,
+    repair: bool,
+) {
+    scheduler.register_per_instance(name, Schedule::Every(interval_secs), Duration::from_secs(3600), move || {
+        let (provider, tracker) = (provider.clone(), tracker.clone());
+        async move {
+            let report = run_reconciliation(&provider, contract_address, &tracker, repair).await?;
+            report.print();
+            Ok(report.summary())
+        }
+    });
+}
+
+// Fetch staked balances for many users in batched multicalls (None when a call failed)
+async fn fetch_staked_balances<T, P>(
+    provider: &P,
+    contract_address: Address,
+    users: &[Address],
+) -> Result<Vec<Option<U256>>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let multicall = IMulticall3::new(MULTICALL3_ADDRESS, provider);
+    let mut balances = Vec::with_capacity(users.len());
+
+    for chunk in users.chunks(MULTICALL_BATCH_SIZE) {
+        let calls: Vec<IMulticall3::Call3> = chunk
+            .iter()
+            .map(|user| IMulticall3::Call3 {
+                target: contract_address,
+                allowFailure: true,
+                callData: SageStaking::stakedBalanceCall { user: *user }.abi_encode().into(),
+            })
+            .collect();
+
+        let results = multicall.aggregate3(calls).call().await?.returnData;
+
+        for result in results {
+            let balance = if result.success {
+                SageStaking::stakedBalanceCall::abi_decode_returns(&result.returnData, true)
+                    .ok()
+                    .map(|r| r._0)
+            } else {
+                None
+            };
+            balances.push(balance);
+        }
+    }
+
+    Ok(balances)
+}
+
+/// Compare every known user's staked balance (active + unstaking) against the contract.
+/// With `repair` set, positions of mismatched users are re-read from the contract and
+/// overwritten in the tracker and database. The tracker is only locked to read balances
+/// and to apply repairs, never across RPC calls, so indexing and the API keep going.
+pub async fn run_reconciliation<T, P>(
+    provider: &P,
+    contract_address: Address,
+    tracker: &SharedTracker,
+    repair: bool,
+) -> Result<ReconciliationReport>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    // Indexed staked balance per user (withdrawn positions count as zero)
+    let mut indexed: HashMap<Address, U256> = HashMap::new();
+    {
+        let tracker = tracker.read().await;
+        for position in tracker.active_positions.values().chain(tracker.unstaking_positions.values()) {
+            *indexed.entry(position.user).or_default() += position.amount;
+        }
+        for position in tracker.withdrawn_positions.values() {
+            indexed.entry(position.user).or_default();
+        }
+    }
+
+    let users: Vec<Address> = indexed.keys().copied().collect();
+    let balances = fetch_staked_balances(provider, contract_address, &users).await?;
+
+    let mut report = ReconciliationReport {
+        users_checked: users.len(),
+        ..Default::default()
+    };
+
+    for (user, on_chain) in users.into_iter().zip(balances) {
+        match on_chain {
+            Some(on_chain) if on_chain != indexed[&user] => {
+                report.discrepancies.push(BalanceDiscrepancy {
+                    user,
+                    indexed: indexed[&user],
+                    on_chain,
+                });
+            }
+            Some(_) => {}
+            None => report.failed_calls += 1,
+        }
+    }
+
+    if repair {
+        let contract = SageStaking::new(contract_address, provider);
+        let keys: Vec<(Address, U256)> = {
+            let tracker = tracker.read().await;
+            report.discrepancies.iter().flat_map(|d| tracker.get_user_position_keys(&d.user)).collect()
+        };
+
+        let mut repairs = Vec::new();
+        for key in keys {
+            let on_chain = match contract.getPosition(key.0, key.1).call().await {
+                Ok(on_chain) => on_chain,
+                Err(e) => {
+                    eprintln!("⚠️  Failed to fetch position {} #{}: {}", format_address(key.0), key.1, e);
+                    continue;
+                }
+            };
+
+            // Contract status: 1 = active, 2 = unstaking, 3 = withdrawn
+            let status = match on_chain.status {
+                1 => PositionStatus::Active,
+                2 => PositionStatus::Unstaking,
+                3 => PositionStatus::Withdrawn,
+                _ => continue,
+            };
+            let unlocks_at = (on_chain.unlocksAt > U256::ZERO).then(|| on_chain.unlocksAt.to::<u64>());
+            repairs.push((key, on_chain.amount, status, unlocks_at));
+        }
+
+        let mut tracker = tracker.write().await;
+        for (key, amount, status, unlocks_at) in repairs {
+            if tracker.repair_position(key, amount, status, unlocks_at).await {
+                report.positions_repaired += 1;
+            }
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,67 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Header carrying the request id in both directions
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    // Id of the request being served by the current task
+    static REQUEST_ID: String;
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Id of the request being served, if called while handling one
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// `eprintln!` prefixed with the current request id
+macro_rules! request_eprintln {
+    ($($arg:tt)*) => {
+        eprintln!(
+            "[{}] {}",
+            $crate::request_id::current().unwrap_or_else(|| "-".to_string()),
+            format_args!($($arg)*)
+        )
+    };
+}
+pub(crate) use request_eprintln;
+
+// Accept client ids that are short and printable, anything else is replaced
+fn is_valid(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 128 && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+// Current time plus a counter, unique across restarts without extra deps
+fn generate() -> String {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64;
+    format!("{:016x}{:08x}", nanos, NEXT_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Middleware that reuses the client's `X-Request-Id` or assigns one, exposes it
+/// to handlers and logs for the duration of the request, and echoes it back
+pub async fn propagate(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> actix_web::Result<ServiceResponse<impl MessageBody>> {
+    let id = req.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| is_valid(v))
+        .map(str::to_string)
+        .unwrap_or_else(generate);
+    let value = HeaderValue::from_str(&id).expect("request ids are printable ASCII");
+
+    // Make the id visible to the access log even when the client did not send one
+    req.headers_mut().insert(HeaderName::from_static(REQUEST_ID_HEADER), value.clone());
+
+    let mut res = REQUEST_ID.scope(id, next.call(req)).await?;
+    res.headers_mut().insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+
+    Ok(res)
+}
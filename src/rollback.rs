@@ -0,0 +1,47 @@
+use eyre::{eyre, Result};
+
+use crate::backtest::replay_events;
+use crate::db::Database;
+
+/// `rollback --to-block N`: forget everything indexed after block N.
+/// Events after N are deleted, positions are rebuilt by replaying the remaining
+/// events and the cursor is reset, so the next start re-indexes from N + 1.
+pub async fn run_rollback_command(db: &Database, args: &[String]) -> Result<()> {
+    let usage = "Usage: rollback --to-block N";
+    let block = args.iter()
+        .position(|a| a == "--to-block")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse::<u64>().ok())
+        .ok_or_else(|| eyre!(usage))?;
+
+    // Before a view bootstrap there are no events to rebuild positions from
+    if let Some(bootstrap) = db.get_bootstrap_block().await?.filter(|&b| block < b) {
+        return Err(eyre!("Can't roll back to block {}, positions were bootstrapped at block {} and have no events before it", block, bootstrap));
+    }
+
+    let cursor = db.get_last_processed_block().await?;
+    if cursor.is_some_and(|cursor| cursor <= block) {
+        println!("Nothing to roll back, last processed block is {:?}", cursor);
+        return Ok(());
+    }
+
+    let events: Vec<_> = db.load_events().await?
+        .into_iter()
+        .filter(|e| e.block_number <= block)
+        .collect();
+    println!("⏪ Replaying {} events up to block {}...", events.len(), block);
+    let tracker = replay_events(&events).await;
+
+    let positions: Vec<_> = tracker.active_positions.values()
+        .chain(tracker.unstaking_positions.values())
+        .chain(tracker.withdrawn_positions.values())
+        .cloned()
+        .collect();
+
+    let removed = db.rollback_to_block(block, &positions).await?;
+    println!("↩️  Rolled back to block {}: removed {} events, {} positions rebuilt (cursor was {:?})",
+        block, removed, positions.len(), cursor);
+    println!("Restart the service to re-index from block {}.", block + 1);
+
+    Ok(())
+}
@@ -0,0 +1,265 @@
+use eyre::{eyre, Result};
+use futures::future::BoxFuture;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+use crate::db::Database;
+
+const DAY: u64 = 86400;
+
+/// When a job runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    Every(u64),   // Seconds between starts
+    DailyAt(u64), // Seconds after 00:00 UTC
+}
+
+impl Schedule {
+    /// Parse `every <n>[s|m|h|d]` or `daily HH:MM` (UTC)
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if let Some(interval) = value.strip_prefix("every ") {
+            let interval = interval.trim();
+            let (number, unit) = interval.split_at(interval.find(|c: char| !c.is_ascii_digit()).unwrap_or(interval.len()));
+            let unit = match unit {
+                "" | "s" => 1,
+                "m" => 60,
+                "h" => 3600,
+                "d" => DAY,
+                _ => return Err(eyre!("Unknown unit in '{}', use s, m, h or d", value)),
+            };
+            let secs = number.parse::<u64>().ok().and_then(|n| n.checked_mul(unit)).filter(|&n| n > 0);
+            return secs.map(Self::Every).ok_or_else(|| eyre!("Invalid interval in '{}'", value));
+        }
+        if let Some(time) = value.strip_prefix("daily ") {
+            let (hours, minutes) = time.trim().split_once(':').ok_or_else(|| eyre!("Expected HH:MM in '{}'", value))?;
+            let (hours, minutes) = (hours.parse::<u64>()?, minutes.parse::<u64>()?);
+            if hours > 23 || minutes > 59 {
+                return Err(eyre!("Invalid time of day in '{}'", value));
+            }
+            return Ok(Self::DailyAt(hours * 3600 + minutes * 60));
+        }
+        Err(eyre!("Invalid schedule '{}', use 'every <n>[s|m|h|d]' or 'daily HH:MM'", value))
+    }
+
+    /// First run strictly after `last`
+    pub fn next_after(self, last: u64) -> u64 {
+        match self {
+            Self::Every(secs) => last + secs,
+            Self::DailyAt(offset) => {
+                let today = last / DAY * DAY + offset;
+                if today > last { today } else { today + DAY }
+            }
+        }
+    }
+
+    pub fn describe(self) -> String {
+        match self {
+            Self::Every(secs) => format!("every {}s", secs),
+            Self::DailyAt(offset) => format!("daily {:02}:{:02} UTC", offset / 3600, offset % 3600 / 60),
+        }
+    }
+}
+
+/// Persisted run state of a job, shared by every instance on the database
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct JobState {
+    pub name: String,
+    pub locked_by: Option<String>,  // Instance running the job right now
+    pub locked_until: Option<u64>,  // Lease end, another instance may take over after it
+    pub last_started_at: Option<u64>,
+    pub last_finished_at: Option<u64>,
+    pub last_status: Option<String>, // ok or failed
+    pub last_message: Option<String>,
+    pub last_duration_ms: Option<u64>,
+    pub run_count: u64,
+}
+
+/// Outcome of one run
+#[derive(Debug, Clone)]
+pub struct JobRun {
+    pub finished_at: u64,
+    pub ok: bool,
+    pub message: String,
+    pub duration_ms: u64,
+}
+
+/// Registered job with its schedule and persisted state, for `/admin/jobs`
+#[derive(Debug, Serialize)]
+pub struct JobStatus {
+    pub schedule: String,
+    pub next_run_at: Option<u64>,
+    pub running: bool,
+    #[serde(flatten)]
+    pub state: JobState,
+}
+
+type JobFn = Arc<dyn Fn() -> BoxFuture<'static, Result<String>> + Send + Sync>;
+
+struct Job {
+    name: String,
+    key: String, // Row in `scheduled_jobs`: the name, or `name@instance` for per-instance jobs
+    schedule: Schedule,
+    lease: Duration, // Longest a run may take before it is cancelled
+    run: JobFn,
+}
+
+/// Runs registered jobs on their schedules. Each run first takes a lease on the job in
+/// the database, so when several instances share a database only one of them runs it.
+pub struct Scheduler {
+    db: Database,
+    instance: String,
+    overrides: HashMap<String, Schedule>,
+    jobs: Vec<Arc<Job>>,
+    next_runs: Mutex<HashMap<String, u64>>,
+}
+
+impl Scheduler {
+    /// Scheduler whose `overrides` (from `JOB_SCHEDULES`, checked by `config::load`)
+    /// replace the schedules jobs are registered with
+    pub fn new(db: Database, overrides: HashMap<String, Schedule>) -> Self {
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "local".to_string());
+        Self {
+            db,
+            instance: format!("{}-{}", host, std::process::id()),
+            overrides,
+            jobs: Vec::new(),
+            next_runs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `run` on `schedule` (unless overridden), cancelling runs that take longer
+    /// than `lease`. The returned message is logged and shown in `/admin/jobs`.
+    pub fn register<F, Fut>(&mut self, name: impl Into<String>, schedule: Schedule, lease: Duration, run: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String>> + Send + 'static,
+    {
+        let name = name.into();
+        self.add(name.clone(), name, schedule, lease, run);
+    }
+
+    /// Like `register`, but every instance runs the job, for work on its own in-memory
+    /// state. Each instance leases and records it as `name@instance`.
+    pub fn register_per_instance<F, Fut>(&mut self, name: impl Into<String>, schedule: Schedule, lease: Duration, run: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String>> + Send + 'static,
+    {
+        let name = name.into();
+        let key = format!("{}@{}", name, self.instance);
+        self.add(name, key, schedule, lease, run);
+    }
+
+    fn add<F, Fut>(&mut self, name: String, key: String, schedule: Schedule, lease: Duration, run: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String>> + Send + 'static,
+    {
+        let schedule = self.overrides.get(&name).copied().unwrap_or(schedule);
+        println!("⏱️  Scheduled job '{}' {}", key, schedule.describe());
+
+        self.jobs.push(Arc::new(Job {
+            name,
+            key,
+            schedule,
+            lease,
+            run: Arc::new(move || -> BoxFuture<'static, Result<String>> { Box::pin(run()) }),
+        }));
+    }
+
+    /// Start every registered job in the background
+    pub fn start(self) -> Arc<Self> {
+        let unknown: Vec<&String> = self.overrides.keys().filter(|name| !self.jobs.iter().any(|j| &j.name == *name)).collect();
+        if !unknown.is_empty() {
+            eprintln!("⚠️  JOB_SCHEDULES names unknown jobs: {:?}", unknown);
+        }
+
+        let scheduler = Arc::new(self);
+        for job in &scheduler.jobs {
+            let (scheduler, job) = (scheduler.clone(), job.clone());
+            tokio::spawn(async move { scheduler.run_job(&job).await });
+        }
+        scheduler
+    }
+
+    async fn run_job(&self, job: &Job) {
+        // Continue from the last run on record, so restarts don't reset the schedule
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut last = match self.db.load_job_states().await {
+            Ok(states) => states.into_iter().find(|s| s.name == job.key).and_then(|s| s.last_started_at).unwrap_or(now),
+            Err(e) => {
+                eprintln!("⚠️  Failed to load state of job '{}': {}", job.key, e);
+                now
+            }
+        };
+
+        loop {
+            let due = job.schedule.next_after(last);
+            self.next_runs.lock().unwrap().insert(job.key.clone(), due);
+            let now = chrono::Utc::now().timestamp() as u64;
+            sleep(Duration::from_secs(due.saturating_sub(now))).await;
+
+            let started_at = chrono::Utc::now().timestamp() as u64;
+            last = started_at;
+            match self.db.try_lock_job(&job.key, &self.instance, started_at, due, started_at + job.lease.as_secs()).await {
+                Ok(true) => {}
+                Ok(false) => continue, // Another instance ran it, or is running it
+                Err(e) => {
+                    eprintln!("⚠️  Failed to lock job '{}': {}", job.key, e);
+                    continue;
+                }
+            }
+
+            let timer = Instant::now();
+            let result = match tokio::time::timeout(job.lease, (job.run)()).await {
+                Ok(result) => result,
+                Err(_) => Err(eyre!("cancelled after {}s", job.lease.as_secs())),
+            };
+            let (ok, message) = match result {
+                Ok(message) => {
+                    println!("⏱️  Job '{}': {}", job.key, message);
+                    (true, message)
+                }
+                Err(e) => {
+                    eprintln!("❌ Job '{}' failed: {}", job.key, e);
+                    (false, e.to_string())
+                }
+            };
+
+            let run = JobRun {
+                finished_at: chrono::Utc::now().timestamp() as u64,
+                ok,
+                message,
+                duration_ms: timer.elapsed().as_millis() as u64,
+            };
+            if let Err(e) = self.db.finish_job(&job.key, &self.instance, &run).await {
+                eprintln!("⚠️  Failed to record run of job '{}': {}", job.key, e);
+            }
+        }
+    }
+
+    /// Every registered job with its schedule and last run
+    pub async fn status(&self) -> Result<Vec<JobStatus>> {
+        let mut states: HashMap<String, JobState> = self.db.load_job_states().await?
+            .into_iter()
+            .map(|s| (s.name.clone(), s))
+            .collect();
+        let now = chrono::Utc::now().timestamp() as u64;
+        let next_runs = self.next_runs.lock().unwrap().clone();
+
+        Ok(self.jobs.iter().map(|job| {
+            let state = states.remove(&job.key).unwrap_or_else(|| JobState { name: job.key.clone(), ..Default::default() });
+            JobStatus {
+                schedule: job.schedule.describe(),
+                next_run_at: next_runs.get(&job.key).copied(),
+                running: state.locked_by.is_some() && state.locked_until.is_some_and(|t| t > now),
+                state,
+            }
+        }).collect())
+    }
+}
@@ -0,0 +1,358 @@
+use alloy::primitives::Address;
+use eyre::{eyre, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::db::{AddressLabel, Database};
+use crate::points::{AddressMigration, AllocationConfig, BoostWindow, CompetitionConfig, DailyCap, DelegatedPoints, DisplayPoints, LockTier, PointsRules, RatePeriod, Season, SharedRules, DEFAULT_FORMATION_RATE, DEFAULT_SAGE_RATE};
+
+/// Non-structural settings that can be reloaded without a restart
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SettingsFile {
+    pub sage_rate: Option<f64>,
+    pub formation_rate: Option<f64>,
+    #[serde(default)]
+    pub boosts: Vec<BoostWindow>,
+    #[serde(default)]
+    pub lock_tiers: Vec<LockTier>,
+    #[serde(default)]
+    pub daily_caps: Vec<DailyCap>,
+    #[serde(default)]
+    pub blocklist: Vec<Address>,
+    #[serde(default)]
+    pub delegated_points_to: DelegatedPoints,
+    pub program_end: Option<u64>,
+    #[serde(default)]
+    pub seasons: Vec<Season>,
+    pub allocation: Option<AllocationConfig>,
+    pub competitions: Option<CompetitionConfig>,
+    pub display: Option<DisplayPoints>,
+    #[serde(default)]
+    pub min_deposit: f64,
+}
+
+impl SettingsFile {
+    /// Read settings from a JSON file, defaults when no path is configured
+    pub fn read(path: Option<&str>) -> Result<Self> {
+        match path {
+            Some(path) => Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+/// Build points rules from the settings file and the persisted rate history.
+/// A rate change starts a new rate period effective from now, so points already
+/// earned under the previous rates are not recomputed.
+pub async fn load_rules(db: &Database, path: Option<&str>) -> Result<PointsRules> {
+    let mut settings = SettingsFile::read(path)?;
+
+    // Caps are applied in order and must not overlap
+    settings.daily_caps.sort_by_key(|c| c.start);
+    for cap in &settings.daily_caps {
+        if cap.end <= cap.start || cap.max_points <= 0.0 {
+            return Err(eyre!("Daily cap {} → {} needs end after start and positive max_points", cap.start, cap.end));
+        }
+    }
+    if let Some(pair) = settings.daily_caps.windows(2).find(|pair| pair[1].start < pair[0].end) {
+        return Err(eyre!("Daily caps {} → {} and {} → {} overlap", pair[0].start, pair[0].end, pair[1].start, pair[1].end));
+    }
+    settings.seasons.sort_by_key(|s| s.start);
+    if let Some(season) = settings.seasons.iter().find(|s| s.end <= s.start) {
+        return Err(eyre!("Season '{}' needs end after start", season.name));
+    }
+
+    if let Some(allocation) = &settings.allocation {
+        let max_share_valid = allocation.max_share.is_none_or(|s| s > 0.0 && s <= 1.0);
+        if allocation.total_tokens <= 0.0 || allocation.min_points < 0.0 || !max_share_valid {
+            return Err(eyre!("Allocation needs positive total_tokens, non-negative min_points and max_share in (0, 1]"));
+        }
+    }
+
+    if settings.competitions.as_ref().is_some_and(|c| c.winners == 0 || c.winners > 100) {
+        return Err(eyre!("Competitions need between 1 and 100 winners"));
+    }
+
+    if let Some(display) = &settings.display {
+        if [display.sage, display.formation, display.total].iter().any(|s| !(s.divisor.is_finite() && s.divisor > 0.0)) {
+            return Err(eyre!("Display divisors must be positive"));
+        }
+    }
+
+    if settings.min_deposit < 0.0 {
+        return Err(eyre!("min_deposit must not be negative"));
+    }
+
+    let mut rate_periods = db.load_rate_periods().await?;
+
+    let sage_rate = settings.sage_rate.unwrap_or(DEFAULT_SAGE_RATE);
+    let formation_rate = settings.formation_rate.unwrap_or(DEFAULT_FORMATION_RATE);
+
+    let rates_changed = rate_periods
+        .last()
+        .map(|p| p.sage_rate != sage_rate || p.formation_rate != formation_rate)
+        .unwrap_or(true);
+
+    if rates_changed {
+        // The very first period covers all history
+        let effective_from = if rate_periods.is_empty() {
+            0
+        } else {
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+        };
+        let period = RatePeriod {
+            effective_from,
+            sage_rate,
+            formation_rate,
+        };
+
+        db.save_rate_period(&period).await?;
+        println!("📐 Rates set to SAGE={} FORM={} effective from {}", sage_rate, formation_rate, effective_from);
+        rate_periods.push(period);
+    }
+
+    Ok(PointsRules {
+        rate_periods,
+        boosts: settings.boosts,
+        lock_tiers: settings.lock_tiers,
+        pauses: db.load_pause_windows().await?,
+        daily_caps: settings.daily_caps,
+        blocklist: settings.blocklist.into_iter().collect(),
+        migrations: db.load_address_migrations().await?,
+        delegations: db.load_delegations().await?,
+        delegated_points_to: settings.delegated_points_to,
+        program_end: settings.program_end,
+        seasons: settings.seasons,
+        allocation: settings.allocation,
+        competitions: settings.competitions,
+        display: settings.display,
+        min_deposit: settings.min_deposit,
+        checkpoints: Default::default(), // Built once positions are loaded
+    })
+}
+
+/// Reload rules whenever the process receives SIGHUP
+pub fn spawn_reload_on_sighup(db: Database, path: Option<String>, rules: SharedRules) {
+    tokio::spawn(async move {
+        let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(e) => {
+                eprintln!("⚠️  Failed to install SIGHUP handler: {}", e);
+                return;
+            }
+        };
+
+        while hangup.recv().await.is_some() {
+            println!("🔄 SIGHUP received, reloading settings...");
+
+            match load_rules(&db, path.as_deref()).await {
+                Ok(new_rules) => {
+                    println!("   ✅ Loaded {} rate periods, {} boosts, {} pause windows, {} blocked addresses, {} address migrations",
+                        new_rules.rate_periods.len(), new_rules.boosts.len(),
+                        new_rules.pauses.len(), new_rules.blocklist.len(), new_rules.migrations.len());
+                    if let Some(end) = new_rules.program_end {
+                        println!("   🏁 Program ends at {}", end);
+                    }
+                    *rules.write().unwrap() = new_rules;
+                }
+                Err(e) => eprintln!("   ❌ Failed to reload settings, keeping current ones: {}", e),
+            }
+        }
+    });
+}
+
+/// `pause add <start> <end> [reason]`, `pause list`, `pause remove <id>`
+pub async fn run_pause_command(db: &Database, args: &[String]) -> Result<()> {
+    let usage = "Usage: pause add <start> <end> [reason] | pause list | pause remove <id>";
+
+    match args.first().map(String::as_str) {
+        Some("add") => {
+            let start = args.get(1).and_then(|v| v.parse::<u64>().ok()).ok_or_else(|| eyre!(usage))?;
+            let end = args.get(2).and_then(|v| v.parse::<u64>().ok()).ok_or_else(|| eyre!(usage))?;
+            if end <= start {
+                return Err(eyre!("Pause end must be after its start"));
+            }
+            let reason = args[3.min(args.len())..].join(" ");
+
+            let id = db.add_pause_window(start, end, &reason).await?;
+            println!("⏸️  Added pause window #{} ({} → {})", id, start, end);
+        }
+        Some("list") => {
+            for pause in db.load_pause_windows().await? {
+                println!("  #{:<4} {} → {}  {}", pause.id, pause.start, pause.end, pause.reason);
+            }
+        }
+        Some("remove") => {
+            let id = args.get(1).and_then(|v| v.parse::<i32>().ok()).ok_or_else(|| eyre!(usage))?;
+            if db.remove_pause_window(id).await? {
+                println!("▶️  Removed pause window #{}", id);
+            } else {
+                println!("No pause window #{}", id);
+            }
+        }
+        _ => return Err(eyre!(usage)),
+    }
+
+    println!("Send SIGHUP to the running service to apply the change.");
+    Ok(())
+}
+
+/// `migrate-points <from> <to> [--with-positions] [reason]`, `migrate-points list`
+pub async fn run_migrate_points_command(db: &Database, args: &[String]) -> Result<()> {
+    let usage = "Usage: migrate-points <from> <to> [--with-positions] [reason] | migrate-points list";
+
+    if args.first().map(String::as_str) == Some("list") {
+        for migration in db.load_address_migrations().await? {
+            println!("  #{:<4} {} → {}  positions: {}  {}",
+                migration.id, migration.from, migration.to,
+                if migration.include_positions { "yes" } else { "no" }, migration.reason);
+        }
+        return Ok(());
+    }
+
+    let from = args.first().and_then(|v| Address::from_str(v).ok()).ok_or_else(|| eyre!(usage))?;
+    let to = args.get(1).and_then(|v| Address::from_str(v).ok()).ok_or_else(|| eyre!(usage))?;
+    let include_positions = args.iter().any(|a| a == "--with-positions");
+    let reason = args[2.min(args.len())..]
+        .iter()
+        .filter(|a| *a != "--with-positions")
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if from == to {
+        return Err(eyre!("Source and destination addresses must differ"));
+    }
+
+    // Reject double migrations and cycles so ownership always resolves to one address
+    let rules = PointsRules {
+        migrations: db.load_address_migrations().await?,
+        ..Default::default()
+    };
+    if rules.migrations.iter().any(|m| m.from == from) {
+        return Err(eyre!("{} has already been migrated", from));
+    }
+    if rules.owner(to) == from {
+        return Err(eyre!("Migrating {} to {} would create a cycle", from, to));
+    }
+
+    let migration = AddressMigration {
+        id: 0,
+        from,
+        to,
+        include_positions,
+        reason,
+        migrated_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+    };
+    let id = db.migrate_address(&migration).await?;
+    println!("🔀 Migrated points from {} to {} (#{}, positions: {})",
+        from, to, id, if include_positions { "yes" } else { "no" });
+
+    println!("Send SIGHUP to the running service to apply the change.");
+    Ok(())
+}
+
+/// `label <address> <label> [--category <category>]`, `label show <address>`
+pub async fn run_label_command(db: &Database, args: &[String]) -> Result<()> {
+    let usage = "Usage: label <address> <label> [--category <category>] | label show <address> | label import <file.csv> [--dry-run]";
+
+    if args.first().map(String::as_str) == Some("import") {
+        let path = args.get(1).ok_or_else(|| eyre!(usage))?;
+        return import_labels(db, path, args.iter().any(|a| a == "--dry-run")).await;
+    }
+
+    if args.first().map(String::as_str) == Some("show") {
+        let address = args.get(1).and_then(|v| Address::from_str(v).ok()).ok_or_else(|| eyre!(usage))?;
+        match db.get_address_label(&address).await? {
+            Some(label) => println!("  {}  {}  {}", label.address, label.label, label.category.unwrap_or_default()),
+            None => println!("No label for {}", address),
+        }
+        return Ok(());
+    }
+
+    let address = args.first().and_then(|v| Address::from_str(v).ok()).ok_or_else(|| eyre!(usage))?;
+    let category_at = args.iter().position(|a| a == "--category");
+    let category = category_at.and_then(|i| args.get(i + 1)).cloned();
+    let label = args[1.min(args.len())..category_at.unwrap_or(args.len()).max(1)].join(" ");
+    if label.is_empty() || (category_at.is_some() && category.is_none()) {
+        return Err(eyre!(usage));
+    }
+
+    db.save_address_label(&AddressLabel { address, label, category }).await?;
+    println!("🏷️  Labelled {}", address);
+    Ok(())
+}
+
+// Parse a label CSV: a header naming `address`, `label` and optionally `category` in
+// any order, comma separated, no quoting. Every bad row is reported.
+fn parse_label_csv(contents: &str) -> Result<(Vec<AddressLabel>, Vec<String>)> {
+    let mut lines = contents.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+    let header: Vec<String> = lines.next()
+        .ok_or_else(|| eyre!("CSV file is empty"))?
+        .1
+        .split(',')
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let (address_at, label_at) = column("address").zip(column("label"))
+        .ok_or_else(|| eyre!("CSV header must name 'address' and 'label' columns"))?;
+    let category_at = column("category");
+
+    let mut labels = Vec::new();
+    let mut errors = Vec::new();
+    for (i, line) in lines {
+        let values: Vec<&str> = line.split(',').map(str::trim).collect();
+        if values.len() != header.len() {
+            errors.push(format!("  line {}: expected {} columns, found {}", i + 1, header.len(), values.len()));
+            continue;
+        }
+        let Ok(address) = Address::from_str(values[address_at]) else {
+            errors.push(format!("  line {}: invalid address '{}'", i + 1, values[address_at]));
+            continue;
+        };
+        if values[label_at].is_empty() {
+            errors.push(format!("  line {}: empty label", i + 1));
+            continue;
+        }
+
+        labels.push(AddressLabel {
+            address,
+            label: values[label_at].to_string(),
+            category: category_at.map(|at| values[at]).filter(|c| !c.is_empty()).map(str::to_string),
+        });
+    }
+
+    Ok((labels, errors))
+}
+
+// `label import <file.csv> [--dry-run]`: all rows are applied in one transaction, or
+// none if any row is invalid
+async fn import_labels(db: &Database, path: &str, dry_run: bool) -> Result<()> {
+    let contents = std::fs::read_to_string(path)?;
+    let (labels, errors) = parse_label_csv(&contents)?;
+    if !errors.is_empty() {
+        for error in errors.iter().take(20) {
+            eprintln!("{}", error);
+        }
+        return Err(eyre!("{} rows are invalid, no labels were imported", errors.len()));
+    }
+
+    // A later row for the same address wins, like applying them one by one
+    let rows = labels.len();
+    let mut by_address: HashMap<Address, AddressLabel> = HashMap::new();
+    for label in labels {
+        by_address.insert(label.address, label);
+    }
+    let labels: Vec<AddressLabel> = by_address.into_values().collect();
+
+    println!("🏷️  {} rows read, {} addresses", rows, labels.len());
+    if dry_run || labels.is_empty() {
+        return Ok(());
+    }
+
+    db.save_address_labels(&labels, path).await?;
+    println!("✅ Labelled {} addresses from {}", labels.len(), path);
+    Ok(())
+}
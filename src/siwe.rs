@@ -0,0 +1,154 @@
+use alloy::hex;
+use alloy::primitives::{Address, PrimitiveSignature, B256};
+use chrono::DateTime;
+use eyre::{eyre, Result};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+// Seconds a nonce from `/auth/nonce` can be signed within
+const NONCE_TTL_SECS: u64 = 600;
+
+const PREAMBLE_SUFFIX: &str = " wants you to sign in with your Ethereum account:";
+
+/// Fields of an EIP-4361 (Sign-In with Ethereum) message this server checks
+#[derive(Debug)]
+struct SiweMessage {
+    domain: String,
+    address: Address,
+    version: String,
+    nonce: String,
+    issued_at: u64,
+    expiration_time: Option<u64>,
+    not_before: Option<u64>,
+}
+
+fn parse_time(value: &str) -> Result<u64> {
+    Ok(DateTime::parse_from_rfc3339(value)?.timestamp().max(0) as u64)
+}
+
+impl SiweMessage {
+    fn parse(message: &str) -> Result<Self> {
+        let mut lines = message.lines();
+        let domain = lines.next()
+            .and_then(|line| line.strip_suffix(PREAMBLE_SUFFIX))
+            .ok_or_else(|| eyre!("not a Sign-In with Ethereum message"))?;
+        // Newer wallets prefix the domain with its scheme
+        let domain = domain.split_once("://").map_or(domain, |(_, rest)| rest);
+        let address = lines.next()
+            .ok_or_else(|| eyre!("missing address"))
+            .and_then(|line| Address::from_str(line.trim()).map_err(|_| eyre!("invalid address")))?;
+
+        let mut fields: HashMap<&str, &str> = HashMap::new();
+        for line in lines {
+            if let Some((key, value)) = line.split_once(": ") {
+                fields.entry(key).or_insert(value.trim());
+            }
+        }
+        let field = |key: &str| fields.get(key).copied().ok_or_else(|| eyre!("missing {}", key));
+
+        Ok(Self {
+            domain: domain.to_string(),
+            address,
+            version: field("Version")?.to_string(),
+            nonce: field("Nonce")?.to_string(),
+            issued_at: parse_time(field("Issued At")?)?,
+            expiration_time: fields.get("Expiration Time").map(|v| parse_time(v)).transpose()?,
+            not_before: fields.get("Not Before").map(|v| parse_time(v)).transpose()?,
+        })
+    }
+}
+
+/// A signed-in address and when its session ends
+#[derive(Debug, Clone, Copy)]
+pub struct Session {
+    pub address: Address,
+    pub expires_at: u64,
+}
+
+/// Sign-In with Ethereum for user-owned settings. Nonces and sessions are kept in
+/// memory, so users sign in again after a restart.
+#[derive(Clone)]
+pub struct SiweAuth {
+    domain: String,   // Must match the domain in signed messages
+    session_ttl: u64, // Seconds
+    nonces: Arc<Mutex<HashMap<String, u64>>>,      // Nonce -> issued at
+    sessions: Arc<Mutex<HashMap<String, Session>>>, // Bearer token -> session
+}
+
+impl SiweAuth {
+    /// Sign-in for `SIWE_DOMAIN` with sessions lasting `session_ttl` seconds, None when unset
+    pub fn from_env(session_ttl: u64) -> Option<Self> {
+        let domain = std::env::var("SIWE_DOMAIN").ok().filter(|d| !d.is_empty())?;
+
+        Some(Self {
+            domain,
+            session_ttl,
+            nonces: Arc::new(Mutex::new(HashMap::new())),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// Fresh single-use nonce to embed in the message
+    pub fn issue_nonce(&self) -> String {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let nonce = hex::encode(&B256::random()[..16]);
+
+        let mut nonces = self.nonces.lock().unwrap();
+        nonces.retain(|_, issued| now.saturating_sub(*issued) < NONCE_TTL_SECS);
+        nonces.insert(nonce.clone(), now);
+        nonce
+    }
+
+    /// Verify a signed message and start a session for its address, returning the bearer token
+    pub fn sign_in(&self, message: &str, signature: &str) -> Result<(String, Session)> {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let parsed = SiweMessage::parse(message)?;
+
+        if parsed.domain != self.domain {
+            return Err(eyre!("message is for {}, not {}", parsed.domain, self.domain));
+        }
+        if parsed.version != "1" {
+            return Err(eyre!("unsupported message version {}", parsed.version));
+        }
+        if parsed.expiration_time.is_some_and(|t| t <= now) {
+            return Err(eyre!("message has expired"));
+        }
+        if parsed.not_before.is_some_and(|t| t > now) || parsed.issued_at > now + 60 {
+            return Err(eyre!("message is not valid yet"));
+        }
+
+        let signature = PrimitiveSignature::from_str(signature).map_err(|_| eyre!("malformed signature"))?;
+        let signer = signature.recover_address_from_msg(message.as_bytes()).map_err(|_| eyre!("invalid signature"))?;
+        if signer != parsed.address {
+            return Err(eyre!("signature is not from {}", parsed.address));
+        }
+
+        // Consume the nonce last, so a rejected attempt doesn't burn it
+        let issued = self.nonces.lock().unwrap().remove(&parsed.nonce);
+        if issued.is_none_or(|issued| now.saturating_sub(issued) >= NONCE_TTL_SECS) {
+            return Err(eyre!("unknown or expired nonce"));
+        }
+
+        let session = Session {
+            address: parsed.address,
+            expires_at: parsed.expiration_time.map_or(now + self.session_ttl, |t| t.min(now + self.session_ttl)),
+        };
+        let token = hex::encode(B256::random());
+
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, s| s.expires_at > now);
+        sessions.insert(token.clone(), session);
+        Ok((token, session))
+    }
+
+    /// Session of a bearer token, if it is still valid
+    pub fn session(&self, token: &str) -> Option<Session> {
+        let now = chrono::Utc::now().timestamp() as u64;
+        self.sessions.lock().unwrap().get(token).copied().filter(|s| s.expires_at > now)
+    }
+}
@@ -0,0 +1,182 @@
+use alloy::primitives::keccak256;
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::db::{Database, EventData, StoredEvent};
+use crate::points::{AddressMigration, Delegation, PauseWindow, RatePeriod};
+use crate::Position;
+
+// Bumped whenever the archive layout changes. 2 added delegations.
+const SNAPSHOT_VERSION: u32 = 2;
+
+/// Full indexer state in a single JSON archive
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub created_at: u64,
+    pub last_processed_block: Option<u64>, // Indexer cursor
+    pub config_hash: Option<String>,       // keccak256 of the settings file in use
+    pub positions: Vec<Position>,
+    pub events: Vec<StoredEvent>,
+    pub rate_periods: Vec<RatePeriod>,
+    pub pause_windows: Vec<PauseWindow>,
+    pub address_migrations: Vec<AddressMigration>,
+    #[serde(default)] // Not in version 1
+    pub delegations: Vec<Delegation>,
+    #[serde(default)]
+    pub bootstrap_block: Option<u64>, // Set when the source started from a view bootstrap
+}
+
+// Hash of the settings file, so a restore can tell whether it runs with the same config
+fn config_hash(settings_path: Option<&str>) -> Result<Option<String>> {
+    match settings_path {
+        Some(path) => Ok(Some(keccak256(std::fs::read(path)?).to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Capture the current state of the database
+pub async fn create(db: &Database, settings_path: Option<&str>) -> Result<Snapshot> {
+    Ok(Snapshot {
+        version: SNAPSHOT_VERSION,
+        created_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+        last_processed_block: db.get_last_processed_block().await?,
+        config_hash: config_hash(settings_path)?,
+        positions: db.fetch_positions(None).await?,
+        events: db.load_events().await?,
+        rate_periods: db.load_rate_periods().await?,
+        pause_windows: db.load_pause_windows().await?,
+        address_migrations: db.load_address_migrations().await?,
+        delegations: db.load_delegations().await?,
+        bootstrap_block: db.get_bootstrap_block().await?,
+    })
+}
+
+/// Load a snapshot into an empty database
+pub async fn restore(db: &Database, snapshot: &Snapshot) -> Result<()> {
+    if snapshot.version == 0 || snapshot.version > SNAPSHOT_VERSION {
+        return Err(eyre!("Unsupported snapshot version {} (expected {})", snapshot.version, SNAPSHOT_VERSION));
+    }
+    if snapshot.version < 2 {
+        println!("⚠️  Snapshot version {} has no delegations, points of delegated positions stay with their stakers", snapshot.version);
+    }
+
+    // Merging two histories would double count, so only fresh databases are accepted
+    if db.get_last_processed_block().await?.is_some() || !db.load_events().await?.is_empty() {
+        return Err(eyre!("Target database already has indexed state, restore into an empty database"));
+    }
+
+    for period in &snapshot.rate_periods {
+        db.save_rate_period(period).await?;
+    }
+    for pause in &snapshot.pause_windows {
+        db.add_pause_window(pause.start, pause.end, &pause.reason).await?;
+    }
+    for migration in &snapshot.address_migrations {
+        db.migrate_address(migration).await?;
+    }
+    for delegation in &snapshot.delegations {
+        db.save_delegation(delegation).await?;
+    }
+    if let Some(block) = snapshot.bootstrap_block {
+        db.set_bootstrap_block(block).await?;
+    }
+
+    for position in &snapshot.positions {
+        db.save_position(position).await?;
+    }
+    for event in &snapshot.events {
+        // Events are in chain order, so the first deposit is the staker's first-seen time
+        if event.event_type == "Deposit" {
+            db.record_staker(&event.user, event.timestamp, event.block_number).await?;
+        }
+        db.save_event(EventData {
+            event_type: event.event_type.clone(),
+            user: event.user,
+            nonce: Some(event.nonce),
+            amount: event.amount,
+            unlocks_at: event.unlocks_at,
+            lock_duration: event.lock_duration,
+            block_number: event.block_number,
+            log_index: event.log_index,
+            tx_hash: event.tx_hash.clone(),
+            timestamp: event.timestamp,
+        }).await?;
+    }
+
+    // The cursor goes last so an interrupted restore is not mistaken for a complete one
+    if let Some(block) = snapshot.last_processed_block {
+        db.update_last_processed_block(block).await?;
+    }
+
+    Ok(())
+}
+
+/// `snapshot create <file>`, `snapshot restore <file>`
+pub async fn run_snapshot_command(db: &Database, settings_path: Option<&str>, args: &[String]) -> Result<()> {
+    let usage = "Usage: snapshot create <file> | snapshot restore <file>";
+    let path = args.get(1).ok_or_else(|| eyre!(usage))?;
+
+    match args.first().map(String::as_str) {
+        Some("create") => {
+            let snapshot = create(db, settings_path).await?;
+            std::fs::write(path, serde_json::to_string(&snapshot)?)?;
+            println!("📦 Wrote snapshot to {} ({} positions, {} events, cursor {:?})",
+                path, snapshot.positions.len(), snapshot.events.len(), snapshot.last_processed_block);
+        }
+        Some("restore") => {
+            let snapshot: Snapshot = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+            restore(db, &snapshot).await?;
+            println!("📥 Restored snapshot from {} ({} positions, {} events, cursor {:?})",
+                path, snapshot.positions.len(), snapshot.events.len(), snapshot.last_processed_block);
+
+            let current_hash = config_hash(settings_path)?;
+            if current_hash != snapshot.config_hash {
+                println!("⚠️  Settings differ from the snapshot's ({:?} → {:?}), new rates apply from the next start",
+                    snapshot.config_hash, current_hash);
+            }
+        }
+        _ => return Err(eyre!(usage)),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::Address;
+
+    #[tokio::test]
+    async fn delegations_survive_a_restore() {
+        let db = Database::in_memory();
+        db.save_delegation(&Delegation {
+            delegator: Address::repeat_byte(0xa1),
+            delegatee: Address::repeat_byte(0xb0),
+            block_number: 10,
+            tx_hash: "0xaa".into(),
+            timestamp: 1_000,
+        }).await.unwrap();
+
+        let snapshot = create(&db, None).await.unwrap();
+        let snapshot: Snapshot = serde_json::from_slice(&serde_json::to_vec(&snapshot).unwrap()).unwrap();
+        let restored = Database::in_memory();
+        restore(&restored, &snapshot).await.unwrap();
+
+        let delegations = restored.load_delegations().await.unwrap();
+        assert_eq!(delegations.len(), 1);
+        assert_eq!(delegations[0].delegatee, Address::repeat_byte(0xb0));
+    }
+
+    #[tokio::test]
+    async fn version_1_snapshots_still_restore() {
+        let snapshot = create(&Database::in_memory(), None).await.unwrap();
+        let mut json = serde_json::to_value(&snapshot).unwrap();
+        json["version"] = 1.into();
+        json.as_object_mut().unwrap().remove("delegations");
+
+        let snapshot: Snapshot = serde_json::from_value(json).unwrap();
+        restore(&Database::in_memory(), &snapshot).await.unwrap();
+    }
+}
@@ -0,0 +1,287 @@
+use alloy::primitives::{Address, B256};
+use alloy::rpc::types::{Filter, Log};
+use alloy::transports::{RpcError, TransportError};
+use async_trait::async_trait;
+use eyre::{eyre, Result};
+
+use crate::chain::ChainProvider;
+use crate::MAX_BLOCK_RANGE;
+
+// Results at which providers cap `eth_getLogs`, some of them without an error
+const LOG_RESULT_CAP: usize = 10_000;
+
+// Error messages providers use for a range or result set that is too large
+const RANGE_ERROR_MESSAGES: &[&str] = &[
+    "query returned more than",  // Infura, Cloudflare
+    "more than 10000 results",
+    "log response size exceeded", // Alchemy
+    "response size should not",
+    "block range",                // "block range too large", "exceed maximum block range", ...
+    "range is too large",
+    "range too large",
+    "limited to a 10,000",        // QuickNode
+    "too many results",
+    "query timeout exceeded",
+];
+
+/// Whether a failed `eth_getLogs` call might succeed on a smaller block range
+pub fn is_range_too_large(error: &eyre::Report) -> bool {
+    // -32005: limit exceeded
+    if let Some(RpcError::ErrorResp(payload)) = error.downcast_ref::<TransportError>() {
+        if payload.code == -32005 {
+            return true;
+        }
+    }
+    let message = error.to_string().to_lowercase();
+    RANGE_ERROR_MESSAGES.iter().any(|m| message.contains(m))
+}
+
+/// Sort logs by (block_number, log_index) so same-block events, e.g. an
+/// InitiateWithdraw followed by a restake, are applied in the order they happened
+pub fn in_chain_order(mut logs: Vec<Log>) -> Vec<Log> {
+    logs.sort_by_key(|log| (log.block_number.unwrap_or_default(), log.log_index.unwrap_or_default()));
+    logs
+}
+
+/// Ordered contract logs that drive the indexer state machine
+#[async_trait]
+pub trait EventSource: Send + Sync {
+    /// Latest block the source can serve
+    async fn head(&self) -> Result<u64>;
+
+    /// Contract logs emitted in `from..=to`, in chain order
+    async fn logs(&self, from: u64, to: u64) -> Result<Vec<Log>>;
+
+    /// Hash of a block, used to detect reorgs (None when the source cannot tell)
+    async fn block_hash(&self, _number: u64) -> Result<Option<B256>> {
+        Ok(None)
+    }
+
+    /// Widest block range to request at once
+    fn max_block_range(&self) -> u64 {
+        MAX_BLOCK_RANGE
+    }
+}
+
+/// Live logs from the chain through an RPC provider
+pub struct RpcEventSource<C> {
+    chain: C,
+    contract_address: Address,
+}
+
+impl<C: ChainProvider> RpcEventSource<C> {
+    pub fn new(chain: C, contract_address: Address) -> Self {
+        Self { chain, contract_address }
+    }
+}
+
+#[async_trait]
+impl<C: ChainProvider> EventSource for RpcEventSource<C> {
+    async fn head(&self) -> Result<u64> {
+        self.chain.get_block_number().await
+    }
+
+    // Halves ranges the provider rejects as too large, or answers with a capped result
+    // set, until every part is served whole
+    async fn logs(&self, from: u64, to: u64) -> Result<Vec<Log>> {
+        let mut pending = vec![(from, to)];
+        let mut logs = Vec::new();
+
+        while let Some((from, to)) = pending.pop() {
+            let filter = Filter::new()
+                .address(self.contract_address)
+                .from_block(from)
+                .to_block(to);
+
+            let reason = match self.chain.get_logs(&filter).await {
+                Ok(part) if part.len() >= LOG_RESULT_CAP && from < to => format!("{} results, possibly capped", part.len()),
+                Ok(part) => {
+                    if part.len() >= LOG_RESULT_CAP {
+                        eprintln!("⚠️  Block {} returned {} logs, the provider may have capped them", from, part.len());
+                    }
+                    logs.extend(part);
+                    continue;
+                }
+                Err(e) if from < to && is_range_too_large(&e) => e.to_string(),
+                Err(e) => return Err(e),
+            };
+
+            let mid = from + (to - from) / 2;
+            println!("✂️  Splitting blocks {}-{} ({})", from, to, reason);
+            // Left half is popped first, so parts are fetched in order
+            pending.push((mid + 1, to));
+            pending.push((from, mid));
+        }
+
+        Ok(logs)
+    }
+
+    async fn block_hash(&self, number: u64) -> Result<Option<B256>> {
+        Ok(self.chain.get_block(number).await?.map(|block| block.header.hash))
+    }
+}
+
+/// Pre-recorded logs, loaded from an `eth_getLogs`-style JSON array or built in code
+pub struct FileEventSource {
+    logs: Vec<Log>, // Sorted by (block_number, log_index)
+}
+
+impl FileEventSource {
+    /// Fixed set of logs, e.g. a test fixture
+    pub fn new(logs: Vec<Log>) -> Self {
+        Self { logs: in_chain_order(logs) }
+    }
+
+    /// Load logs from a JSON file
+    pub fn open(path: &str) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| eyre!("Failed to read events file {}: {}", path, e))?;
+        Ok(Self::new(serde_json::from_str(&contents)?))
+    }
+
+    /// Block of the first recorded log
+    pub fn first_block(&self) -> Option<u64> {
+        self.logs.first().and_then(|log| log.block_number)
+    }
+}
+
+#[async_trait]
+impl EventSource for FileEventSource {
+    async fn head(&self) -> Result<u64> {
+        Ok(self.logs.last().and_then(|log| log.block_number).unwrap_or_default())
+    }
+
+    async fn logs(&self, from: u64, to: u64) -> Result<Vec<Log>> {
+        Ok(self.logs
+            .iter()
+            .filter(|log| log.block_number.is_some_and(|block| block >= from && block <= to))
+            .cloned()
+            .collect())
+    }
+
+    // Everything is already in memory, no need to batch
+    fn max_block_range(&self) -> u64 {
+        u64::MAX
+    }
+}
+
+/// Serves wide ranges, i.e. historical syncs, from a fast backfill source up to its
+/// height and everything else from the live source, which also reports the head and
+/// block hashes. Falls back to the live source while the backfill source fails.
+pub struct BackfillSource {
+    backfill: Box<dyn EventSource>,
+    live: Box<dyn EventSource>,
+}
+
+impl BackfillSource {
+    pub fn new(backfill: Box<dyn EventSource>, live: Box<dyn EventSource>) -> Self {
+        Self { backfill, live }
+    }
+
+    async fn backfill_logs(&self, from: u64, to: u64) -> Result<Vec<Log>> {
+        let height = self.backfill.head().await?;
+        if from > height {
+            return self.live.logs(from, to).await;
+        }
+
+        let mut logs = self.backfill.logs(from, to.min(height)).await?;
+        if to > height {
+            logs.extend(self.live.logs(height + 1, to).await?);
+        }
+        Ok(logs)
+    }
+}
+
+#[async_trait]
+impl EventSource for BackfillSource {
+    async fn head(&self) -> Result<u64> {
+        self.live.head().await
+    }
+
+    async fn logs(&self, from: u64, to: u64) -> Result<Vec<Log>> {
+        // Live polling stays on the live source
+        if to - from < self.live.max_block_range() {
+            return self.live.logs(from, to).await;
+        }
+
+        match self.backfill_logs(from, to).await {
+            Ok(logs) => Ok(logs),
+            Err(e) => {
+                eprintln!("⚠️  Backfill source failed for blocks {}-{}, using the live source: {}", from, to, e);
+                self.live.logs(from, to).await
+            }
+        }
+    }
+
+    async fn block_hash(&self, number: u64) -> Result<Option<B256>> {
+        self.live.block_hash(number).await
+    }
+
+    fn max_block_range(&self) -> u64 {
+        self.backfill.max_block_range()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::testing::{deposit, tokens, ScriptedChain, CONTRACT};
+
+    const ALICE: Address = Address::repeat_byte(0xa1);
+
+    // One deposit in each of `blocks`
+    fn deposits(blocks: &[u64]) -> Vec<Log> {
+        blocks.iter().enumerate().map(|(i, &block)| deposit(ALICE, i as u64 + 1, tokens(1), block * 12, block, 0)).collect()
+    }
+
+    // Requests that were answered, i.e. not rejected by the range limit
+    fn served(chain: &ScriptedChain, max_range: u64) -> Vec<(u64, u64)> {
+        chain.log_requests().into_iter().filter(|(from, to)| to - from < max_range).collect()
+    }
+
+    #[tokio::test]
+    async fn range_errors_split_the_range_until_it_is_covered_once() {
+        let blocks = [100, 101, 250, 333, 334, 600, 999, 1000];
+        let chain = ScriptedChain::new(1000, deposits(&blocks));
+        chain.limit_range(64);
+        let source = RpcEventSource::new(chain.clone(), CONTRACT);
+
+        let logs = source.logs(100, 1000).await.unwrap();
+
+        // Every log once, in chain order
+        let found: Vec<u64> = logs.iter().map(|log| log.block_number.unwrap()).collect();
+        assert_eq!(found, blocks);
+
+        // Served parts tile [100, 1000] in order, without gaps or overlaps
+        let parts = served(&chain, 64);
+        assert_eq!(parts.first().unwrap().0, 100);
+        assert_eq!(parts.last().unwrap().1, 1000);
+        for pair in parts.windows(2) {
+            assert_eq!(pair[1].0, pair[0].1 + 1);
+        }
+        // Each rejected range was split in two, and nothing was fetched twice
+        assert_eq!(chain.log_requests().len(), 2 * parts.len() - 1);
+    }
+
+    #[tokio::test]
+    async fn a_single_block_over_the_limit_fails() {
+        let chain = ScriptedChain::new(10, deposits(&[5]));
+        chain.limit_range(0);
+        let source = RpcEventSource::new(chain.clone(), CONTRACT);
+
+        let error = source.logs(4, 6).await.unwrap_err();
+
+        assert!(is_range_too_large(&error));
+        assert_eq!(chain.log_requests(), vec![(4, 6), (4, 5), (4, 4)]);
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_not_split() {
+        let chain = ScriptedChain::new(10, deposits(&[5]));
+        chain.fail_logs("connection reset");
+        let source = RpcEventSource::new(chain.clone(), CONTRACT);
+
+        assert!(source.logs(0, 10).await.is_err());
+        assert_eq!(chain.log_requests(), vec![(0, 10)]);
+    }
+}
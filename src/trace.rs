@@ -0,0 +1,248 @@
+use alloy::rpc::json_rpc::ErrorPayload;
+use alloy::rpc::types::{Block, Filter, Log};
+use alloy::transports::{RpcError, TransportError};
+use async_trait::async_trait;
+use eyre::{eyre, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::chain::ChainProvider;
+
+/// Whether chain responses are recorded to a trace file or replayed from one, from
+/// `RPC_RECORD_FILE` or `RPC_REPLAY_FILE` (read by `config::load`)
+#[derive(Debug, Clone)]
+pub enum TraceMode {
+    Record(String),
+    Replay(String),
+}
+
+impl TraceMode {
+    /// Mode for `program`, with its name added to the file name
+    pub fn for_program(&self, program: &str) -> Self {
+        match self {
+            Self::Record(path) => Self::Record(program_path(path, program)),
+            Self::Replay(path) => Self::Replay(program_path(path, program)),
+        }
+    }
+
+    /// Whether the indexer runs from a trace, without reading the chain
+    pub fn is_replay(&self) -> bool {
+        matches!(self, Self::Replay(_))
+    }
+}
+
+// `trace.jsonl` becomes `trace.{program}.jsonl`
+fn program_path(path: &str, program: &str) -> String {
+    let path = Path::new(path);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("trace");
+    let name = match path.extension().and_then(|e| e.to_str()) {
+        Some(extension) => format!("{}.{}.{}", stem, program, extension),
+        None => format!("{}.{}", stem, program),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+/// A chain call and its response, one line of a trace file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TraceEntry {
+    method: String,
+    params: Value,
+    #[serde(default)]
+    result: Value, // Null for errors and missing blocks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<TraceError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TraceError {
+    code: Option<i64>, // JSON-RPC error code, when the node answered with an error
+    message: String,
+}
+
+impl TraceError {
+    fn from_report(error: &eyre::Report) -> Self {
+        match error.downcast_ref::<TransportError>() {
+            Some(RpcError::ErrorResp(payload)) => Self { code: Some(payload.code), message: payload.message.to_string() },
+            _ => Self { code: None, message: error.to_string() },
+        }
+    }
+
+    // Rebuilt so range-splitting and retries see the error they saw when recording
+    fn to_report(&self) -> eyre::Report {
+        match self.code {
+            Some(code) => {
+                let error: TransportError = RpcError::ErrorResp(ErrorPayload { code, message: self.message.clone().into(), data: None });
+                error.into()
+            }
+            None => eyre!("{}", self.message),
+        }
+    }
+}
+
+/// Passes calls through to `inner` and appends every call with its response to a
+/// JSON Lines trace file, for replaying the sync later with [`ReplayChain`]
+pub struct RecordingChain<C> {
+    inner: C,
+    file: Mutex<File>,
+}
+
+impl<C: ChainProvider> RecordingChain<C> {
+    /// Start a new trace at `path`, replacing any previous one
+    pub fn create(inner: C, path: &str) -> Result<Self> {
+        let file = File::create(path).map_err(|e| eyre!("Failed to create trace file {}: {}", path, e))?;
+        Ok(Self { inner, file: Mutex::new(file) })
+    }
+
+    // A broken trace only affects the recording, so indexing goes on
+    fn record<T: Serialize>(&self, method: &str, params: Value, response: &Result<T>) {
+        let entry = match response {
+            Ok(result) => serde_json::to_value(result).map(|result| TraceEntry { method: method.to_string(), params, result, error: None }),
+            Err(e) => Ok(TraceEntry { method: method.to_string(), params, result: Value::Null, error: Some(TraceError::from_report(e)) }),
+        };
+        let written = entry.map_err(eyre::Report::from).and_then(|entry| {
+            let mut file = self.file.lock().unwrap();
+            writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+            Ok(file.flush()?)
+        });
+        if let Err(e) = written {
+            eprintln!("⚠️  Failed to record {} in the trace: {}", method, e);
+        }
+    }
+}
+
+#[async_trait]
+impl<C: ChainProvider> ChainProvider for RecordingChain<C> {
+    async fn get_block_number(&self) -> Result<u64> {
+        let response = self.inner.get_block_number().await;
+        self.record("eth_blockNumber", Value::Null, &response);
+        response
+    }
+
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
+        let response = self.inner.get_logs(filter).await;
+        self.record("eth_getLogs", serde_json::to_value(filter)?, &response);
+        response
+    }
+
+    async fn get_block(&self, number: u64) -> Result<Option<Block>> {
+        let response = self.inner.get_block(number).await;
+        self.record("eth_getBlockByNumber", number.into(), &response);
+        response
+    }
+}
+
+#[derive(Default)]
+struct ReplayState {
+    responses: HashMap<String, VecDeque<TraceEntry>>, // By method and params, in recorded order
+    last_served: HashSet<String>, // Calls whose final response was served at least once
+    unserved: usize,
+    finished: bool,
+}
+
+/// Answers chain calls from a trace written by [`RecordingChain`], without network
+/// access. Repeated calls get the recorded responses in order; once a call's responses
+/// run out, the last one is repeated, so the indexer idles at the recorded head.
+pub struct ReplayChain {
+    state: Mutex<ReplayState>,
+}
+
+impl ReplayChain {
+    pub fn open(path: &str) -> Result<Self> {
+        let file = File::open(path).map_err(|e| eyre!("Failed to open trace file {}: {}", path, e))?;
+        let mut state = ReplayState::default();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: TraceEntry = serde_json::from_str(&line)
+                .map_err(|e| eyre!("Invalid trace entry on line {} of {}: {}", index + 1, path, e))?;
+            state.responses.entry(format!("{} {}", entry.method, entry.params)).or_default().push_back(entry);
+            state.unserved += 1;
+        }
+        println!("📼 Loaded {} recorded chain responses from {}", state.unserved, path);
+
+        Ok(Self { state: Mutex::new(state) })
+    }
+
+    fn respond<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let key = format!("{} {}", method, params);
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        let queue = state.responses.get_mut(&key)
+            .ok_or_else(|| eyre!("{} {} is not in the replay trace, the sync diverged from the recording", method, params))?;
+
+        let entry = if queue.len() > 1 {
+            state.unserved -= 1;
+            queue.pop_front()
+        } else {
+            if state.last_served.insert(key.clone()) {
+                state.unserved -= 1;
+            }
+            queue.front().cloned()
+        };
+        let entry = entry.ok_or_else(|| eyre!("Empty replay queue for {}", key))?;
+        if state.unserved == 0 && !state.finished {
+            state.finished = true;
+            println!("📼 Replay trace fully consumed");
+        }
+
+        match entry.error {
+            Some(error) => Err(error.to_report()),
+            None => Ok(serde_json::from_value(entry.result)?),
+        }
+    }
+}
+
+#[async_trait]
+impl ChainProvider for ReplayChain {
+    async fn get_block_number(&self) -> Result<u64> {
+        self.respond("eth_blockNumber", Value::Null)
+    }
+
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
+        self.respond("eth_getLogs", serde_json::to_value(filter)?)
+    }
+
+    async fn get_block(&self, number: u64) -> Result<Option<Block>> {
+        self.respond("eth_getBlockByNumber", number.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{Address, U256};
+
+    use crate::chain::testing::{tokens, CONTRACT};
+    use crate::source::{EventSource, RpcEventSource};
+    use crate::{sync_historical, PointsRules, PointsTracker, PositionStatus};
+
+    const ALICE: Address = Address::repeat_byte(0xa1);
+    const BOB: Address = Address::repeat_byte(0xb0);
+
+    // Recorded from a scripted chain that drops the first `eth_getLogs` call and rejects
+    // ranges over 300 blocks, so the replay has to retry and split like the recording did
+    #[tokio::test(start_paused = true)]
+    async fn replayed_trace_syncs_like_the_recording() {
+        let chain = ReplayChain::open(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/trace.jsonl")).unwrap();
+        let source = RpcEventSource::new(chain, CONTRACT);
+        let mut tracker = PointsTracker::in_memory(PointsRules::default());
+
+        let head = source.head().await.unwrap();
+        let last_block = sync_historical(&source, &mut tracker, 0, head).await.unwrap();
+
+        assert_eq!(last_block, 1_200);
+        let position = |user, nonce: u64| tracker.get_position(&(user, U256::from(nonce))).unwrap();
+        assert_eq!(position(ALICE, 1).status, PositionStatus::Unstaking);
+        assert_eq!(position(BOB, 1).amount, tokens(50));
+        assert_eq!(position(BOB, 2).amount, tokens(25));
+        assert_eq!(position(BOB, 2).block_number, 1_150);
+    }
+}
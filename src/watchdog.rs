@@ -0,0 +1,128 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::sleep;
+
+use crate::alerts::Notifier;
+
+// Seconds between watchdog checks
+const CHECK_INTERVAL_SECS: u64 = 30;
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+/// Indexer progress shared between the monitoring task, the watchdog and `/health`
+pub struct IndexerStatus {
+    last_processed_block: AtomicU64,
+    chain_head: AtomicU64,
+    last_progress: AtomicU64, // Last time the cursor advanced or was at the head
+    caught_up_at: AtomicU64,  // Last time the cursor was at the head
+    stalled: AtomicBool,
+}
+
+/// Point-in-time view of the indexer for health checks
+#[derive(Debug, Serialize)]
+pub struct IndexerHealth {
+    pub last_processed_block: u64,
+    pub chain_head: u64,
+    pub seconds_since_progress: u64,
+    pub stalled: bool,
+}
+
+/// How far indexed data trails the chain, attached to API responses
+#[derive(Debug, Clone, Serialize)]
+pub struct Freshness {
+    pub stale: bool,
+    pub last_indexed_block: u64,
+    pub lag_seconds: u64, // Seconds since the indexer was last at the chain head
+}
+
+impl IndexerStatus {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            last_processed_block: AtomicU64::new(0),
+            chain_head: AtomicU64::new(0),
+            last_progress: AtomicU64::new(now()),
+            caught_up_at: AtomicU64::new(now()),
+            stalled: AtomicBool::new(false),
+        })
+    }
+
+    /// Called by the monitoring loop after every poll
+    pub fn record(&self, last_processed_block: u64, chain_head: u64) {
+        let previous = self.last_processed_block.swap(last_processed_block, Ordering::Relaxed);
+        self.chain_head.store(chain_head, Ordering::Relaxed);
+
+        if last_processed_block > previous || last_processed_block >= chain_head {
+            self.last_progress.store(now(), Ordering::Relaxed);
+        }
+        if last_processed_block >= chain_head {
+            self.caught_up_at.store(now(), Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_stalled(&self) -> bool {
+        self.stalled.load(Ordering::Relaxed)
+    }
+
+    pub fn health(&self) -> IndexerHealth {
+        IndexerHealth {
+            last_processed_block: self.last_processed_block.load(Ordering::Relaxed),
+            chain_head: self.chain_head.load(Ordering::Relaxed),
+            seconds_since_progress: now().saturating_sub(self.last_progress.load(Ordering::Relaxed)),
+            stalled: self.is_stalled(),
+        }
+    }
+
+    /// Lag behind the chain head, stale once it reaches `stale_after_secs` or the
+    /// indexer is stalled
+    pub fn freshness(&self, stale_after_secs: u64) -> Freshness {
+        let lag_seconds = now().saturating_sub(self.caught_up_at.load(Ordering::Relaxed));
+        Freshness {
+            stale: lag_seconds >= stale_after_secs || self.is_stalled(),
+            last_indexed_block: self.last_processed_block.load(Ordering::Relaxed),
+            lag_seconds,
+        }
+    }
+}
+
+/// Stall detection settings, from `STALL_ALERT_MINUTES` (read by `config::load`)
+#[derive(Debug, Clone)]
+pub struct WatchdogConfig {
+    pub stall_after_secs: u64,
+}
+
+/// Flag the indexer as stalled when its cursor has neither advanced nor been at the
+/// chain head for `stall_after_secs`, and alert on the transition in both directions
+pub fn spawn(status: Arc<IndexerStatus>, config: WatchdogConfig, notifier: Notifier) {
+    tokio::spawn(async move {
+        loop {
+            sleep(Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+
+            let health = status.health();
+            let stalled = health.seconds_since_progress >= config.stall_after_secs;
+            if stalled == status.stalled.swap(stalled, Ordering::Relaxed) {
+                continue;
+            }
+
+            let message = if stalled {
+                format!(
+                    "🚨 Points indexer stalled: no progress for {}s, last processed block {} vs chain head {}",
+                    health.seconds_since_progress, health.last_processed_block, health.chain_head
+                )
+            } else {
+                format!("✅ Points indexer recovered at block {}", health.last_processed_block)
+            };
+            eprintln!("{}", message);
+
+            if notifier.is_enabled() {
+                let details = serde_json::json!({ "indexer": status.health() });
+                if let Err(e) = notifier.send(&message, details).await {
+                    eprintln!("⚠️  Failed to send stall alert: {}", e);
+                }
+            }
+        }
+    });
+}